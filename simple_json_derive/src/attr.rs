@@ -0,0 +1,163 @@
+use syn::{ Attribute, Error, Fields, Ident, LitStr, Result };
+
+/// How enum variants are told apart.
+pub enum Tagging {
+    /// `{"Variant": payload}`, or `"Variant"` for unit variants.
+    External,
+    /// `{"tag": "Variant", ...fields}`.
+    Internal(String),
+    /// `{"tag": "Variant", "content": payload}`.
+    Adjacent(String, String),
+    /// Only the payload, variants are tried in order when reading.
+    Untagged,
+}
+
+pub struct Container
+{
+    pub tagging: Tagging,
+}
+
+pub struct Field
+{
+    pub member:  syn::Member,
+    pub name:    String,
+    pub default: bool,
+    pub skip:    bool,
+    pub flatten: bool,
+}
+
+pub struct Variant
+{
+    pub ident:  Ident,
+    pub name:   String,
+    pub fields: Vec<Field>,
+    pub style:  Style,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Style {
+    Unit,
+    Newtype,
+    Tuple,
+    Struct,
+}
+
+pub fn container(attrs: &[Attribute]) -> Result<Container>
+{
+    let mut tag      = None;
+    let mut content  = None;
+    let mut untagged = false;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("json")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("tag") {
+                tag = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("content") {
+                content = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("untagged") {
+                untagged = true;
+            } else {
+                return Err(meta.error("unknown json container attribute"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    let tagging = match (tag, content, untagged) {
+        (None, None, false)           => Tagging::External,
+        (None, None, true)            => Tagging::Untagged,
+        (Some(tag), None, false)      => Tagging::Internal(tag),
+        (Some(tag), Some(content), false) => Tagging::Adjacent(tag, content),
+        (None, Some(_), _) => {
+            return Err(Error::new(proc_macro2::Span::call_site(), "`content` requires `tag`"));
+        },
+        (Some(_), _, true) => {
+            return Err(Error::new(proc_macro2::Span::call_site(), "`untagged` can't be combined with `tag`"));
+        },
+    };
+
+    Ok(Container { tagging })
+}
+
+pub fn fields(fields: &Fields) -> Result<Vec<Field>>
+{
+    let mut result = vec![];
+
+    for (index, field) in fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None        => syn::Member::Unnamed(syn::Index::from(index)),
+        };
+
+        let mut parsed = Field {
+            name:    field.ident.as_ref().map_or_else(|| index.to_string(), unraw),
+            member,
+            default: false,
+            skip:    false,
+            flatten: false,
+        };
+
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("json")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    parsed.name = meta.value()?.parse::<LitStr>()?.value();
+                } else if meta.path.is_ident("default") {
+                    parsed.default = true;
+                } else if meta.path.is_ident("skip") {
+                    parsed.skip = true;
+                } else if meta.path.is_ident("flatten") {
+                    parsed.flatten = true;
+                } else {
+                    return Err(meta.error("unknown json field attribute"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        if field.ident.is_none() && (parsed.flatten || parsed.default || parsed.skip) {
+            return Err(Error::new_spanned(field, "tuple fields only support `rename`"));
+        }
+
+        result.push(parsed);
+    }
+
+    Ok(result)
+}
+
+pub fn variant(variant: &syn::Variant) -> Result<Variant>
+{
+    let mut name = unraw(&variant.ident);
+
+    for attr in variant.attrs.iter().filter(|attr| attr.path().is_ident("json")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                name = meta.value()?.parse::<LitStr>()?.value();
+            } else {
+                return Err(meta.error("unknown json variant attribute"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    let style = match &variant.fields {
+        Fields::Unit => Style::Unit,
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Style::Newtype,
+        Fields::Unnamed(_) => Style::Tuple,
+        Fields::Named(_)   => Style::Struct,
+    };
+
+    Ok(Variant { ident: variant.ident.clone(), name, fields: fields(&variant.fields)?, style })
+}
+
+fn unraw(ident: &Ident) -> String
+{
+    let name = ident.to_string();
+
+    match name.strip_prefix("r#") {
+        Some(name) => name.to_string(),
+        None       => name,
+    }
+}
@@ -0,0 +1,235 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{ Data, DeriveInput, Error, Fields, Result };
+
+use crate::attr::{ self, Field, Style, Tagging };
+
+pub fn expand(input: &DeriveInput) -> Result<TokenStream>
+{
+    let container = attr::container(&input.attrs)?;
+    let name      = &input.ident;
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            crate::rejects_tagging(&container, input)?;
+
+            let style = match &data.fields {
+                Fields::Unit => Style::Unit,
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Style::Newtype,
+                Fields::Unnamed(_) => Style::Tuple,
+                Fields::Named(_)   => Style::Struct,
+            };
+
+            construct(&quote!(Self), style, &attr::fields(&data.fields)?, &quote!(json))
+        },
+        Data::Enum(data) => {
+            let mut variants = vec![];
+
+            for variant in &data.variants {
+                let variant = attr::variant(variant)?;
+
+                if let (Tagging::Internal(tag), Style::Tuple) = (&container.tagging, variant.style) {
+                    let message = format!("variant `{}` can't be internally tagged by `{}`", variant.ident, tag);
+                    return Err(Error::new_spanned(&variant.ident, message));
+                }
+
+                variants.push(variant);
+            }
+
+            enum_body(&container.tagging, name, &variants)
+        },
+        Data::Union(_) => return Err(Error::new_spanned(name, "unions are not supported")),
+    };
+
+    let generics = crate::bounded(&input.generics, quote!(::simple_json::convert::FromJson));
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::simple_json::convert::FromJson for #name #type_generics #where_clause {
+            fn from_json(json: &::simple_json::Json) -> ::std::result::Result<Self, ::simple_json::convert::ConvertError> {
+                #body
+            }
+        }
+    })
+}
+
+// An expression building `path` out of `source`, it evaluates to a `Result`.
+fn construct(path: &TokenStream, style: Style, fields: &[Field], source: &TokenStream) -> TokenStream
+{
+    match style {
+        Style::Unit => quote! {
+            <() as ::simple_json::convert::FromJson>::from_json(#source).map(|_| #path)
+        },
+        Style::Newtype => quote! {
+            ::simple_json::convert::FromJson::from_json(#source).map(#path)
+        },
+        Style::Tuple => {
+            let length   = fields.len();
+            let elements = (0..length).map(|index| quote!(::simple_json::convert::__element(elements, #index)?));
+
+            quote! {{
+                let elements = ::simple_json::convert::__elements(#source, #length)?;
+                ::std::result::Result::Ok(#path(#(#elements),*))
+            }}
+        },
+        Style::Struct => {
+            let values = fields.iter().map(|field| {
+                let member = &field.member;
+                let name   = &field.name;
+
+                let value = if field.skip {
+                    quote!(::std::default::Default::default())
+                } else if field.flatten {
+                    quote!(::simple_json::convert::FromJson::from_json(#source)?)
+                } else if field.default {
+                    quote!(::simple_json::convert::__field_or_default(members, #name)?)
+                } else {
+                    quote!(::simple_json::convert::__field(members, #name)?)
+                };
+
+                quote!(#member: #value)
+            });
+
+            quote! {{
+                let members = ::simple_json::convert::__members(#source, "object")?;
+                ::std::result::Result::Ok(#path { #(#values),* })
+            }}
+        },
+    }
+}
+
+// Wraps `expression` in a closure, so `?` can be used inside of it.
+fn attempt(expression: TokenStream) -> TokenStream
+{
+    quote! {
+        (|| -> ::std::result::Result<Self, ::simple_json::convert::ConvertError> { #expression })()
+    }
+}
+
+fn enum_body(tagging: &Tagging, name: &syn::Ident, variants: &[attr::Variant]) -> TokenStream
+{
+    let path = |variant: &attr::Variant| {
+        let ident = &variant.ident;
+        quote!(Self::#ident)
+    };
+
+    match tagging {
+        Tagging::External => {
+            let units = variants.iter().filter(|variant| variant.style == Style::Unit).map(|variant| {
+                let name = &variant.name;
+                let path = path(variant);
+                quote!(#name => ::std::result::Result::Ok(#path),)
+            });
+
+            let others = variants.iter().map(|variant| {
+                let name  = &variant.name;
+                let value = attempt(construct(&path(variant), variant.style, &variant.fields, &quote!(payload)));
+                quote!(#name => #value.map_err(|error| error.at(#name)),)
+            });
+
+            quote! {
+                if let ::std::option::Option::Some(variant) = json.as_str() {
+                    return match variant {
+                        #(#units)*
+                        variant => ::simple_json::convert::__unknown_variant(variant),
+                    };
+                }
+
+                let members = ::simple_json::convert::__members(json, "string or object")?;
+
+                if members.len() != 1 {
+                    return ::std::result::Result::Err(::simple_json::convert::ConvertError::new(
+                        "expected an object with a single member",
+                    ));
+                }
+
+                let (variant, payload) = members.iter().next().unwrap();
+
+                match variant.as_str() {
+                    #(#others)*
+                    variant => ::simple_json::convert::__unknown_variant(variant),
+                }
+            }
+        },
+        Tagging::Internal(tag) => {
+            let arms = variants.iter().map(|variant| {
+                let name  = &variant.name;
+                let value = match variant.style {
+                    Style::Unit => {
+                        let path = path(variant);
+                        quote!(::std::result::Result::Ok(#path))
+                    },
+                    _ => construct(&path(variant), variant.style, &variant.fields, &quote!(json)),
+                };
+
+                quote!(#name => #value,)
+            });
+
+            quote! {
+                let members = ::simple_json::convert::__members(json, "object")?;
+
+                match ::simple_json::convert::__tag(members, #tag)? {
+                    #(#arms)*
+                    variant => ::simple_json::convert::__unknown_variant(variant),
+                }
+            }
+        },
+        Tagging::Adjacent(tag, content) => {
+            let arms = variants.iter().map(|variant| {
+                let name = &variant.name;
+
+                match variant.style {
+                    Style::Unit => {
+                        let path = path(variant);
+                        quote!(#name => ::std::result::Result::Ok(#path),)
+                    },
+                    _ => {
+                        let value = attempt(construct(&path(variant), variant.style, &variant.fields, &quote!(payload)));
+
+                        quote! {
+                            #name => {
+                                let payload = match members.get(#content) {
+                                    ::std::option::Option::Some(payload) => payload,
+                                    ::std::option::Option::None => {
+                                        return ::std::result::Result::Err(::simple_json::convert::ConvertError::new(
+                                            ::std::format!("missing field `{}`", #content),
+                                        ));
+                                    },
+                                };
+
+                                #value.map_err(|error| error.at(#content))
+                            },
+                        }
+                    },
+                }
+            });
+
+            quote! {
+                let members = ::simple_json::convert::__members(json, "object")?;
+
+                match ::simple_json::convert::__tag(members, #tag)? {
+                    #(#arms)*
+                    variant => ::simple_json::convert::__unknown_variant(variant),
+                }
+            }
+        },
+        Tagging::Untagged => {
+            let attempts = variants.iter().map(|variant| {
+                let value = attempt(construct(&path(variant), variant.style, &variant.fields, &quote!(json)));
+
+                quote! {
+                    if let ::std::result::Result::Ok(value) = #value {
+                        return ::std::result::Result::Ok(value);
+                    }
+                }
+            });
+
+            let message = format!("data did not match any variant of untagged enum {}", name);
+
+            quote! {
+                #(#attempts)*
+                ::std::result::Result::Err(::simple_json::convert::ConvertError::new(#message))
+            }
+        },
+    }
+}
@@ -0,0 +1,60 @@
+//! `#[derive(ToJson, FromJson)]` for `simple_json`.
+//!
+//! Structs become objects, newtype structs their inner value and tuple
+//! structs arrays. Enums are externally tagged unless the container says
+//! otherwise. The `json` attribute accepts:
+//!
+//! - on containers: `tag = "..."`, `content = "..."` and `untagged`;
+//! - on fields: `rename = "..."`, `default`, `skip` and `flatten`;
+//! - on variants: `rename = "..."`.
+
+extern crate proc_macro;
+
+mod attr;
+mod from_json;
+mod to_json;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as Tokens;
+use syn::{ parse_macro_input, parse_quote, DeriveInput, Generics };
+
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream
+{
+    let input = parse_macro_input!(input as DeriveInput);
+
+    to_json::expand(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream
+{
+    let input = parse_macro_input!(input as DeriveInput);
+
+    from_json::expand(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+// Requires every type parameter to implement `bound`.
+fn bounded(generics: &Generics, bound: Tokens) -> Generics
+{
+    let mut generics = generics.clone();
+
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+
+    generics
+}
+
+fn rejects_tagging(container: &attr::Container, input: &DeriveInput) -> syn::Result<()>
+{
+    match container.tagging {
+        attr::Tagging::External => Ok(()),
+        _ => Err(syn::Error::new_spanned(&input.ident, "tagging attributes only apply to enums")),
+    }
+}
+
+fn binding(index: usize) -> syn::Ident
+{
+    quote::format_ident!("__field{}", index)
+}
@@ -0,0 +1,190 @@
+use proc_macro2::TokenStream;
+use quote::{ quote, ToTokens };
+use syn::{ Data, DeriveInput, Error, Fields, Result };
+
+use crate::attr::{ self, Field, Style, Tagging };
+
+pub fn expand(input: &DeriveInput) -> Result<TokenStream>
+{
+    let container = attr::container(&input.attrs)?;
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            crate::rejects_tagging(&container, input)?;
+            struct_body(&data.fields)?
+        },
+        Data::Enum(data) => {
+            let mut arms = vec![];
+
+            for variant in &data.variants {
+                arms.push(variant_arm(&container.tagging, &attr::variant(variant)?)?);
+            }
+
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        },
+        Data::Union(_) => return Err(Error::new_spanned(&input.ident, "unions are not supported")),
+    };
+
+    let name     = &input.ident;
+    let generics = crate::bounded(&input.generics, quote!(::simple_json::convert::ToJson));
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::simple_json::convert::ToJson for #name #type_generics #where_clause {
+            fn to_json(&self) -> ::simple_json::Json {
+                #body
+            }
+        }
+    })
+}
+
+fn struct_body(fields: &Fields) -> Result<TokenStream>
+{
+    let parsed = attr::fields(fields)?;
+
+    Ok(match fields {
+        Fields::Named(_) => {
+            let members = members(&parsed, |field| {
+                let member = &field.member;
+                quote!(&self.#member)
+            });
+
+            quote! {
+                let mut members = ::std::vec::Vec::new();
+                #members
+                ::simple_json::convert::__object(members)
+            }
+        },
+        Fields::Unnamed(_) if parsed.len() == 1 => quote! {
+            ::simple_json::convert::ToJson::to_json(&self.0)
+        },
+        Fields::Unnamed(_) => {
+            let elements = parsed.iter().map(|field| {
+                let member = &field.member;
+                quote!(::simple_json::convert::ToJson::to_json(&self.#member))
+            });
+
+            quote!(::simple_json::Json::from(::std::vec![#(#elements),*]))
+        },
+        Fields::Unit => quote!(::simple_json::Json::from(())),
+    })
+}
+
+// Pushes the fields into a `members` vector, `access` gives a reference to
+// the value of each field.
+fn members<F: Fn(&Field) -> TokenStream>(fields: &[Field], access: F) -> TokenStream
+{
+    let pushes = fields.iter().filter(|field| !field.skip).map(|field| {
+        let value = access(field);
+        let name  = &field.name;
+
+        if field.flatten {
+            quote! {
+                ::simple_json::convert::__flatten(&mut members, ::simple_json::convert::ToJson::to_json(#value));
+            }
+        } else {
+            quote! {
+                members.push((::std::string::String::from(#name), ::simple_json::convert::ToJson::to_json(#value)));
+            }
+        }
+    });
+
+    quote!(#(#pushes)*)
+}
+
+fn variant_arm(tagging: &Tagging, variant: &attr::Variant) -> Result<TokenStream>
+{
+    let ident = &variant.ident;
+    let name  = &variant.name;
+
+    let bindings: Vec<TokenStream> = variant.fields.iter().enumerate().map(|(index, field)| {
+        match field.skip {
+            true  => quote!(_),
+            false => crate::binding(index).into_token_stream(),
+        }
+    }).collect();
+
+    let pattern = match variant.style {
+        Style::Unit => quote!(Self::#ident),
+        Style::Newtype | Style::Tuple => quote!(Self::#ident(#(#bindings),*)),
+        Style::Struct => {
+            let members = variant.fields.iter().map(|field| &field.member);
+            quote!(Self::#ident { #(#members: #bindings),* })
+        },
+    };
+
+    let access = |field: &Field| {
+        let index = variant.fields.iter().position(|candidate| candidate.member == field.member).unwrap();
+        crate::binding(index).into_token_stream()
+    };
+
+    let payload = match variant.style {
+        Style::Unit    => quote!(::simple_json::Json::from(())),
+        Style::Newtype => quote!(::simple_json::convert::ToJson::to_json(__field0)),
+        Style::Tuple   => {
+            let elements = (0..variant.fields.len()).map(crate::binding);
+            quote!(::simple_json::Json::from(::std::vec![#(::simple_json::convert::ToJson::to_json(#elements)),*]))
+        },
+        Style::Struct => {
+            let members = members(&variant.fields, access);
+            quote! {{
+                let mut members = ::std::vec::Vec::new();
+                #members
+                ::simple_json::convert::__object(members)
+            }}
+        },
+    };
+
+    let body = match (tagging, variant.style) {
+        (Tagging::External, Style::Unit) => quote! {
+            ::simple_json::convert::ToJson::to_json(#name)
+        },
+        (Tagging::External, _) => quote! {
+            ::simple_json::convert::__object(::std::vec![(::std::string::String::from(#name), #payload)])
+        },
+        (Tagging::Internal(tag), Style::Tuple) => {
+            let message = format!("variant `{}` can't be internally tagged by `{}`", ident, tag);
+            return Err(Error::new_spanned(ident, message));
+        },
+        (Tagging::Internal(tag), Style::Struct) => {
+            let members = members(&variant.fields, access);
+            quote! {
+                let mut members = ::std::vec![(
+                    ::std::string::String::from(#tag),
+                    ::simple_json::convert::ToJson::to_json(#name),
+                )];
+                #members
+                ::simple_json::convert::__object(members)
+            }
+        },
+        (Tagging::Internal(tag), _) => quote! {
+            let mut members = ::std::vec![(
+                ::std::string::String::from(#tag),
+                ::simple_json::convert::ToJson::to_json(#name),
+            )];
+            ::simple_json::convert::__flatten(&mut members, #payload);
+            ::simple_json::convert::__object(members)
+        },
+        (Tagging::Adjacent(tag, _), Style::Unit) => quote! {
+            ::simple_json::convert::__object(::std::vec![(
+                ::std::string::String::from(#tag),
+                ::simple_json::convert::ToJson::to_json(#name),
+            )])
+        },
+        (Tagging::Adjacent(tag, content), _) => quote! {
+            ::simple_json::convert::__object(::std::vec![
+                (::std::string::String::from(#tag), ::simple_json::convert::ToJson::to_json(#name)),
+                (::std::string::String::from(#content), #payload),
+            ])
+        },
+        (Tagging::Untagged, _) => payload,
+    };
+
+    Ok(quote! {
+        #pattern => { #body },
+    })
+}
@@ -0,0 +1,343 @@
+//! Completion of object keys and values from the document's schema.
+//!
+//! The schema is the local file named by the root `$schema` member, relative
+//! to the document. Remote schemas are never fetched. The cursor's location
+//! is found by scanning the text before it, so completion works while the
+//! document is still invalid.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{ Path, PathBuf };
+
+use simple_json::{ json, Json, JsonPointer };
+
+use crate::protocol::Document;
+
+// Values of the LSP `CompletionItemKind` enumeration.
+const PROPERTY: u64 = 10;
+const VALUE:    u64 = 12;
+
+pub fn completion(document: &Document, uri: &str, offset: usize) -> Json
+{
+    let schema = match load_schema(document, uri) {
+        Some(schema) => schema,
+        None         => return Json::from(vec![]),
+    };
+
+    let Cursor { pointer, target, in_string } = Cursor::scan(&document.text[..offset]);
+    let items = match target {
+        Target::Key(keys) => subschema(&schema, &pointer)
+            .map(|object| keys_of(&schema, object, &keys, in_string))
+            .unwrap_or_default(),
+        Target::Value(pointer) => subschema(&schema, &pointer)
+            .map(|value| values_of(&schema, value, in_string))
+            .unwrap_or_default(),
+    };
+
+    Json::from(items)
+}
+
+// What the cursor is on: a key of the object at `Cursor::pointer`, along
+// with the keys it already has, or the value at a pointer.
+enum Target
+{
+    Key(HashSet<String>),
+    Value(JsonPointer),
+}
+
+struct Cursor
+{
+    pointer:   JsonPointer,
+    target:    Target,
+    in_string: bool,
+}
+
+// An array or object open before the cursor.
+enum Frame
+{
+    Array(usize),
+    Object { key: Option<String>, keys: HashSet<String>, value: bool },
+}
+
+impl Cursor
+{
+    fn scan(text: &str) -> Cursor
+    {
+        let mut frames: Vec<Frame> = vec![];
+        let mut chars = text.char_indices();
+        let mut in_string = false;
+
+        while let Some((start, chr)) = chars.next() {
+            match chr {
+                '{' => frames.push(Frame::Object { key: None, keys: HashSet::new(), value: false }),
+                '[' => frames.push(Frame::Array(0)),
+                '}' | ']' => {
+                    frames.pop();
+                },
+                ',' => match frames.last_mut() {
+                    Some(Frame::Array(index)) => *index += 1,
+                    Some(Frame::Object { key, value, .. }) => {
+                        *key   = None;
+                        *value = false;
+                    },
+                    None => {},
+                },
+                ':' => if let Some(Frame::Object { value, .. }) = frames.last_mut() {
+                    *value = true;
+                },
+                '"' => {
+                    let mut escaped = false;
+                    let end = chars.by_ref().find(|(_, chr)| {
+                        let found = *chr == '"' && !escaped;
+                        escaped = *chr == '\\' && !escaped;
+                        found
+                    });
+
+                    let end = match end {
+                        Some((end, _)) => end,
+                        None => {
+                            in_string = true;
+                            break;
+                        },
+                    };
+
+                    if let Some(Frame::Object { key, keys, value: false }) = frames.last_mut() {
+                        let literal = &text[start..=end];
+                        let name    = Json::parse(literal).ok().and_then(|json| json.as_str().map(str::to_string));
+                        let name    = name.unwrap_or_else(|| literal[1..literal.len() - 1].to_string());
+
+                        keys.insert(name.clone());
+                        *key = Some(name);
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        let mut pointer = JsonPointer::root();
+        for frame in &frames[..frames.len().saturating_sub(1)] {
+            match frame {
+                Frame::Array(index)       => pointer.push(index.to_string()),
+                Frame::Object { key, .. } => pointer.push(key.clone().unwrap_or_default()),
+            }
+        }
+
+        let target = match frames.pop() {
+            Some(Frame::Array(index)) => Target::Value(pointer.child(index.to_string())),
+            Some(Frame::Object { key: Some(key), value: true, .. }) => Target::Value(pointer.child(key)),
+            Some(Frame::Object { keys, .. }) => Target::Key(keys),
+            None => Target::Value(pointer.clone()),
+        };
+
+        Cursor { pointer, target, in_string }
+    }
+}
+
+// Finds the schema from the `$schema` member of the document.
+fn load_schema(document: &Document, uri: &str) -> Option<Json>
+{
+    let location = document.parsed.value.as_object()?.get("$schema")?.as_str()?;
+
+    let path = match location.strip_prefix("file://") {
+        Some(path) => PathBuf::from(percent_decode(path)),
+        None if location.contains("://") => return None,
+        None => {
+            let document = percent_decode(uri.strip_prefix("file://")?);
+            Path::new(&document).parent()?.join(location)
+        },
+    };
+
+    Json::parse(&fs::read_to_string(path).ok()?).ok()
+}
+
+fn percent_decode(text: &str) -> String
+{
+    let mut bytes = vec![];
+    let mut rest  = text.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        let hex = tail.get(..2).and_then(|hex| std::str::from_utf8(hex).ok());
+
+        match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(decoded) if byte == b'%' => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            },
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            },
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+// Follows `$ref`s to the document's own definitions.
+fn dereference<'a>(root: &'a Json, mut schema: &'a Json) -> &'a Json
+{
+    for _ in 0..32 {
+        let reference = match schema.as_object().and_then(|members| members.get("$ref")).and_then(Json::as_str) {
+            Some(reference) => reference,
+            None            => break,
+        };
+
+        let target = reference.strip_prefix('#')
+            .and_then(|pointer| JsonPointer::parse(pointer).ok())
+            .and_then(|pointer| pointer.resolve(root).ok());
+
+        match target {
+            Some(target) => schema = target,
+            None         => break,
+        }
+    }
+
+    schema
+}
+
+// The schema itself and the branches of its `allOf`, `anyOf` and `oneOf`.
+fn branches<'a>(root: &'a Json, schema: &'a Json) -> Vec<&'a Json>
+{
+    let schema = dereference(root, schema);
+    let mut branches = vec![schema];
+
+    for keyword in &["allOf", "anyOf", "oneOf"] {
+        if let Some(Json::Array(subschemas, _)) = schema.as_object().and_then(|members| members.get(*keyword)) {
+            for subschema in subschemas {
+                branches.extend(self::branches(root, subschema));
+            }
+        }
+    }
+
+    branches
+}
+
+// The schema of the value at `pointer`.
+fn subschema<'a>(root: &'a Json, pointer: &JsonPointer) -> Option<&'a Json>
+{
+    let mut schema = root;
+
+    for token in pointer.tokens() {
+        schema = branches(root, schema).into_iter().find_map(|branch| child(branch, token))?;
+    }
+
+    Some(schema)
+}
+
+fn child<'a>(schema: &'a Json, token: &str) -> Option<&'a Json>
+{
+    let members = schema.as_object()?;
+    let index   = token.parse::<usize>().ok();
+
+    if let Some(property) = members.get("properties").and_then(Json::as_object).and_then(|properties| properties.get(token)) {
+        return Some(property);
+    }
+
+    if let Some(index) = index {
+        for keyword in &["prefixItems", "items"] {
+            if let Some(Json::Array(items, _)) = members.get(*keyword) {
+                if let Some(item) = items.get(index) {
+                    return Some(item);
+                }
+            }
+        }
+
+        if let Some(items @ Json::Object(_, _)) = members.get("items") {
+            return Some(items);
+        }
+    }
+
+    match members.get("additionalProperties") {
+        Some(additional @ Json::Object(_, _)) if index.is_none() => Some(additional),
+        _ => None,
+    }
+}
+
+fn keys_of(root: &Json, schema: &Json, present: &HashSet<String>, in_string: bool) -> Vec<Json>
+{
+    let mut properties = vec![];
+
+    for branch in branches(root, schema) {
+        if let Some(members) = branch.as_object().and_then(|members| members.get("properties")).and_then(Json::as_object) {
+            for (key, property) in members {
+                if !present.contains(key) && !properties.iter().any(|(name, _)| name == key) {
+                    properties.push((key.clone(), dereference(root, property)));
+                }
+            }
+        }
+    }
+    properties.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    properties.into_iter()
+        .map(|(key, property)| {
+            let insert = match in_string {
+                true  => key.clone(),
+                false => Json::from(key.as_str()).to_source(),
+            };
+
+            item(&key, PROPERTY, insert, property)
+        })
+        .collect()
+}
+
+fn values_of(root: &Json, schema: &Json, in_string: bool) -> Vec<Json>
+{
+    let mut values: Vec<Json> = vec![];
+
+    for branch in branches(root, schema) {
+        let members = match branch.as_object() {
+            Some(members) => members,
+            None          => continue,
+        };
+
+        if let Some(Json::Array(options, _)) = members.get("enum") {
+            values.extend(options.iter().cloned());
+        }
+        if let Some(value) = members.get("const") {
+            values.push(value.clone());
+        }
+
+        let types = match members.get("type") {
+            Some(Json::String(name, _))  => vec![name.as_str()],
+            Some(Json::Array(names, _))  => names.iter().filter_map(Json::as_str).collect(),
+            _ => vec![],
+        };
+        for name in types {
+            match name {
+                "boolean" => values.extend(vec![json!(true), json!(false)]),
+                "null"    => values.push(json!(null)),
+                _ => {},
+            }
+        }
+    }
+
+    let mut seen  = HashSet::new();
+    let mut items = vec![];
+
+    for value in values {
+        let source = value.compact();
+        if !seen.insert(source.clone()) {
+            continue;
+        }
+
+        // Inside quotes only strings fit, written without them.
+        let insert = match (&value, in_string) {
+            (Json::String(text, _), true) => text.clone(),
+            (_, true)                     => continue,
+            (_, false)                    => source.clone(),
+        };
+
+        items.push(item(&source, VALUE, insert, schema));
+    }
+
+    items
+}
+
+fn item(label: &str, kind: u64, insert: String, schema: &Json) -> Json
+{
+    let members = schema.as_object();
+    let detail  = members.and_then(|members| members.get("type")).map(Json::compact).unwrap_or_default();
+    let text    = members.and_then(|members| members.get("description")).and_then(Json::as_str).unwrap_or("");
+
+    json!({ "label": label, "kind": kind, "detail": detail, "documentation": text, "insertText": insert })
+}
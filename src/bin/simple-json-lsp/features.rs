@@ -0,0 +1,163 @@
+//! Requests answered from the document alone: diagnostics, formatting,
+//! hover, folding ranges and document symbols.
+//!
+//! They all work from the tree and spans the recovering parser made of the
+//! document, so hover, folding ranges and symbols still answer while the
+//! document is invalid. Only formatting needs valid JSON.
+
+use simple_json::format::PrettyOptions;
+use simple_json::span::{ Span, Spans };
+use simple_json::{ json, Json, JsonPointer };
+
+use crate::protocol::{ number, Document };
+
+// Values of the LSP `SymbolKind` enumeration.
+const STRING:  u64 = 15;
+const NUMBER:  u64 = 16;
+const BOOLEAN: u64 = 17;
+const ARRAY:   u64 = 18;
+const OBJECT:  u64 = 19;
+const NULL:    u64 = 21;
+
+const ERROR: u64 = 1;
+
+pub fn diagnostics(document: &Document) -> Json
+{
+    let diagnostics = document.parsed.diagnostics.iter().map(|diagnostic| json!({
+        "range":    (document.range(diagnostic.span.start.offset, diagnostic.span.end.offset)),
+        "severity": ERROR,
+        "source":   "simple-json",
+        "message":  (diagnostic.message.as_str()),
+    }));
+
+    Json::from(diagnostics.collect::<Vec<_>>())
+}
+
+/// Replaces the whole document with its pretty printed form, indented as
+/// the editor asks.
+pub fn formatting(document: &Document, options: &Json) -> Json
+{
+    if !document.parsed.diagnostics.is_empty() {
+        return Json::from(vec![]);
+    }
+    let json = &document.parsed.value;
+
+    let tabs = options.pointer("/insertSpaces").ok().and_then(Json::as_bool) == Some(false);
+    let size = match number(options, "/tabSize") {
+        0    => PrettyOptions::default().indent,
+        size => size,
+    };
+
+    let mut text = json.pretty_with(&PrettyOptions { indent: if tabs { 1 } else { size }, ..PrettyOptions::default() });
+    if tabs {
+        // Strings can't hold a line break, so every line starts with indentation.
+        text = text.lines()
+            .map(|line| {
+                let content = line.trim_start_matches(' ');
+                "\t".repeat(line.len() - content.len()) + content
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    text.push('\n');
+
+    if text == document.text {
+        return Json::from(vec![]);
+    }
+
+    json!([{ "range": (document.range(0, document.text.len())), "newText": text }])
+}
+
+/// Shows the JSON Pointer of the key or value under the cursor.
+pub fn hover(document: &Document, offset: usize) -> Json
+{
+    let spans = &document.parsed.spans;
+
+    let pointer = match spans.at(offset) {
+        Some(pointer) => pointer,
+        None          => return json!(null),
+    };
+
+    let span = match spans.key(&pointer) {
+        Some(span) if span.contains(offset) => span,
+        _ => spans.value(&pointer).unwrap(),
+    };
+
+    let pointer = match pointer.is_root() {
+        true  => "`\"\"` (the whole document)".to_string(),
+        false => format!("`{}`", pointer),
+    };
+
+    json!({
+        "contents": { "kind": "markdown", "value": pointer },
+        "range":    (document.range(span.start.offset, span.end.offset)),
+    })
+}
+
+/// Arrays and objects spanning several lines.
+pub fn folding_ranges(document: &Document) -> Json
+{
+    let (json, spans) = (&document.parsed.value, &document.parsed.spans);
+
+    let mut ranges: Vec<_> = spans.iter()
+        .filter(|(pointer, span)| {
+            let container = matches!(pointer.resolve(json), Ok(Json::Array(_, _)) | Ok(Json::Object(_, _)));
+            container && span.start.line < span.end.line
+        })
+        .map(|(_, span)| (span.start.line - 1, span.end.line - 1))
+        .collect();
+    ranges.sort();
+
+    let ranges = ranges.into_iter().map(|(start, end)| json!({ "startLine": (start as u64), "endLine": (end as u64) }));
+    Json::from(ranges.collect::<Vec<_>>())
+}
+
+/// The members of every object, nested like the document.
+pub fn document_symbols(document: &Document) -> Json
+{
+    let parsed = &document.parsed;
+    Json::from(symbols(&parsed.value, &JsonPointer::root(), &parsed.spans, document))
+}
+
+fn symbols(json: &Json, pointer: &JsonPointer, spans: &Spans, document: &Document) -> Vec<Json>
+{
+    let children: Vec<(JsonPointer, &Json)> = match json {
+        Json::Object(members, _) => members.iter().map(|(key, value)| (pointer.child(key.as_str()), value)).collect(),
+        Json::Array(elements, _) => elements.iter().enumerate().map(|(index, value)| (pointer.child(index.to_string()), value)).collect(),
+        _ => vec![],
+    };
+
+    // Values missing or invalid have no span and no symbol.
+    let mut children: Vec<(JsonPointer, &Json, Span)> = children.into_iter()
+        .filter_map(|(child, value)| spans.value(&child).map(|span| (child, value, span)))
+        .collect();
+    children.sort_by_key(|(_, _, span)| span.start.offset);
+
+    children.into_iter()
+        .map(|(child, value, span)| {
+            let selection = spans.key(&child).unwrap_or(span);
+            let name      = match child.last() {
+                Some("") | None => "\"\"".to_string(),
+                Some(name)      => name.to_string(),
+            };
+
+            let (kind, detail) = match value {
+                Json::Null(_)         => (NULL, value.to_source()),
+                Json::Boolean(_, _)   => (BOOLEAN, value.to_source()),
+                Json::Number(_, _)    => (NUMBER, value.to_source()),
+                Json::String(_, _)    => (STRING, value.to_source()),
+                Json::Array(items, _) => (ARRAY, format!("{} items", items.len())),
+                Json::Object(_, _)    => (OBJECT, String::new()),
+            };
+
+            json!({
+                "name":           name,
+                "detail":         detail,
+                "kind":           kind,
+                "range":          (document.range(selection.start.offset, span.end.offset)),
+                "selectionRange": (document.range(selection.start.offset, selection.end.offset)),
+                "children":       (symbols(value, &child, spans, document)),
+            })
+        })
+        .collect()
+}
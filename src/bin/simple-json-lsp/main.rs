@@ -0,0 +1,213 @@
+//! Language server for JSON documents, built with the `lsp` feature.
+//!
+//! Speaks the Language Server Protocol over the standard input and output,
+//! with the whole document sent on every change. It publishes the parser's
+//! diagnostics and answers formatting, hover, folding range, document symbol
+//! and completion requests.
+
+mod completion;
+mod features;
+mod protocol;
+
+use std::collections::HashMap;
+use std::io::{ self, BufReader };
+use std::panic::{ self, AssertUnwindSafe };
+use std::process;
+
+use simple_json::{ json, Json };
+
+use crate::protocol::{ error, notification, response, string, Document };
+
+// JSON-RPC error codes.
+const PARSE_ERROR:      i64 = -32700;
+const INVALID_REQUEST:  i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INTERNAL_ERROR:   i64 = -32603;
+
+#[derive(Default)]
+struct Server
+{
+    documents: HashMap<String, Document>,
+    shutdown:  bool,
+    // Messages to send once the current one is handled.
+    outgoing:  Vec<Json>,
+}
+
+fn main()
+{
+    let stdin  = io::stdin();
+    let stdout = io::stdout();
+    let mut input  = BufReader::new(stdin.lock());
+    let mut output = stdout.lock();
+
+    let mut server = Server::default();
+
+    loop {
+        let body = match protocol::read(&mut input) {
+            Ok(Some(body)) => body,
+            Ok(None)       => break,
+            Err(error) => {
+                eprintln!("simple-json-lsp: {}", error);
+                process::exit(1);
+            },
+        };
+
+        let message = String::from_utf8(body)
+            .map_err(|error| error.to_string())
+            .and_then(|body| Json::parse(&body).map_err(|error| error.to_string()));
+
+        let exit = match message {
+            Ok(message) => server.handle_safely(&message),
+            Err(parse)  => {
+                server.outgoing.push(error(json!(null), PARSE_ERROR, &parse));
+                false
+            },
+        };
+
+        for message in server.outgoing.drain(..) {
+            if protocol::write(&mut output, &message).is_err() {
+                process::exit(1);
+            }
+        }
+
+        if exit {
+            process::exit(if server.shutdown { 0 } else { 1 });
+        }
+    }
+}
+
+impl Server
+{
+    // Handles a message, a panic fails that message only: the messages it
+    // queued are dropped and a request gets an internal error.
+    fn handle_safely(&mut self, message: &Json) -> bool
+    {
+        match panic::catch_unwind(AssertUnwindSafe(|| self.handle(message))) {
+            Ok(exit) => exit,
+            Err(_) => {
+                self.outgoing.clear();
+                if let Ok(id) = message.pointer("/id") {
+                    self.outgoing.push(error(id.clone(), INTERNAL_ERROR, "internal error"));
+                }
+                false
+            },
+        }
+    }
+
+    // Handles a request or a notification, true when the server must exit.
+    fn handle(&mut self, message: &Json) -> bool
+    {
+        let id     = message.pointer("/id").ok().cloned();
+        let params = message.pointer("/params").ok().cloned().unwrap_or(json!(null));
+
+        let method = match string(message, "/method") {
+            Some(method) => method,
+            None => {
+                // Responses to requests the server never sends are ignored.
+                if let Some(id) = id {
+                    if message.pointer("/result").is_err() && message.pointer("/error").is_err() {
+                        self.outgoing.push(error(id, INVALID_REQUEST, "missing method"));
+                    }
+                }
+                return false;
+            },
+        };
+
+        let result = match method {
+            "initialize" => Some(initialize()),
+            "shutdown" => {
+                self.shutdown = true;
+                Some(json!(null))
+            },
+            "exit" => return true,
+            "textDocument/didOpen" => {
+                let text = string(&params, "/textDocument/text").unwrap_or("");
+                self.open(&params, text.to_string());
+                None
+            },
+            "textDocument/didChange" => {
+                // Full synchronization, the last change holds the whole text.
+                let changes = params.pointer("/contentChanges").ok().and_then(Json::as_array).unwrap_or(&[]);
+                if let Some(text) = changes.last().and_then(|change| string(change, "/text")) {
+                    self.open(&params, text.to_string());
+                }
+                None
+            },
+            "textDocument/didClose" => {
+                if let Some(uri) = string(&params, "/textDocument/uri") {
+                    self.documents.remove(uri);
+                    self.outgoing.push(notification("textDocument/publishDiagnostics", json!({ "uri": uri, "diagnostics": [] })));
+                }
+                None
+            },
+            "textDocument/formatting" => Some(self.with_document(&params, |document, _| {
+                let options = params.pointer("/options").ok().cloned().unwrap_or(json!(null));
+                features::formatting(document, &options)
+            })),
+            "textDocument/hover" => Some(self.with_document(&params, |document, _| {
+                features::hover(document, document.offset(params.pointer("/position").unwrap_or(&json!(null))))
+            })),
+            "textDocument/foldingRange" => Some(self.with_document(&params, |document, _| features::folding_ranges(document))),
+            "textDocument/documentSymbol" => Some(self.with_document(&params, |document, _| features::document_symbols(document))),
+            "textDocument/completion" => Some(self.with_document(&params, |document, uri| {
+                completion::completion(document, uri, document.offset(params.pointer("/position").unwrap_or(&json!(null))))
+            })),
+            // Notifications the server doesn't know, like `initialized`, are ignored.
+            _ => {
+                if let Some(id) = &id {
+                    self.outgoing.push(error(id.clone(), METHOD_NOT_FOUND, &format!("unknown method {}", method)));
+                }
+                None
+            },
+        };
+
+        if let (Some(id), Some(result)) = (id, result) {
+            self.outgoing.push(response(id, result));
+        }
+
+        false
+    }
+
+    // Stores a document's new text and publishes its diagnostics.
+    fn open(&mut self, params: &Json, text: String)
+    {
+        let uri = match string(params, "/textDocument/uri") {
+            Some(uri) => uri.to_string(),
+            None      => return,
+        };
+
+        let document = Document::new(text);
+        let diagnostics = features::diagnostics(&document);
+
+        self.documents.insert(uri.clone(), document);
+        self.outgoing.push(notification("textDocument/publishDiagnostics", json!({ "uri": uri, "diagnostics": diagnostics })));
+    }
+
+    // Runs `answer` on the document a request is about, null for documents
+    // that aren't open.
+    fn with_document<F>(&self, params: &Json, answer: F) -> Json
+        where F: FnOnce(&Document, &str) -> Json
+    {
+        let uri = string(params, "/textDocument/uri").unwrap_or("");
+
+        match self.documents.get(uri) {
+            Some(document) => answer(document, uri),
+            None           => json!(null),
+        }
+    }
+}
+
+fn initialize() -> Json
+{
+    json!({
+        "capabilities": {
+            "textDocumentSync":           1,
+            "documentFormattingProvider": true,
+            "hoverProvider":              true,
+            "foldingRangeProvider":       true,
+            "documentSymbolProvider":     true,
+            "completionProvider":         { "triggerCharacters": ["\"", ":"] },
+        },
+        "serverInfo": { "name": "simple-json-lsp", "version": (env!("CARGO_PKG_VERSION")) },
+    })
+}
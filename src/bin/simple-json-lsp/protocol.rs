@@ -0,0 +1,137 @@
+//! Base protocol: messages framed by a `Content-Length` header, and the
+//! conversion between byte offsets and LSP positions.
+
+use std::io::{ self, BufRead, Write };
+
+use simple_json::recover::Recovered;
+use simple_json::{ json, Json };
+
+/// Reads the body of the next message, `None` at the end of the input. The
+/// body is left undecoded, a message that isn't UTF-8 is answered like any
+/// other malformed message.
+pub fn read(input: &mut impl BufRead) -> io::Result<Option<Vec<u8>>>
+{
+    let mut length = None;
+
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = length.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length"))?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+
+    Ok(Some(body))
+}
+
+pub fn write(output: &mut impl Write, message: &Json) -> io::Result<()>
+{
+    let body = message.to_source();
+
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+pub fn response(id: Json, result: Json) -> Json
+{
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+pub fn error(id: Json, code: i64, message: &str) -> Json
+{
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+pub fn notification(method: &str, params: Json) -> Json
+{
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+/// A document's text along with where its lines start, and the tree and
+/// spans parsed from it. Every version of a document is parsed once, the
+/// requests on it share the result. LSP positions count lines from 0 and
+/// columns in UTF-16 code units.
+pub struct Document
+{
+    pub text:   String,
+    pub parsed: Recovered,
+    starts:     Vec<usize>,
+}
+
+impl Document
+{
+    pub fn new(text: String) -> Document
+    {
+        let mut starts = vec![0];
+        starts.extend(text.match_indices('\n').map(|(index, _)| index + 1));
+        let parsed = Json::parse_tolerant(&text);
+
+        Document { text, parsed, starts }
+    }
+
+    pub fn position(&self, offset: usize) -> Json
+    {
+        let line   = self.starts.partition_point(|start| *start <= offset) - 1;
+        let column = self.text[self.starts[line]..offset].encode_utf16().count();
+
+        json!({ "line": (line as u64), "character": (column as u64) })
+    }
+
+    pub fn range(&self, start: usize, end: usize) -> Json
+    {
+        json!({ "start": (self.position(start)), "end": (self.position(end)) })
+    }
+
+    /// Offset of an LSP position, positions past the end of a line or of
+    /// the document are moved back to it.
+    pub fn offset(&self, position: &Json) -> usize
+    {
+        let line   = number(position, "/line");
+        let column = number(position, "/character");
+
+        let start = match self.starts.get(line) {
+            Some(start) => *start,
+            None        => return self.text.len(),
+        };
+        let end = self.starts.get(line + 1).map_or(self.text.len(), |next| next - 1);
+
+        let mut units = 0;
+        for (index, chr) in self.text[start..end].char_indices() {
+            if units >= column {
+                return start + index;
+            }
+            units += chr.len_utf16();
+        }
+
+        end
+    }
+}
+
+/// The string at `pointer`, if there is one.
+pub fn string<'a>(json: &'a Json, pointer: &str) -> Option<&'a str>
+{
+    json.pointer(pointer).ok().and_then(Json::as_str)
+}
+
+/// The number at `pointer`, 0 if there isn't one.
+pub fn number(json: &Json, pointer: &str) -> usize
+{
+    match json.pointer(pointer).ok().and_then(Json::as_number) {
+        Some(number) => f64::from(number.clone()).max(0.) as usize,
+        None         => 0,
+    }
+}
@@ -0,0 +1,406 @@
+//! Command line interface to the library, built with the `cli` feature.
+
+use std::env;
+use std::fs;
+use std::io::{ self, Read };
+use std::process;
+
+use simple_json::config::{ Config, ConfigError, ConfigOptions };
+use simple_json::diff::{ diff_with, render, DiffOptions };
+use simple_json::format::PrettyOptions;
+use simple_json::{ Error, Json, JsonPointer, Schema };
+
+const USAGE: &str = "\
+Usage: simple-json <command> [options] [file...]
+
+Commands:
+    fmt [--compact] [--indent N] [--sort-keys] [-i] [file...]
+        Reformats documents, in place with -i.
+    check [--schema file] [file...]
+        Checks documents are valid JSON, and valid against a schema.
+    get <pointer> [file]
+        Prints the value at a JSON Pointer.
+    set [-i] <pointer> <value> [file]
+        Replaces or adds the value at a JSON Pointer, keeping the
+        formatting of the rest of the document.
+    diff [--key name] <before> <after>
+        Prints the differences between two documents, arrays of objects
+        are matched by the `name` member with --key.
+    config [--env PREFIX] [file...]
+        Merges configuration files, later ones overriding earlier ones,
+        then environment variables like PREFIX__DB__HOST with --env.
+    config explain [--env PREFIX] <pointer> [file...]
+        Prints a value of the configuration and where it comes from.
+
+Files default to the standard input, also read for `-`, except for config.
+Arguments after `--` are never taken for options.
+
+Exit status:
+    0  success
+    1  diff found differences
+    2  invalid command line
+    3  a file couldn't be read or written
+    4  a document isn't valid JSON
+    5  a document doesn't match the schema, or a configuration is invalid
+    6  the pointer doesn't resolve
+";
+
+const DIFFERENT:   i32 = 1;
+const USAGE_ERROR: i32 = 2;
+const IO_ERROR:    i32 = 3;
+const SYNTAX:      i32 = 4;
+const INVALID:     i32 = 5;
+const NOT_FOUND:   i32 = 6;
+
+// Stops the command, `message` goes to the standard error.
+struct Failure
+{
+    code:    i32,
+    message: String,
+}
+
+impl Failure
+{
+    fn new<S: Into<String>>(code: i32, message: S) -> Failure
+    {
+        Failure { code, message: message.into() }
+    }
+}
+
+// Command line split into flags, options taking a value and operands.
+struct Args
+{
+    flags:    Vec<String>,
+    options:  Vec<(String, String)>,
+    operands: Vec<String>,
+}
+
+impl Args
+{
+    fn parse(args: &[String], flags: &[&str], options: &[&str]) -> Result<Args, Failure>
+    {
+        let mut parsed = Args { flags: vec![], options: vec![], operands: vec![] };
+        let mut iter   = args.iter();
+
+        while let Some(arg) = iter.next() {
+            if arg == "--" {
+                parsed.operands.extend(iter.cloned());
+                break;
+            } else if flags.contains(&arg.as_str()) {
+                parsed.flags.push(arg.clone());
+            } else if options.contains(&arg.as_str()) {
+                match iter.next() {
+                    Some(value) => parsed.options.push((arg.clone(), value.clone())),
+                    None        => return Err(Failure::new(USAGE_ERROR, format!("{} needs a value", arg))),
+                }
+            } else if arg.starts_with('-') && arg != "-" && !is_negative_number(arg) {
+                return Err(Failure::new(USAGE_ERROR, format!("unknown option {}", arg)));
+            } else {
+                parsed.operands.push(arg.clone());
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    fn flag(&self, names: &[&str]) -> bool
+    {
+        self.flags.iter().any(|flag| names.contains(&flag.as_str()))
+    }
+
+    fn option(&self, name: &str) -> Option<&str>
+    {
+        self.options.iter().rev().find(|(option, _)| option == name).map(|(_, value)| value.as_str())
+    }
+
+    // The operands, or the standard input when there are none.
+    fn files(&self) -> Vec<String>
+    {
+        match self.operands.is_empty() {
+            true  => vec!["-".to_string()],
+            false => self.operands.clone(),
+        }
+    }
+}
+
+// Negative numbers are operands, so values like -1 need no `--`.
+fn is_negative_number(arg: &str) -> bool
+{
+    arg[1..].starts_with(|chr: char| chr.is_ascii_digit())
+}
+
+fn main()
+{
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let code = match run(&args) {
+        Ok(code) => code,
+        Err(failure) => {
+            eprintln!("simple-json: {}", failure.message);
+            if failure.code == USAGE_ERROR {
+                eprint!("\n{}", USAGE);
+            }
+            failure.code
+        },
+    };
+
+    process::exit(code);
+}
+
+fn run(args: &[String]) -> Result<i32, Failure>
+{
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => return Err(Failure::new(USAGE_ERROR, "missing command")),
+    };
+
+    match command {
+        "fmt"    => fmt(args),
+        "check"  => check(args),
+        "get"    => get(args),
+        "set"    => set(args),
+        "diff"   => diff(args),
+        "config" => config(args),
+        "help" | "-h" | "--help" => {
+            print!("{}", USAGE);
+            Ok(0)
+        },
+        _ => Err(Failure::new(USAGE_ERROR, format!("unknown command {}", command))),
+    }
+}
+
+fn fmt(args: &[String]) -> Result<i32, Failure>
+{
+    let args = Args::parse(args, &["--compact", "--sort-keys", "-i", "--in-place"], &["--indent"])?;
+
+    let mut options = PrettyOptions { sort_keys: args.flag(&["--sort-keys"]), ..PrettyOptions::default() };
+    if let Some(indent) = args.option("--indent") {
+        options.indent = indent.parse().map_err(|_| Failure::new(USAGE_ERROR, format!("invalid indent {}", indent)))?;
+    }
+
+    let in_place = args.flag(&["-i", "--in-place"]);
+    if in_place && args.files().iter().any(|file| file == "-") {
+        return Err(Failure::new(USAGE_ERROR, "can't format the standard input in place"));
+    }
+
+    for file in args.files() {
+        let (_, json) = load(&file)?;
+
+        let output = match args.flag(&["--compact"]) {
+            true  => json.compact() + "\n",
+            false => json.pretty_with(&options) + "\n",
+        };
+
+        match in_place {
+            true  => store(&file, &output)?,
+            false => print!("{}", output),
+        }
+    }
+
+    Ok(0)
+}
+
+fn check(args: &[String]) -> Result<i32, Failure>
+{
+    let args = Args::parse(args, &[], &["--schema"])?;
+
+    let schema = match args.option("--schema") {
+        Some(file) => {
+            let (_, json) = load(file)?;
+            Some(Schema::new(json).map_err(|error| Failure::new(USAGE_ERROR, format!("{}: {}", file, error)))?)
+        },
+        None => None,
+    };
+
+    let mut code = 0;
+
+    for file in args.files() {
+        let text = read(&file)?;
+
+        let (json, spans) = match Json::parse_with_spans(&text) {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                println!("{}:{}:{}: {}", name(&file), error.line, error.column, error.error);
+                code = SYNTAX;
+                continue;
+            },
+        };
+
+        let errors = match &schema {
+            Some(schema) => schema.validate_with_spans(&json, &spans).err().unwrap_or_default(),
+            None         => vec![],
+        };
+
+        for error in &errors {
+            println!("{}:{}:{}: {} ({})", name(&file), error.line, error.column, error.message, error.schema_path);
+        }
+
+        if !errors.is_empty() && code == 0 {
+            code = INVALID;
+        }
+    }
+
+    Ok(code)
+}
+
+fn get(args: &[String]) -> Result<i32, Failure>
+{
+    let args = Args::parse(args, &[], &[])?;
+
+    let (pointer, file) = match args.operands.as_slice() {
+        [pointer]       => (pointer, "-"),
+        [pointer, file] => (pointer, file.as_str()),
+        _ => return Err(Failure::new(USAGE_ERROR, "get takes a pointer and at most one file")),
+    };
+
+    let pointer = parse_pointer(pointer)?;
+    let (_, json) = load(file)?;
+    let value = pointer.resolve(&json).map_err(not_found)?;
+
+    println!("{}", value);
+    Ok(0)
+}
+
+fn set(args: &[String]) -> Result<i32, Failure>
+{
+    let args = Args::parse(args, &["-i", "--in-place"], &[])?;
+
+    let (pointer, value, file) = match args.operands.as_slice() {
+        [pointer, value]       => (pointer, value, "-"),
+        [pointer, value, file] => (pointer, value, file.as_str()),
+        _ => return Err(Failure::new(USAGE_ERROR, "set takes a pointer, a value and at most one file")),
+    };
+
+    let pointer = parse_pointer(pointer)?;
+    let value   = Json::parse(value).map_err(|error| Failure::new(USAGE_ERROR, format!("invalid value: {}", error)))?;
+
+    let in_place = args.flag(&["-i", "--in-place"]);
+    if in_place && file == "-" {
+        return Err(Failure::new(USAGE_ERROR, "can't edit the standard input in place"));
+    }
+
+    let (text, _) = load(file)?;
+    let output = pointer.set_in_source(&text, &value).map_err(not_found)?;
+
+    match in_place {
+        true  => store(file, &output)?,
+        false => print!("{}", output),
+    }
+
+    Ok(0)
+}
+
+fn diff(args: &[String]) -> Result<i32, Failure>
+{
+    let args = Args::parse(args, &[], &["--key"])?;
+
+    let (before, after) = match args.operands.as_slice() {
+        [before, after] => (before, after),
+        _ => return Err(Failure::new(USAGE_ERROR, "diff takes two files")),
+    };
+
+    let options = DiffOptions { array_key: args.option("--key").map(str::to_string) };
+    let changes = diff_with(&load(before)?.1, &load(after)?.1, &options);
+
+    print!("{}", render(&changes));
+
+    match changes.is_empty() {
+        true  => Ok(0),
+        false => Ok(DIFFERENT),
+    }
+}
+
+fn config(args: &[String]) -> Result<i32, Failure>
+{
+    let (explain, args) = match args.split_first() {
+        Some((command, rest)) if command == "explain" => (true, rest),
+        _ => (false, args),
+    };
+    let args = Args::parse(args, &[], &["--env"])?;
+
+    let (pointer, files) = match (explain, args.operands.split_first()) {
+        (true, Some((pointer, files))) => (Some(parse_pointer(pointer)?), files),
+        (true, None) => return Err(Failure::new(USAGE_ERROR, "config explain takes a pointer")),
+        (false, _)   => (None, args.operands.as_slice()),
+    };
+
+    if files.iter().any(|file| file == "-") {
+        return Err(Failure::new(USAGE_ERROR, "config can't read the standard input"));
+    }
+
+    // Variables are only read when asked for.
+    let (options, vars) = match args.option("--env") {
+        Some(prefix) => (ConfigOptions { prefix: prefix.to_string(), ..ConfigOptions::default() }, env::vars().collect()),
+        None         => (ConfigOptions::default(), vec![]),
+    };
+
+    let config = Config::load_with(files, vars, &options).map_err(|error| match error {
+        ConfigError::Io(_, _)    => Failure::new(IO_ERROR, error.to_string()),
+        ConfigError::Parse(_, _) => Failure::new(SYNTAX, error.to_string()),
+        ConfigError::Merge(_) | ConfigError::Env(_, _) => Failure::new(INVALID, error.to_string()),
+    })?;
+
+    match pointer {
+        Some(pointer) => {
+            let value = pointer.resolve(&config.value).map_err(not_found)?;
+
+            println!("{}", value);
+            if let Some(provenance) = config.explain(&pointer) {
+                println!("from {}", provenance);
+            }
+        },
+        None => println!("{}", config.value.pretty()),
+    }
+
+    Ok(0)
+}
+
+fn read(file: &str) -> Result<String, Failure>
+{
+    let mut text = String::new();
+
+    let result = match file {
+        "-" => io::stdin().read_to_string(&mut text).map(|_| ()),
+        _   => fs::read_to_string(file).map(|contents| text = contents),
+    };
+
+    result.map_err(|error| Failure::new(IO_ERROR, format!("{}: {}", name(file), error)))?;
+    Ok(text)
+}
+
+// Reads and parses a document, syntax errors stop the command.
+fn load(file: &str) -> Result<(String, Json), Failure>
+{
+    let text = read(file)?;
+
+    match Json::parse_with_position(&text) {
+        Ok(json)   => Ok((text, json)),
+        Err(error) => Err(Failure::new(SYNTAX, format!("{}:{}:{}: {}", name(file), error.line, error.column, error.error))),
+    }
+}
+
+fn store(file: &str, contents: &str) -> Result<(), Failure>
+{
+    fs::write(file, contents).map_err(|error| Failure::new(IO_ERROR, format!("{}: {}", file, error)))
+}
+
+fn parse_pointer(text: &str) -> Result<JsonPointer, Failure>
+{
+    JsonPointer::parse(text).map_err(|error| Failure::new(USAGE_ERROR, error.to_string()))
+}
+
+fn not_found(error: Error) -> Failure
+{
+    match error {
+        Error::InvalidIndex(_) | Error::InvalidPointer(_) => Failure::new(USAGE_ERROR, error.to_string()),
+        _ => Failure::new(NOT_FOUND, error.to_string()),
+    }
+}
+
+fn name(file: &str) -> &str
+{
+    match file {
+        "-" => "<stdin>",
+        _   => file,
+    }
+}
@@ -0,0 +1,88 @@
+//! The JSON Canonicalization Scheme of RFC 8785.
+
+use crate::json::{ quote, Json };
+use crate::number::{ as_i128, Number };
+
+impl Json
+{
+    /// Writes the value in the canonical form of RFC 8785, so equal documents
+    /// always produce the same bytes, to be signed or hashed.
+    ///
+    /// There is no whitespace, object members are sorted by the UTF-16 code
+    /// units of their keys, numbers are written like ECMAScript does and
+    /// strings only escape what JSON requires. NaN and infinities, which
+    /// JSON can't represent, are written as `null`.
+    pub fn canonical(&self) -> String
+    {
+        let mut output = String::new();
+        write_canonical(self, false, &mut output);
+
+        output
+    }
+
+    // The canonical form, except that integers are written with all their
+    // digits instead of going through `f64`, so 2^53 + 1 doesn't read as 2^53.
+    pub(crate) fn canonical_exact(&self) -> String
+    {
+        let mut output = String::new();
+        write_canonical(self, true, &mut output);
+
+        output
+    }
+}
+
+fn write_canonical(json: &Json, exact: bool, output: &mut String)
+{
+    match json {
+        Json::Null(_)           => output.push_str("null"),
+        Json::Boolean(value, _) => output.push_str(if *value { "true" } else { "false" }),
+        Json::Number(value, _)  => match (integer(value).filter(|_| exact), f64::from(value.clone()).is_finite()) {
+            (Some(integer), _) => output.push_str(&integer.to_string()),
+            (None, true)       => output.push_str(&value.to_ecmascript_string()),
+            (None, false)      => output.push_str("null"),
+        },
+        Json::String(value, _)  => output.push_str(&quote(value)),
+        Json::Array(elements, _) => {
+            output.push('[');
+            for (index, element) in elements.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+
+                write_canonical(element, exact, output);
+            }
+            output.push(']');
+        },
+        Json::Object(members, _) => {
+            let mut members: Vec<(Vec<u16>, &String, &Json)> = members.iter()
+                .map(|(key, value)| (key.encode_utf16().collect(), key, value))
+                .collect();
+            members.sort_by(|left, right| left.0.cmp(&right.0));
+
+            output.push('{');
+            for (index, (_, key, value)) in members.into_iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+
+                output.push_str(&quote(key));
+                output.push(':');
+                write_canonical(value, exact, output);
+            }
+            output.push('}');
+        },
+    }
+}
+
+// The value of an integer, or of a float holding one, so equal values are
+// written the same whatever their kind.
+fn integer(number: &Number) -> Option<i128>
+{
+    match number {
+        Number::Float(value) => match value.fract() == 0. && value.abs() < 2f64.powi(64) {
+            true  => Some(*value as i128),
+            false => None,
+        },
+        number => Some(as_i128(number)),
+    }
+}
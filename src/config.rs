@@ -0,0 +1,234 @@
+//! Configuration loaded from layered files and environment variables, with
+//! the origin of every value.
+//!
+//! Files are merged with `Json::merge_with`, later files overriding earlier
+//! ones. Environment variables named after the prefix then override single
+//! values: with the default options, `APP__DB__HOST` sets `/db/host`.
+//!
+//! `Config::explain` tells where a value comes from, the file and line it
+//! was read at or the variable that set it.
+
+use std::collections::HashMap;
+use std::env;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{ Path, PathBuf };
+
+use crate::error::{ Error, ParseError };
+use crate::json::{ object, Json };
+use crate::merge::MergeOptions;
+use crate::pointer::JsonPointer;
+use crate::span::Span;
+
+/// Settings for `Config::load_with`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigOptions
+{
+    /// Environment variables start with the prefix and a separator.
+    pub prefix:    String,
+    /// Goes between the prefix and the keys of a variable name.
+    pub separator: String,
+    /// How files are merged.
+    pub merge:     MergeOptions,
+}
+
+impl Default for ConfigOptions
+{
+    fn default() -> ConfigOptions
+    {
+        ConfigOptions { prefix: "APP".to_string(), separator: "__".to_string(), merge: MergeOptions::default() }
+    }
+}
+
+/// Where a value of the configuration comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Provenance
+{
+    /// A file, `pointer` being the location of the value in it.
+    File { path: PathBuf, pointer: JsonPointer, span: Span },
+    /// An environment variable, by name.
+    Env(String),
+}
+
+impl fmt::Display for Provenance
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self {
+            Provenance::File { path, span, .. } => write!(f, "{}:{}:{}", path.display(), span.start.line, span.start.column),
+            Provenance::Env(name)               => write!(f, "environment variable {}", name),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError
+{
+    /// A file couldn't be read.
+    Io(PathBuf, io::Error),
+    /// A file isn't valid JSON.
+    Parse(PathBuf, ParseError),
+    /// Files give conflicting values, see `Conflict::Error`, or a node of
+    /// the result has no span in the file it comes from.
+    Merge(Error),
+    /// An environment variable names an invalid key, or a location that
+    /// can't be set.
+    Env(String, Error),
+}
+
+impl fmt::Display for ConfigError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self {
+            ConfigError::Io(path, error)    => write!(f, "{}: {}", path.display(), error),
+            ConfigError::Parse(path, error) => write!(f, "{}:{}", path.display(), error),
+            ConfigError::Merge(error)       => write!(f, "{}", error),
+            ConfigError::Env(name, error)   => write!(f, "{}: {}", name, error),
+        }
+    }
+}
+
+impl error::Error for ConfigError {}
+
+/// Configuration, along with the provenance of each of its nodes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config
+{
+    pub value:      Json,
+    pub provenance: HashMap<JsonPointer, Provenance>,
+}
+
+impl Config
+{
+    /// Loads the files in order, then the variables of the environment of
+    /// the process starting with `APP__`.
+    pub fn load<P: AsRef<Path>>(files: &[P]) -> Result<Config, ConfigError>
+    {
+        let vars = env::vars_os().filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)));
+
+        Config::load_with(files, vars, &ConfigOptions::default())
+    }
+
+    /// Loads the files in order, then `vars` as environment variables.
+    ///
+    /// Variable names are split at the separator after the prefix, and the
+    /// parts lowercased, to give a location that is created when missing.
+    /// Scalars on the way are replaced by objects, arrays are indexed by the
+    /// parts, which must then be indices, or `-` to append. Values that are
+    /// valid JSON are parsed, others are taken as strings. With no files the
+    /// configuration starts as an empty object.
+    pub fn load_with<P, I>(files: &[P], vars: I, options: &ConfigOptions) -> Result<Config, ConfigError>
+        where P: AsRef<Path>, I: IntoIterator<Item = (String, String)>
+    {
+        let mut layers = vec![];
+        let mut spans  = vec![];
+
+        for path in files {
+            let path = path.as_ref();
+            let text = fs::read_to_string(path).map_err(|error| ConfigError::Io(path.to_path_buf(), error))?;
+            let (json, layer_spans) = Json::parse_with_spans(&text).map_err(|error| ConfigError::Parse(path.to_path_buf(), error))?;
+
+            layers.push(json);
+            spans.push((path.to_path_buf(), layer_spans));
+        }
+
+        let mut config = match layers.is_empty() {
+            true  => Config { value: object(vec![]), provenance: HashMap::new() },
+            false => {
+                let merged = Json::merge_with(&layers, &options.merge).map_err(ConfigError::Merge)?;
+                let provenance = merged.origins.into_iter()
+                    .map(|(pointer, origin)| {
+                        let (path, layer_spans) = &spans[origin.layer];
+                        let span = layer_spans.value(&origin.pointer)
+                            .ok_or_else(|| ConfigError::Merge(Error::MissingKey(origin.pointer.to_string())))?;
+
+                        Ok((pointer, Provenance::File { path: path.clone(), pointer: origin.pointer, span }))
+                    })
+                    .collect::<Result<_, ConfigError>>()?;
+
+                Config { value: merged.value, provenance }
+            },
+        };
+
+        let start = format!("{}{}", options.prefix, options.separator);
+        let mut overrides = vec![];
+
+        for (name, value) in vars {
+            if !name.starts_with(&start) {
+                continue;
+            }
+
+            let tokens: Vec<String> = name[start.len()..].split(options.separator.as_str()).map(str::to_lowercase).collect();
+            if tokens.iter().any(String::is_empty) {
+                return Err(ConfigError::Env(name.clone(), Error::InvalidKey(name)));
+            }
+
+            overrides.push((JsonPointer::from_tokens(tokens), name, value));
+        }
+
+        // Shallower variables go first, so deeper ones override them.
+        overrides.sort_by(|(a, a_name, _), (b, b_name, _)| (a.len(), a_name).cmp(&(b.len(), b_name)));
+
+        for (pointer, name, value) in overrides {
+            config.set(&pointer, &name, &value).map_err(|error| ConfigError::Env(name, error))?;
+        }
+
+        Ok(config)
+    }
+
+    /// Provenance of the node at `pointer`. Arrays and objects are from the
+    /// last file that had them, or from a variable that created them.
+    pub fn explain(&self, pointer: &JsonPointer) -> Option<&Provenance>
+    {
+        self.provenance.get(pointer)
+    }
+
+    // Sets the value of a variable, creating the objects leading to it in
+    // place of what is missing or isn't a container. A `-` in an array is
+    // the index past its end, so that provenance is recorded by index.
+    fn set(&mut self, pointer: &JsonPointer, name: &str, text: &str) -> Result<(), Error>
+    {
+        let value = Json::parse(text).unwrap_or_else(|_| Json::from(text));
+        let mut resolved = JsonPointer::root();
+
+        for (depth, token) in pointer.tokens().iter().enumerate() {
+            match resolved.resolve(&self.value) {
+                Ok(Json::Array(elements, _)) if token == "-" => resolved.push(elements.len().to_string()),
+                _ => resolved.push(token.as_str()),
+            }
+
+            if depth + 1 == pointer.len() {
+                break;
+            }
+
+            match resolved.resolve(&self.value) {
+                Ok(Json::Array(_, _)) | Ok(Json::Object(_, _)) => {},
+                _ => {
+                    resolved.set(&mut self.value, object(vec![]))?;
+                    self.provenance.insert(resolved.clone(), Provenance::Env(name.to_string()));
+                },
+            }
+        }
+
+        let within = |child: JsonPointer| JsonPointer::from_tokens(resolved.tokens().iter().chain(child.tokens()).cloned());
+        let nodes: Vec<JsonPointer> = value.walk().map(|(child, _)| within(child)).collect();
+
+        // Only the nodes of the value replaced lose their provenance.
+        if let Ok(old) = resolved.resolve(&self.value) {
+            for (child, _) in old.walk() {
+                self.provenance.remove(&within(child));
+            }
+        }
+
+        resolved.set(&mut self.value, value)?;
+
+        for node in nodes {
+            self.provenance.insert(node, Provenance::Env(name.to_string()));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,458 @@
+//! Conversions between Rust types and `Json`.
+//!
+//! These are the traits implemented by `#[derive(ToJson, FromJson)]`, from
+//! the `derive` feature. They are also implemented for the primitive types,
+//! strings, options, vectors, maps and `Json` itself.
+
+use std::collections::{ BTreeMap, HashMap };
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::hash::BuildHasher;
+
+use crate::json::{ object, Json };
+use crate::number::Number;
+use crate::pointer::JsonPointer;
+
+pub trait ToJson
+{
+    fn to_json(&self) -> Json;
+}
+
+pub trait FromJson: Sized
+{
+    fn from_json(json: &Json) -> Result<Self, ConvertError>;
+
+    /// The value used when an object member is missing, `None` makes it an
+    /// error.
+    fn from_missing() -> Option<Self>
+    {
+        None
+    }
+}
+
+/// Error produced by `FromJson`, `path` points at the offending value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConvertError
+{
+    pub path:    JsonPointer,
+    pub message: String,
+}
+
+impl ConvertError
+{
+    pub fn new<T: fmt::Display>(message: T) -> ConvertError
+    {
+        ConvertError { path: JsonPointer::root(), message: message.to_string() }
+    }
+
+    fn expected(expected: &str, found: &Json) -> ConvertError
+    {
+        ConvertError::new(format!("expected {}, found {}", expected, kind(found)))
+    }
+
+    /// Moves the error one level down, below `token`.
+    pub fn at<S: Into<String>>(self, token: S) -> ConvertError
+    {
+        let mut tokens = vec![token.into()];
+        tokens.extend(self.path.tokens().iter().cloned());
+
+        ConvertError { path: JsonPointer::from_tokens(tokens), message: self.message }
+    }
+}
+
+impl fmt::Display for ConvertError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        if self.path.is_root() {
+            return f.write_str(&self.message);
+        }
+
+        write!(f, "{} at {}", self.message, self.path)
+    }
+}
+
+impl error::Error for ConvertError {}
+
+fn kind(json: &Json) -> &'static str
+{
+    match json {
+        Json::Object(_, _)  => "object",
+        Json::Array(_, _)   => "array",
+        Json::String(_, _)  => "string",
+        Json::Number(_, _)  => "number",
+        Json::Boolean(_, _) => "boolean",
+        Json::Null(_)       => "null",
+    }
+}
+
+impl Json
+{
+    /// Converts this value into any `FromJson` type.
+    pub fn to<T: FromJson>(&self) -> Result<T, ConvertError>
+    {
+        T::from_json(self)
+    }
+}
+
+impl ToJson for Json
+{
+    fn to_json(&self) -> Json
+    {
+        self.clone()
+    }
+}
+
+impl FromJson for Json
+{
+    fn from_json(json: &Json) -> Result<Json, ConvertError>
+    {
+        Ok(json.clone())
+    }
+}
+
+impl ToJson for bool
+{
+    fn to_json(&self) -> Json
+    {
+        Json::from(*self)
+    }
+}
+
+impl FromJson for bool
+{
+    fn from_json(json: &Json) -> Result<bool, ConvertError>
+    {
+        json.as_bool().ok_or_else(|| ConvertError::expected("boolean", json))
+    }
+}
+
+impl ToJson for ()
+{
+    fn to_json(&self) -> Json
+    {
+        Json::from(())
+    }
+}
+
+impl FromJson for ()
+{
+    fn from_json(json: &Json) -> Result<(), ConvertError>
+    {
+        match json.is_null() {
+            true  => Ok(()),
+            false => Err(ConvertError::expected("null", json)),
+        }
+    }
+}
+
+macro_rules! integer {
+    ($($T: ty),*) => {$(
+        impl ToJson for $T
+        {
+            fn to_json(&self) -> Json
+            {
+                match i64::try_from(*self) {
+                    Ok(value) if value < 0 => Json::from(value),
+                    _ => Json::from(*self as u64),
+                }
+            }
+        }
+
+        impl FromJson for $T
+        {
+            fn from_json(json: &Json) -> Result<$T, ConvertError>
+            {
+                let converted = match json.as_number() {
+                    Some(Number::Unsigned(value)) => <$T>::try_from(*value).ok(),
+                    Some(Number::Integer(value))  => <$T>::try_from(*value).ok(),
+                    Some(Number::Float(_)) | None => return Err(ConvertError::expected("integer", json)),
+                };
+
+                converted.ok_or_else(|| ConvertError::new(format!("{} is out of range for {}", json, stringify!($T))))
+            }
+        }
+    )*};
+}
+
+integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! float {
+    ($($T: ty),*) => {$(
+        impl ToJson for $T
+        {
+            // NaN and infinities have no JSON representation.
+            fn to_json(&self) -> Json
+            {
+                match self.is_finite() {
+                    true  => Json::from(f64::from(*self)),
+                    false => Json::from(()),
+                }
+            }
+        }
+
+        impl FromJson for $T
+        {
+            fn from_json(json: &Json) -> Result<$T, ConvertError>
+            {
+                match json.as_number() {
+                    Some(Number::Unsigned(value)) => Ok(*value as $T),
+                    Some(Number::Integer(value))  => Ok(*value as $T),
+                    Some(Number::Float(value))    => Ok(*value as $T),
+                    None => Err(ConvertError::expected("number", json)),
+                }
+            }
+        }
+    )*};
+}
+
+float!(f32, f64);
+
+impl ToJson for str
+{
+    fn to_json(&self) -> Json
+    {
+        Json::from(self.to_string())
+    }
+}
+
+impl ToJson for String
+{
+    fn to_json(&self) -> Json
+    {
+        Json::from(self.clone())
+    }
+}
+
+impl FromJson for String
+{
+    fn from_json(json: &Json) -> Result<String, ConvertError>
+    {
+        json.as_str().map(str::to_string).ok_or_else(|| ConvertError::expected("string", json))
+    }
+}
+
+impl ToJson for char
+{
+    fn to_json(&self) -> Json
+    {
+        Json::from(self.to_string())
+    }
+}
+
+impl FromJson for char
+{
+    fn from_json(json: &Json) -> Result<char, ConvertError>
+    {
+        let text = json.as_str().ok_or_else(|| ConvertError::expected("string", json))?;
+        let mut chars = text.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(chr), None) => Ok(chr),
+            _ => Err(ConvertError::new("expected a single character")),
+        }
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T
+{
+    fn to_json(&self) -> Json
+    {
+        (**self).to_json()
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T>
+{
+    fn to_json(&self) -> Json
+    {
+        (**self).to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Box<T>
+{
+    fn from_json(json: &Json) -> Result<Box<T>, ConvertError>
+    {
+        Ok(Box::new(T::from_json(json)?))
+    }
+
+    fn from_missing() -> Option<Box<T>>
+    {
+        T::from_missing().map(Box::new)
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T>
+{
+    fn to_json(&self) -> Json
+    {
+        match self {
+            Some(value) => value.to_json(),
+            None        => Json::from(()),
+        }
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T>
+{
+    fn from_json(json: &Json) -> Result<Option<T>, ConvertError>
+    {
+        match json.is_null() {
+            true  => Ok(None),
+            false => Ok(Some(T::from_json(json)?)),
+        }
+    }
+
+    fn from_missing() -> Option<Option<T>>
+    {
+        Some(None)
+    }
+}
+
+impl<T: ToJson> ToJson for [T]
+{
+    fn to_json(&self) -> Json
+    {
+        Json::from(self.iter().map(ToJson::to_json).collect::<Vec<Json>>())
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T>
+{
+    fn to_json(&self) -> Json
+    {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T>
+{
+    fn from_json(json: &Json) -> Result<Vec<T>, ConvertError>
+    {
+        let elements = json.as_array().ok_or_else(|| ConvertError::expected("array", json))?;
+
+        elements.iter()
+            .enumerate()
+            .map(|(index, element)| T::from_json(element).map_err(|error| error.at(index.to_string())))
+            .collect()
+    }
+}
+
+impl<T: ToJson, S: BuildHasher> ToJson for HashMap<String, T, S>
+{
+    fn to_json(&self) -> Json
+    {
+        let mut members: Vec<(String, Json)> = self.iter().map(|(key, value)| (key.clone(), value.to_json())).collect();
+        members.sort_by(|left, right| left.0.cmp(&right.0));
+
+        object(members)
+    }
+}
+
+impl<T: FromJson, S: BuildHasher + Default> FromJson for HashMap<String, T, S>
+{
+    fn from_json(json: &Json) -> Result<HashMap<String, T, S>, ConvertError>
+    {
+        let members = json.as_object().ok_or_else(|| ConvertError::expected("object", json))?;
+
+        members.iter()
+            .map(|(key, value)| Ok((key.clone(), T::from_json(value).map_err(|error| error.at(key.as_str()))?)))
+            .collect()
+    }
+}
+
+impl<T: ToJson> ToJson for BTreeMap<String, T>
+{
+    fn to_json(&self) -> Json
+    {
+        object(self.iter().map(|(key, value)| (key.clone(), value.to_json())).collect())
+    }
+}
+
+impl<T: FromJson> FromJson for BTreeMap<String, T>
+{
+    fn from_json(json: &Json) -> Result<BTreeMap<String, T>, ConvertError>
+    {
+        let members = json.as_object().ok_or_else(|| ConvertError::expected("object", json))?;
+
+        members.iter()
+            .map(|(key, value)| Ok((key.clone(), T::from_json(value).map_err(|error| error.at(key.as_str()))?)))
+            .collect()
+    }
+}
+
+// Helpers for the code generated by the derive macros.
+
+#[doc(hidden)]
+pub fn __object(members: Vec<(String, Json)>) -> Json
+{
+    object(members)
+}
+
+// Adds the members of a flattened field, which must produce an object.
+#[doc(hidden)]
+pub fn __flatten(members: &mut Vec<(String, Json)>, json: Json)
+{
+    if let Json::Object(flattened, _) = json {
+        let mut flattened: Vec<(String, Json)> = flattened.into_iter().collect();
+        flattened.sort_by(|left, right| left.0.cmp(&right.0));
+        members.extend(flattened);
+    }
+}
+
+#[doc(hidden)]
+pub fn __members<'a>(json: &'a Json, expected: &str) -> Result<&'a HashMap<String, Json>, ConvertError>
+{
+    json.as_object().ok_or_else(|| ConvertError::expected(expected, json))
+}
+
+#[doc(hidden)]
+pub fn __elements(json: &Json, length: usize) -> Result<&[Json], ConvertError>
+{
+    match json.as_array() {
+        Some(elements) if elements.len() == length => Ok(elements),
+        Some(elements) => Err(ConvertError::new(format!("expected {} elements, found {}", length, elements.len()))),
+        None => Err(ConvertError::expected("array", json)),
+    }
+}
+
+#[doc(hidden)]
+pub fn __element<T: FromJson>(elements: &[Json], index: usize) -> Result<T, ConvertError>
+{
+    T::from_json(&elements[index]).map_err(|error| error.at(index.to_string()))
+}
+
+#[doc(hidden)]
+pub fn __field<T: FromJson>(members: &HashMap<String, Json>, key: &str) -> Result<T, ConvertError>
+{
+    match members.get(key) {
+        Some(value) => T::from_json(value).map_err(|error| error.at(key)),
+        None => T::from_missing().ok_or_else(|| ConvertError::new(format!("missing field `{}`", key))),
+    }
+}
+
+#[doc(hidden)]
+pub fn __field_or_default<T: FromJson + Default>(members: &HashMap<String, Json>, key: &str) -> Result<T, ConvertError>
+{
+    match members.get(key) {
+        Some(value) => T::from_json(value).map_err(|error| error.at(key)),
+        None        => Ok(T::default()),
+    }
+}
+
+#[doc(hidden)]
+pub fn __tag<'a>(members: &'a HashMap<String, Json>, key: &str) -> Result<&'a str, ConvertError>
+{
+    match members.get(key) {
+        Some(value) => value.as_str().ok_or_else(|| ConvertError::expected("string", value).at(key)),
+        None        => Err(ConvertError::new(format!("missing field `{}`", key))),
+    }
+}
+
+#[doc(hidden)]
+pub fn __unknown_variant<T>(variant: &str) -> Result<T, ConvertError>
+{
+    Err(ConvertError::new(format!("unknown variant `{}`", variant)))
+}
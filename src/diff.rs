@@ -0,0 +1,338 @@
+use std::collections::{ HashMap, HashSet };
+
+use crate::hash::Semantic;
+use crate::json::{ values_equal, Json };
+use crate::patch::runs;
+use crate::pointer::JsonPointer;
+
+/// One difference between two documents.
+///
+/// Paths of removed values point into the old document, every other path
+/// points into the new one.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    Added { path: JsonPointer, value: Json },
+    Removed { path: JsonPointer, value: Json },
+    Modified { path: JsonPointer, before: Json, after: Json },
+    /// An array element that changed position, `from` points into the old
+    /// document.
+    Moved { from: JsonPointer, path: JsonPointer, value: Json },
+}
+
+/// Settings for `diff_with`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DiffOptions
+{
+    /// When set, arrays whose elements are all objects holding this member
+    /// are matched by its value instead of by position, e.g. `"id"`.
+    pub array_key: Option<String>,
+}
+
+impl Change
+{
+    pub fn path(&self) -> &JsonPointer
+    {
+        match self {
+            Change::Added { path, .. }    => path,
+            Change::Removed { path, .. }  => path,
+            Change::Modified { path, .. } => path,
+            Change::Moved { path, .. }    => path,
+        }
+    }
+}
+
+/// Lists the differences between `before` and `after`, arrays are compared
+/// through their longest common subsequence.
+pub fn diff(before: &Json, after: &Json) -> Vec<Change>
+{
+    diff_with(before, after, &DiffOptions::default())
+}
+
+pub fn diff_with(before: &Json, after: &Json, options: &DiffOptions) -> Vec<Change>
+{
+    let mut changes = vec![];
+    compare(&JsonPointer::root(), &JsonPointer::root(), before, after, options, &mut changes);
+    changes
+}
+
+fn compare(
+    before_path: &JsonPointer, after_path: &JsonPointer,
+    before: &Json, after: &Json, options: &DiffOptions, changes: &mut Vec<Change>
+)
+{
+    if values_equal(before, after) {
+        return;
+    }
+
+    match (before, after) {
+        (Json::Object(before, _), Json::Object(after, _)) => {
+            let mut keys: Vec<&String> = before.keys().chain(after.keys().filter(|key| !before.contains_key(*key))).collect();
+            keys.sort();
+
+            for key in keys {
+                match (before.get(key), after.get(key)) {
+                    (Some(old), Some(new)) => {
+                        compare(&before_path.child(key.as_str()), &after_path.child(key.as_str()), old, new, options, changes);
+                    },
+                    (Some(old), None) => {
+                        changes.push(Change::Removed { path: before_path.child(key.as_str()), value: old.clone() });
+                    },
+                    (None, Some(new)) => {
+                        changes.push(Change::Added { path: after_path.child(key.as_str()), value: new.clone() });
+                    },
+                    (None, None) => {},
+                }
+            }
+        },
+        (Json::Array(before, _), Json::Array(after, _)) => {
+            match &options.array_key {
+                Some(key) if keyed(before, key) && keyed(after, key) => {
+                    compare_by_key(before_path, after_path, before, after, key, options, changes);
+                },
+                _ => compare_arrays(before_path, after_path, before, after, options, changes),
+            }
+        },
+        _ => changes.push(Change::Modified { path: after_path.clone(), before: before.clone(), after: after.clone() }),
+    }
+}
+
+fn compare_arrays(
+    before_path: &JsonPointer, after_path: &JsonPointer,
+    before: &[Json], after: &[Json], options: &DiffOptions, changes: &mut Vec<Change>
+)
+{
+    let mut removed  = vec![];
+    let mut added    = vec![];
+    let mut modified = vec![];
+
+    for run in runs(before, after) {
+        let paired = run.deleted.len().min(run.inserted.len());
+        modified.extend(run.deleted.iter().cloned().zip(run.inserted.iter().cloned()));
+        removed.extend(run.deleted[paired..].iter().cloned());
+        added.extend(run.inserted[paired..].iter().cloned());
+    }
+
+    // A removed element that shows up again somewhere else was moved. Each
+    // takes the first added element holding the same value.
+    let mut positions = positions(added.iter().map(|new| &after[*new]));
+    let mut taken     = HashSet::new();
+    let mut moved     = vec![];
+
+    removed.retain(|old| {
+        match positions.get_mut(&Semantic(before[*old].clone())).and_then(Vec::pop) {
+            Some(position) => {
+                moved.push((*old, added[position]));
+                taken.insert(position);
+                false
+            },
+            None => true,
+        }
+    });
+
+    let added: Vec<usize> = added.into_iter().enumerate()
+        .filter(|(position, _)| !taken.contains(position))
+        .map(|(_, new)| new)
+        .collect();
+
+    for (old, new) in modified {
+        compare(&before_path.child(old.to_string()), &after_path.child(new.to_string()), &before[old], &after[new], options, changes);
+    }
+
+    for old in removed {
+        changes.push(Change::Removed { path: before_path.child(old.to_string()), value: before[old].clone() });
+    }
+
+    for new in added {
+        changes.push(Change::Added { path: after_path.child(new.to_string()), value: after[new].clone() });
+    }
+
+    for (old, new) in moved {
+        changes.push(Change::Moved {
+            from:  before_path.child(old.to_string()),
+            path:  after_path.child(new.to_string()),
+            value: after[new].clone(),
+        });
+    }
+}
+
+fn keyed(elements: &[Json], key: &str) -> bool
+{
+    elements.iter().all(|element| match element {
+        Json::Object(members, _) => members.contains_key(key),
+        _ => false,
+    })
+}
+
+fn key_of<'a>(element: &'a Json, key: &str) -> &'a Json
+{
+    match element {
+        Json::Object(members, _) => &members[key],
+        _ => unreachable!(),
+    }
+}
+
+fn compare_by_key(
+    before_path: &JsonPointer, after_path: &JsonPointer,
+    before: &[Json], after: &[Json], key: &str, options: &DiffOptions, changes: &mut Vec<Change>
+)
+{
+    // Each new element is matched with the first old element holding the
+    // same key that isn't matched yet.
+    let mut positions = positions(before.iter().map(|element| key_of(element, key)));
+    let mut matched: HashMap<usize, usize> = HashMap::new();
+
+    for (new, element) in after.iter().enumerate() {
+        if let Some(old) = positions.get_mut(&Semantic(key_of(element, key).clone())).and_then(Vec::pop) {
+            matched.insert(old, new);
+        }
+    }
+
+    for (old, element) in before.iter().enumerate() {
+        if !matched.contains_key(&old) {
+            changes.push(Change::Removed { path: before_path.child(old.to_string()), value: element.clone() });
+        }
+    }
+
+    let matched_new: HashSet<usize> = matched.values().cloned().collect();
+
+    for (new, element) in after.iter().enumerate() {
+        if !matched_new.contains(&new) {
+            changes.push(Change::Added { path: after_path.child(new.to_string()), value: element.clone() });
+        }
+    }
+
+    let mut pairs: Vec<(usize, usize)> = matched.into_iter().collect();
+    pairs.sort_by_key(|(_, new)| *new);
+
+    // Elements keep their relative order unless they are outside of the
+    // longest increasing run of old positions.
+    let in_order: HashSet<usize> = longest_increasing(&pairs.iter().map(|(old, _)| *old).collect::<Vec<usize>>()).into_iter().collect();
+
+    for (position, (old, new)) in pairs.into_iter().enumerate() {
+        let old_path = before_path.child(old.to_string());
+        let new_path = after_path.child(new.to_string());
+
+        if !in_order.contains(&position) {
+            changes.push(Change::Moved { from: old_path.clone(), path: new_path.clone(), value: after[new].clone() });
+        }
+
+        compare(&old_path, &new_path, &before[old], &after[new], options, changes);
+    }
+}
+
+// Positions of the values, grouped by value. Each group lists them last to
+// first, so popping takes the first one left.
+fn positions<'a>(values: impl Iterator<Item = &'a Json>) -> HashMap<Semantic, Vec<usize>>
+{
+    let mut positions: HashMap<Semantic, Vec<usize>> = HashMap::new();
+
+    for (position, value) in values.enumerate() {
+        positions.entry(Semantic(value.clone())).or_default().push(position);
+    }
+
+    for group in positions.values_mut() {
+        group.reverse();
+    }
+
+    positions
+}
+
+// Positions of one longest strictly increasing subsequence of `values`.
+fn longest_increasing(values: &[usize]) -> Vec<usize>
+{
+    // `ends[length - 1]` is the position ending the increasing subsequence of
+    // that length whose last value is the smallest.
+    let mut ends: Vec<usize> = vec![];
+    let mut lengths  = vec![0; values.len()];
+    let mut previous = vec![None; values.len()];
+
+    for (position, value) in values.iter().enumerate() {
+        let length = ends.partition_point(|end| values[*end] < *value);
+
+        if length > 0 {
+            previous[position] = Some(ends[length - 1]);
+        }
+        lengths[position] = length + 1;
+
+        match length == ends.len() {
+            true  => ends.push(position),
+            false => ends[length] = position,
+        }
+    }
+
+    let mut result = vec![];
+    // The subsequence ending first is kept.
+    let mut cursor = (0..values.len()).find(|position| lengths[*position] == ends.len());
+
+    while let Some(position) = cursor {
+        result.push(position);
+        cursor = previous[position];
+    }
+
+    result.reverse();
+    result
+}
+
+/// Renders changes as a unified-diff-like text. Values are printed from
+/// their original source, so formatting of the input documents is kept.
+pub fn render(changes: &[Change]) -> String
+{
+    let mut output = String::new();
+
+    for change in changes {
+        match change {
+            Change::Added { path, value } => {
+                output.push_str(&format!("@@ {} @@\n", display(path)));
+                push_lines(&mut output, '+', &value.to_source());
+            },
+            Change::Removed { path, value } => {
+                output.push_str(&format!("@@ {} @@\n", display(path)));
+                push_lines(&mut output, '-', &value.to_source());
+            },
+            Change::Modified { path, before, after } => {
+                output.push_str(&format!("@@ {} @@\n", display(path)));
+                push_lines(&mut output, '-', &before.to_source());
+                push_lines(&mut output, '+', &after.to_source());
+            },
+            Change::Moved { from, path, value } => {
+                output.push_str(&format!("@@ {} -> {} @@\n", display(from), display(path)));
+                push_lines(&mut output, ' ', &value.to_source());
+            },
+        }
+    }
+
+    output
+}
+
+fn display(path: &JsonPointer) -> String
+{
+    if path.is_root() {
+        return "\"\"".to_string();
+    }
+
+    path.to_string()
+}
+
+fn push_lines(output: &mut String, marker: char, text: &str)
+{
+    for line in text.lines() {
+        output.push(marker);
+        output.push(' ');
+        output.push_str(line);
+        output.push('\n');
+    }
+
+    if text.is_empty() {
+        output.push(marker);
+        output.push('\n');
+    }
+}
+
+impl Json
+{
+    /// See `diff::diff`.
+    pub fn diff(&self, other: &Json) -> Vec<Change>
+    {
+        diff(self, other)
+    }
+}
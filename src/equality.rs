@@ -0,0 +1,82 @@
+//! Equality by value.
+//!
+//! The derived `PartialEq` of `Json` compares sources too, so `[1,2]` and
+//! `[1, 2]` differ. `Json::semantic_eq` compares values only, and wrapping
+//! values in `Semantic` makes `==` do the same.
+
+use std::cmp::Ordering;
+
+use crate::json::Json;
+use crate::number::{ as_i128, compare_numbers, Number };
+
+/// When numbers of different kinds are equal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumberEquivalence
+{
+    /// Numbers holding the same value are equal, `1`, `-0` and `1.0`
+    /// included.
+    Value,
+    /// `Unsigned` and `Integer` numbers holding the same value are equal,
+    /// floats are only equal to floats.
+    Integers,
+    /// Numbers must be of the same kind.
+    Kind,
+}
+
+/// Settings for `Json::semantic_eq_with`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EqOptions
+{
+    pub numbers: NumberEquivalence,
+}
+
+impl Default for EqOptions
+{
+    fn default() -> EqOptions
+    {
+        EqOptions { numbers: NumberEquivalence::Value }
+    }
+}
+
+impl Json
+{
+    /// Compares values, ignoring sources and the order of object members.
+    pub fn semantic_eq(&self, other: &Json) -> bool
+    {
+        self.semantic_eq_with(other, &EqOptions::default())
+    }
+
+    pub fn semantic_eq_with(&self, other: &Json, options: &EqOptions) -> bool
+    {
+        equal(self, other, options.numbers)
+    }
+}
+
+pub(crate) fn equal(left: &Json, right: &Json, numbers: NumberEquivalence) -> bool
+{
+    match (left, right) {
+        (Json::Null(_), Json::Null(_)) => true,
+        (Json::Boolean(left, _), Json::Boolean(right, _)) => left == right,
+        (Json::Number(left, _), Json::Number(right, _)) => numbers_equal(left, right, numbers),
+        (Json::String(left, _), Json::String(right, _)) => left == right,
+        (Json::Array(left, _), Json::Array(right, _)) => {
+            left.len() == right.len() && left.iter().zip(right).all(|(left, right)| equal(left, right, numbers))
+        },
+        (Json::Object(left, _), Json::Object(right, _)) => {
+            left.len() == right.len()
+                && left.iter().all(|(key, left)| right.get(key).is_some_and(|right| equal(left, right, numbers)))
+        },
+        _ => false,
+    }
+}
+
+fn numbers_equal(left: &Number, right: &Number, numbers: NumberEquivalence) -> bool
+{
+    match (numbers, left, right) {
+        (NumberEquivalence::Value, _, _) => compare_numbers(left, right) == Some(Ordering::Equal),
+        (_, Number::Float(left), Number::Float(right)) => left == right,
+        (_, Number::Float(_), _) | (_, _, Number::Float(_)) => false,
+        (NumberEquivalence::Integers, _, _) => as_i128(left) == as_i128(right),
+        (NumberEquivalence::Kind, _, _) => left == right,
+    }
+}
@@ -1,6 +1,85 @@
+use std::error;
+use std::fmt;
+
 #[derive(Debug, PartialEq)]
 pub enum Error {
     UnexpectedEof,
     InvalidCharacter(String),
+
+    // JSON Pointer errors, each one carries the pointer to the offending
+    // location.
+    InvalidPointer(String),
+    MissingKey(String),
+    IndexOutOfRange(String),
+    InvalidIndex(String),
+    NotAContainer(String),
+
+    // JSON Patch errors.
+    InvalidPatch(String),
+    TestFailed(String),
+
+    // Flattening errors, each one carries the flat key.
+    InvalidKey(String),
+    KeyConflict(String),
+
+    // Merge errors, carrying the pointer to the conflicting values.
+    MergeConflict(String),
+}
+
+impl fmt::Display for Error
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self {
+            Error::UnexpectedEof            => write!(f, "unexpected end of input"),
+            Error::InvalidCharacter(chr)    => write!(f, "invalid character `{}`", chr),
+            Error::InvalidPointer(pointer)  => write!(f, "invalid pointer {}", pointer),
+            Error::MissingKey(pointer)      => write!(f, "no member at {}", pointer),
+            Error::IndexOutOfRange(pointer) => write!(f, "index out of range at {}", pointer),
+            Error::InvalidIndex(pointer)    => write!(f, "invalid index at {}", pointer),
+            Error::NotAContainer(pointer)   => write!(f, "not an object or array at {}", pointer),
+            Error::InvalidPatch(message)    => write!(f, "invalid patch: {}", message),
+            Error::TestFailed(pointer)      => write!(f, "test failed at {}", pointer),
+            Error::InvalidKey(key)          => write!(f, "invalid key {}", key),
+            Error::KeyConflict(key)         => write!(f, "conflicting values for {}", key),
+            Error::MergeConflict(pointer)   => write!(f, "conflicting values at {}", pointer),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// Error returned by `Json::parse_with_position`.
+///
+/// `offset` is the byte offset of the offending character in the text,
+/// `line` and `column` both start at 1 and count characters.
+#[derive(Debug, PartialEq)]
+pub struct ParseError
+{
+    pub error:  Error,
+    pub offset: usize,
+    pub line:   usize,
+    pub column: usize,
+}
+
+impl ParseError
+{
+    pub(crate) fn new(error: Error, text: &str, offset: usize) -> ParseError
+    {
+        let before = &text[..offset];
+        let line   = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+
+        ParseError { error, offset, line, column }
+    }
+}
+
+impl fmt::Display for ParseError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "{}:{}: {}", self.line, self.column, self.error)
+    }
 }
 
+impl error::Error for ParseError {}
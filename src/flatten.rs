@@ -0,0 +1,372 @@
+//! Conversion between nested values and flat lists of `(key, value)` pairs,
+//! as used by environment variables and metrics labels.
+//!
+//! Flat keys are paths to the leaves: scalars, and empty arrays and objects.
+//! Leaves come in the order of the document, and a document that is a leaf
+//! has the empty key. A member of the root with an empty key has its
+//! children written after a leading separator, `.a` for `{"": {"a": 1}}`,
+//! but when it is a leaf, or an array in the `Brackets` notation, it can't be
+//! told apart from the root.
+//!
+//! In the `Brackets` and `Separator` notations a backslash escapes the next
+//! character. Object keys have a backslash put before every separator,
+//! bracket and backslash they contain, and in the `Separator` notation before
+//! keys made of digits, which would read as indices otherwise. Text at either
+//! end of a key that would make a separator with the one next to it, like
+//! `_` in `a_` and `_a` with `__`, is escaped too.
+
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::format::Orders;
+use crate::json::{ object, Json };
+use crate::pointer::{ escape, JsonPointer };
+
+/// How flat keys are written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Notation
+{
+    /// `a.b[0].c`, array indices between brackets.
+    Brackets,
+    /// `a.b.0.c`, array indices written like object keys.
+    Separator,
+    /// `/a/b/0/c`, JSON Pointers. Arrays and objects look the same, so when
+    /// unflattening, the tokens of a container being all the indices from 0
+    /// makes it an array.
+    Pointer,
+}
+
+/// Settings for `Json::flatten_with` and `Json::unflatten_with`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlattenOptions
+{
+    /// Goes between the parts of a key, unused by the `Pointer` notation.
+    /// It can't be empty in the other notations.
+    pub separator: String,
+    pub notation:  Notation,
+}
+
+impl Default for FlattenOptions
+{
+    fn default() -> FlattenOptions
+    {
+        FlattenOptions { separator: ".".to_string(), notation: Notation::Brackets }
+    }
+}
+
+// A part of a flat key.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Segment
+{
+    Key(String),
+    Index(usize),
+    // A JSON Pointer token, either of the above.
+    Token(String),
+}
+
+impl Json
+{
+    /// The leaves of the document with their keys, like `a.b[0].c`.
+    pub fn flatten(&self) -> Vec<(String, Json)>
+    {
+        self.flatten_with(&FlattenOptions::default())
+    }
+
+    /// Panics when the separator is empty outside of the `Pointer` notation,
+    /// keys couldn't be split back then.
+    pub fn flatten_with(&self, options: &FlattenOptions) -> Vec<(String, Json)>
+    {
+        check_separator(options);
+
+        let orders    = Orders::new(self);
+        let mut pairs = vec![];
+        let mut stack = vec![(String::new(), JsonPointer::root(), self)];
+
+        while let Some((key, pointer, json)) = stack.pop() {
+            let children: Vec<(String, JsonPointer, &Json)> = match json {
+                Json::Array(elements, _) => elements.iter()
+                    .enumerate()
+                    .map(|(index, element)| (join(&key, &pointer, &Segment::Index(index), options), pointer.child(index.to_string()), element))
+                    .collect(),
+                Json::Object(members, source) => orders.ordered(&pointer, members, source).into_iter()
+                    .map(|(name, value)| (join(&key, &pointer, &Segment::Key(name.clone()), options), pointer.child(name.as_str()), value))
+                    .collect(),
+                _ => vec![],
+            };
+
+            match children.is_empty() {
+                true  => pairs.push((key, json.clone())),
+                false => stack.extend(children.into_iter().rev()),
+            }
+        }
+
+        pairs
+    }
+
+    /// Rebuilds a document from the keys and values `flatten` gives.
+    ///
+    /// Indices missing from an array are filled with nulls. A key can't be
+    /// both a leaf and the parent of another key. No pairs give an empty
+    /// object.
+    pub fn unflatten<I>(pairs: I) -> Result<Json, Error>
+        where I: IntoIterator<Item = (String, Json)>
+    {
+        Json::unflatten_with(pairs, &FlattenOptions::default())
+    }
+
+    /// Panics when the separator is empty outside of the `Pointer` notation.
+    pub fn unflatten_with<I>(pairs: I, options: &FlattenOptions) -> Result<Json, Error>
+        where I: IntoIterator<Item = (String, Json)>
+    {
+        check_separator(options);
+
+        let mut root: Option<Node> = None;
+
+        for (key, value) in pairs {
+            let segments = split(&key, options)?;
+            let mut node = &mut root;
+
+            for segment in segments {
+                let (children, indices) = match node.get_or_insert_with(|| Node::Branch(vec![], HashMap::new())) {
+                    Node::Branch(children, indices) => (children, indices),
+                    Node::Leaf(_) => return Err(Error::KeyConflict(key)),
+                };
+
+                // Elements and members can't share a parent.
+                let is_index = |segment: &Segment| matches!(segment, Segment::Index(_));
+                if children.first().is_some_and(|(first, _)| is_index(first) != is_index(&segment)) {
+                    return Err(Error::KeyConflict(key));
+                }
+
+                let index = match indices.get(&segment) {
+                    Some(index) => *index,
+                    None => {
+                        indices.insert(segment.clone(), children.len());
+                        children.push((segment, None));
+                        children.len() - 1
+                    },
+                };
+                node = &mut children[index].1;
+            }
+
+            match node {
+                Some(Node::Branch(_, _)) => return Err(Error::KeyConflict(key)),
+                _ => *node = Some(Node::Leaf(value)),
+            }
+        }
+
+        match root {
+            Some(root) => Ok(root.build()),
+            None       => Ok(object(vec![])),
+        }
+    }
+}
+
+// Document being rebuilt, children keep the order they were first seen in.
+enum Node
+{
+    Leaf(Json),
+    Branch(Vec<(Segment, Option<Node>)>, HashMap<Segment, usize>),
+}
+
+impl Node
+{
+    fn build(self) -> Json
+    {
+        let children = match self {
+            Node::Leaf(json)          => return json,
+            Node::Branch(children, _) => children,
+        };
+
+        let built: Vec<(Segment, Json)> = children.into_iter()
+            .map(|(segment, node)| (segment, node.unwrap().build()))
+            .collect();
+
+        let indices: Option<Vec<usize>> = built.iter()
+            .map(|(segment, _)| match segment {
+                Segment::Index(index) => Some(*index),
+                Segment::Token(token) => pointer_index(token),
+                Segment::Key(_)       => None,
+            })
+            .collect();
+        let tokens = built.iter().all(|(segment, _)| matches!(segment, Segment::Token(_)));
+
+        match indices {
+            // Pointer tokens are only indices when they make a whole array,
+            // they're all different so none past the end means none missing.
+            Some(indices) if !tokens || indices.iter().all(|index| *index < indices.len()) => {
+                let length = indices.iter().max().map_or(0, |max| max + 1);
+                let mut elements = vec![Json::Null("null".to_string()); length];
+
+                for (index, (_, json)) in indices.into_iter().zip(built) {
+                    elements[index] = json;
+                }
+                Json::from(elements)
+            },
+            _ => {
+                let members = built.into_iter().map(|(segment, json)| match segment {
+                    Segment::Key(key) | Segment::Token(key) => (key, json),
+                    Segment::Index(_) => unreachable!("elements and members aren't mixed"),
+                });
+
+                object(members.collect())
+            },
+        }
+    }
+}
+
+// Array index of a JSON Pointer token, written without leading zeros.
+fn pointer_index(token: &str) -> Option<usize>
+{
+    match token.len() > 1 && token.starts_with('0') {
+        true  => None,
+        false => token.parse().ok(),
+    }
+}
+
+fn check_separator(options: &FlattenOptions)
+{
+    if options.notation != Notation::Pointer && options.separator.is_empty() {
+        panic!("flat keys need a separator that isn't empty");
+    }
+}
+
+// Key of a child of the node at `pointer`, whose key is `key`.
+fn join(key: &str, pointer: &JsonPointer, segment: &Segment, options: &FlattenOptions) -> String
+{
+    let (token, index) = match segment {
+        Segment::Index(index) => (index.to_string(), true),
+        Segment::Key(token) | Segment::Token(token) => (token.clone(), false),
+    };
+
+    let part = match (options.notation, index) {
+        (Notation::Pointer, _)      => return format!("{}/{}", key, escape(&token)),
+        (Notation::Brackets, true)  => return format!("{}[{}]", key, token),
+        (Notation::Separator, true) => token,
+        (_, false)                  => escape_key(&token, options),
+    };
+
+    // Children of the root have no separator before them, those of an empty
+    // key still do, or they would pass for children of the root.
+    match pointer.is_root() {
+        true  => part,
+        false => format!("{}{}{}", key, options.separator, part),
+    }
+}
+
+fn escape_key(key: &str, options: &FlattenOptions) -> String
+{
+    let separator = options.separator.as_str();
+    let mut escaped = String::new();
+
+    // Keys made of digits would read as indices, and a separator before the
+    // key could end inside it, like `__` then `_a`.
+    let digits  = !key.is_empty() && key.bytes().all(|byte| byte.is_ascii_digit());
+    let leading = (1..separator.len())
+        .filter(|start| separator.is_char_boundary(*start))
+        .any(|start| separator.starts_with(&separator[start..]) && key.starts_with(&separator[separator.len() - start..]));
+    let first   = (digits && options.notation == Notation::Separator) || leading;
+
+    for (index, chr) in key.char_indices() {
+        let special = match chr {
+            '\\'      => true,
+            '[' | ']' => options.notation == Notation::Brackets,
+            _         => (index == 0 && first) || makes_separator(&key[index..], separator),
+        };
+
+        if special {
+            escaped.push('\\');
+        }
+        escaped.push(chr);
+    }
+
+    escaped
+}
+
+// Whether `text` starts a separator, either within it or with the separator
+// that may follow it, like `a_` then `__`.
+fn makes_separator(text: &str, separator: &str) -> bool
+{
+    match text.len() >= separator.len() {
+        true  => text.starts_with(separator),
+        false => separator.starts_with(text) && separator.starts_with(&separator[text.len()..]),
+    }
+}
+
+// Parts of a flat key, none for the root.
+fn split(key: &str, options: &FlattenOptions) -> Result<Vec<Segment>, Error>
+{
+    let invalid = || Error::InvalidKey(key.to_string());
+
+    if key.is_empty() {
+        return Ok(vec![]);
+    }
+
+    if options.notation == Notation::Pointer {
+        let pointer = JsonPointer::parse(key).map_err(|_| invalid())?;
+        return Ok(pointer.tokens().iter().cloned().map(Segment::Token).collect());
+    }
+
+    let brackets  = options.notation == Notation::Brackets;
+    let separator = options.separator.as_str();
+
+    let mut segments = vec![];
+    let mut rest     = key;
+    let mut expect_key = !(brackets && rest.starts_with('['));
+
+    loop {
+        if expect_key {
+            let (segment, tail) = read_key(rest, options).ok_or_else(invalid)?;
+            segments.push(segment);
+            rest = tail;
+        }
+
+        if rest.is_empty() {
+            return Ok(segments);
+        }
+
+        if brackets && rest.starts_with('[') {
+            let end   = rest.find(']').ok_or_else(invalid)?;
+            let index = pointer_index(&rest[1..end]).ok_or_else(invalid)?;
+
+            segments.push(Segment::Index(index));
+            rest = &rest[end + 1..];
+            expect_key = false;
+        } else if rest.starts_with(separator) {
+            rest = &rest[separator.len()..];
+            expect_key = true;
+        } else {
+            return Err(invalid());
+        }
+    }
+}
+
+// Reads a key up to the next separator or bracket, `None` for a dangling
+// backslash or an index that isn't one.
+fn read_key<'a>(text: &'a str, options: &FlattenOptions) -> Option<(Segment, &'a str)>
+{
+    let brackets = options.notation == Notation::Brackets;
+    let mut key  = String::new();
+    let mut end  = text.len();
+    let mut chars = text.char_indices();
+
+    while let Some((index, chr)) = chars.next() {
+        let rest = &text[index..];
+
+        if chr == '\\' {
+            key.push(chars.next()?.1);
+        } else if (brackets && chr == '[') || rest.starts_with(options.separator.as_str()) {
+            end = index;
+            break;
+        } else {
+            key.push(chr);
+        }
+    }
+
+    let raw = &text[..end];
+    if options.notation == Notation::Separator && !raw.is_empty() && raw.bytes().all(|byte| byte.is_ascii_digit()) {
+        return pointer_index(raw).map(|index| (Segment::Index(index), &text[end..]));
+    }
+
+    Some((Segment::Key(key), &text[end..]))
+}
@@ -0,0 +1,260 @@
+//! Pretty and compact printing.
+//!
+//! Objects keep their members in the order they were written, scalars keep
+//! their original spelling, only the whitespace between tokens changes.
+
+use std::collections::{ HashMap, HashSet };
+
+use crate::json::{ key_at, member_offsets, quote, skip_whitespace, value_end, Json };
+use crate::pointer::JsonPointer;
+
+/// Settings for `Json::pretty_with`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrettyOptions
+{
+    /// Number of spaces per nesting level.
+    pub indent:    usize,
+    /// Sorts object members by key instead of keeping their order.
+    pub sort_keys: bool,
+}
+
+impl Default for PrettyOptions
+{
+    fn default() -> PrettyOptions
+    {
+        PrettyOptions { indent: 2, sort_keys: false }
+    }
+}
+
+impl Json
+{
+    /// Prints the value over several lines, indented by 2 spaces.
+    pub fn pretty(&self) -> String
+    {
+        self.pretty_with(&PrettyOptions::default())
+    }
+
+    pub fn pretty_with(&self, options: &PrettyOptions) -> String
+    {
+        let mut output = String::new();
+        write_pretty(self, options, 0, &mut output);
+
+        output
+    }
+
+    /// Prints the value without any whitespace.
+    pub fn compact(&self) -> String
+    {
+        let mut output = String::new();
+        write_compact(self, &mut output);
+
+        output
+    }
+}
+
+fn write_pretty(json: &Json, options: &PrettyOptions, depth: usize, output: &mut String)
+{
+    let inner = " ".repeat(options.indent * (depth + 1));
+    let outer = " ".repeat(options.indent * depth);
+
+    match json {
+        Json::Array(elements, _) if !elements.is_empty() => {
+            output.push_str("[\n");
+            for (index, element) in elements.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(",\n");
+                }
+
+                output.push_str(&inner);
+                write_pretty(element, options, depth + 1, output);
+            }
+            output.push('\n');
+            output.push_str(&outer);
+            output.push(']');
+        },
+        Json::Object(members, source) if !members.is_empty() => {
+            output.push_str("{\n");
+            for (index, (key, value)) in ordered(members, source, options.sort_keys).into_iter().enumerate() {
+                if index > 0 {
+                    output.push_str(",\n");
+                }
+
+                output.push_str(&inner);
+                output.push_str(&quote(key));
+                output.push_str(": ");
+                write_pretty(value, options, depth + 1, output);
+            }
+            output.push('\n');
+            output.push_str(&outer);
+            output.push('}');
+        },
+        Json::Array(_, _)  => output.push_str("[]"),
+        Json::Object(_, _) => output.push_str("{}"),
+        _ => output.push_str(&json.to_source()),
+    }
+}
+
+fn write_compact(json: &Json, output: &mut String)
+{
+    match json {
+        Json::Array(elements, _) => {
+            output.push('[');
+            for (index, element) in elements.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+
+                write_compact(element, output);
+            }
+            output.push(']');
+        },
+        Json::Object(members, source) => {
+            output.push('{');
+            for (index, (key, value)) in ordered(members, source, false).into_iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+
+                output.push_str(&quote(key));
+                output.push(':');
+                write_compact(value, output);
+            }
+            output.push('}');
+        },
+        _ => output.push_str(&json.to_source()),
+    }
+}
+
+// Members in the order of the source, or sorted by key. Keys the source
+// doesn't mention, as in objects built from a map, come last and sorted.
+pub(crate) fn ordered<'a>(members: &'a HashMap<String, Json>, source: &str, sort_keys: bool) -> Vec<(&'a String, &'a Json)>
+{
+    let offsets = match sort_keys {
+        true  => vec![],
+        false => member_offsets(source).unwrap_or_default(),
+    };
+
+    arrange(members, offsets.iter().map(|member| member.key.as_str()))
+}
+
+// Members in the order of `keys`, then sorted.
+fn arrange<'a, 'k, I>(members: &'a HashMap<String, Json>, keys: I) -> Vec<(&'a String, &'a Json)>
+    where I: Iterator<Item = &'k str>
+{
+    let mut found: Vec<&String> = vec![];
+    let mut seen = HashSet::new();
+
+    for key in keys {
+        if let Some((key, _)) = members.get_key_value(key) {
+            if seen.insert(key) {
+                found.push(key);
+            }
+        }
+    }
+
+    let mut rest: Vec<&String> = members.keys().filter(|key| !seen.contains(key)).collect();
+    rest.sort();
+    found.extend(rest);
+
+    found.into_iter().map(|key| (key, &members[key])).collect()
+}
+
+// The order of the members of every object in a tree, from one pass over
+// the source of the root, so traversals don't go through the source of
+// each object again.
+pub(crate) struct Orders
+{
+    keys: HashMap<JsonPointer, Vec<String>>,
+}
+
+// An array or object `Orders::scan` is in, with the keys so far for objects.
+struct Open
+{
+    pointer: JsonPointer,
+    keys:    Option<Vec<String>>,
+    count:   usize,
+}
+
+impl Orders
+{
+    pub(crate) fn new(json: &Json) -> Orders
+    {
+        let mut orders = Orders { keys: HashMap::new() };
+
+        if let Json::Array(_, source) | Json::Object(_, source) = json {
+            orders.scan(source);
+        }
+
+        orders
+    }
+
+    // Goes over the source of the root without recursing, keeping the keys
+    // of every object. Stops at anything invalid, the objects closing after
+    // it are left out.
+    fn scan(&mut self, source: &str) -> Option<()>
+    {
+        let mut open: Vec<Open> = vec![];
+        let mut pointer  = JsonPointer::root();
+        let mut position = skip_whitespace(source, 0);
+
+        loop {
+            position = match source.as_bytes().get(position)? {
+                b'{' => {
+                    open.push(Open { pointer, keys: Some(vec![]), count: 0 });
+                    position + 1
+                },
+                b'[' => {
+                    open.push(Open { pointer, keys: None, count: 0 });
+                    position + 1
+                },
+                _ => value_end(source, position)?,
+            };
+
+            // Closes what ends here, up to the start of the next value.
+            loop {
+                let close = match open.last() {
+                    Some(Open { keys: Some(_), .. }) => '}',
+                    Some(Open { keys: None, .. })    => ']',
+                    None                             => return Some(()),
+                };
+
+                position = skip_whitespace(source, position);
+                position = skip_whitespace(source, position + source[position..].starts_with(',') as usize);
+
+                if !source[position..].starts_with(close) {
+                    break;
+                }
+                position += 1;
+
+                // The last of repeated keys is the one in the tree.
+                if let Some(Open { pointer, keys: Some(keys), .. }) = open.pop() {
+                    self.keys.insert(pointer, keys);
+                }
+            }
+
+            let parent = open.last_mut()?;
+            pointer = match &mut parent.keys {
+                Some(keys) => {
+                    let (key, key_end) = key_at(source, position)?;
+                    position = skip_whitespace(source, key_end);
+                    position = skip_whitespace(source, position + 1);
+
+                    let child = parent.pointer.child(key.as_str());
+                    keys.push(key);
+                    child
+                },
+                None => parent.pointer.child(parent.count.to_string()),
+            };
+            parent.count += 1;
+        }
+    }
+
+    // Members of the object at `pointer`, as `ordered` gives them.
+    pub(crate) fn ordered<'a>(&self, pointer: &JsonPointer, members: &'a HashMap<String, Json>, source: &str) -> Vec<(&'a String, &'a Json)>
+    {
+        match self.keys.get(pointer) {
+            Some(keys) => arrange(members, keys.iter().map(String::as_str)),
+            None       => ordered(members, source, false),
+        }
+    }
+}
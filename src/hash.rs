@@ -0,0 +1,126 @@
+//! Hashing by content.
+//!
+//! `Json` hashes its value only, leaving out sources and the order of object
+//! members, and numbers hash the same when they hold the same value whatever
+//! their kind. `Semantic` pairs that with an equality that also ignores the
+//! sources, so documents can be deduplicated in a `HashSet`.
+
+use std::cmp::Ordering;
+use std::hash::{ Hash, Hasher };
+use std::ops::Deref;
+
+use crate::json::Json;
+use crate::number::Number;
+use crate::sha256::sha256;
+
+impl Hash for Number
+{
+    // Numbers equal as `f64` compare equal, so that is what gets hashed.
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        let value = f64::from(self.clone());
+
+        // Both zeros and every NaN hash the same.
+        let bits = if value == 0. {
+            0
+        } else if value.is_nan() {
+            f64::NAN.to_bits()
+        } else {
+            value.to_bits()
+        };
+
+        state.write_u64(bits);
+    }
+}
+
+impl Hash for Json
+{
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        match self {
+            Json::Null(_) => state.write_u8(0),
+            Json::Boolean(value, _) => {
+                state.write_u8(1);
+                value.hash(state);
+            },
+            Json::Number(value, _) => {
+                state.write_u8(2);
+                value.hash(state);
+            },
+            Json::String(value, _) => {
+                state.write_u8(3);
+                value.hash(state);
+            },
+            Json::Array(elements, _) => {
+                state.write_u8(4);
+                elements.hash(state);
+            },
+            Json::Object(members, _) => {
+                let mut members: Vec<(&String, &Json)> = members.iter().collect();
+                members.sort_by(|left, right| left.0.cmp(right.0));
+
+                state.write_u8(5);
+                members.hash(state);
+            },
+        }
+    }
+}
+
+/// A `Json` compared by value, ignoring its source, so `==` behaves like
+/// `Json::semantic_eq` and `<` follows `Json::total_cmp`.
+///
+/// Numbers are equal when they hold the same value, `1`, `1.0` and `1e0`
+/// included. Unlike with `semantic_eq`, a NaN, which only values built by
+/// hand can hold, is equal to itself, as `Eq` and `Ord` require.
+#[derive(Clone, Debug)]
+pub struct Semantic(pub Json);
+
+impl PartialEq for Semantic
+{
+    fn eq(&self, other: &Semantic) -> bool
+    {
+        self.0.total_cmp(&other.0) == Ordering::Equal
+    }
+}
+
+impl Eq for Semantic {}
+
+impl Hash for Semantic
+{
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        self.0.hash(state);
+    }
+}
+
+impl Deref for Semantic
+{
+    type Target = Json;
+
+    fn deref(&self) -> &Json
+    {
+        &self.0
+    }
+}
+
+impl From<Json> for Semantic
+{
+    fn from(json: Json) -> Semantic
+    {
+        Semantic(json)
+    }
+}
+
+impl Json
+{
+    /// SHA-256 of the canonical form, as lowercase hexadecimal.
+    ///
+    /// It only depends on the content, so it is stable across formatting,
+    /// member order, runs and platforms, and fit for cache keys. Unlike in
+    /// the canonical form, integers keep all their digits, so the digests of
+    /// 9007199254740992 and 9007199254740993 differ.
+    pub fn digest(&self) -> String
+    {
+        sha256(self.canonical_exact().as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
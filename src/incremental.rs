@@ -0,0 +1,211 @@
+//! Updating a parsed document after an edit, without parsing all of it
+//! again.
+//!
+//! `Json::reparse` parses again only the innermost array or object holding
+//! the edit, and patches the source of the nodes around it in place. The
+//! tree and spans it leaves are the same as a full parse of the new text
+//! would give.
+
+use std::ops::Range;
+
+use crate::error::ParseError;
+use crate::json::Json;
+use crate::pointer::JsonPointer;
+use crate::span::{ record, Position, Spans };
+
+/// Replacement of the bytes in `range` of a text by `text`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit
+{
+    pub range: Range<usize>,
+    pub text:  String,
+}
+
+impl Json
+{
+    /// Brings a tree and its spans, from `Json::parse_with_spans`, up to date
+    /// after `edit`. `text` is the whole text once edited.
+    ///
+    /// When the innermost array or object around the edit isn't valid on its
+    /// own, as when the edit moves brackets, the nodes enclosing it are tried
+    /// in turn, up to the whole text. On error the tree and spans are left as
+    /// they were.
+    pub fn reparse(&mut self, spans: &mut Spans, text: &str, edit: &Edit) -> Result<(), ParseError>
+    {
+        let mut containers = containers(self, spans, &edit.range);
+
+        while let Some(pointer) = containers.pop() {
+            let start  = spans.values[&pointer].start.offset;
+            let source = source(pointer.resolve(self).unwrap());
+
+            let mut edited = String::with_capacity(source.len() + edit.text.len());
+            edited.push_str(&source[..edit.range.start - start]);
+            edited.push_str(&edit.text);
+            edited.push_str(&source[edit.range.end - start..]);
+
+            if let Ok(json) = Json::parse(&edited) {
+                splice(self, spans, &containers, &pointer, json, edit);
+                return Ok(());
+            }
+        }
+
+        let (json, new_spans) = Json::parse_with_spans(text)?;
+        *self = json;
+        *spans = new_spans;
+
+        Ok(())
+    }
+}
+
+fn source(json: &Json) -> &str
+{
+    match json {
+        Json::Array(_, source) | Json::Object(_, source) => source,
+        _ => unreachable!("only arrays and objects are reparsed"),
+    }
+}
+
+// Arrays and objects whose brackets enclose `range`, outermost first.
+fn containers(json: &Json, spans: &Spans, range: &Range<usize>) -> Vec<JsonPointer>
+{
+    let encloses = |pointer: &JsonPointer, json: &Json| {
+        let container = matches!(json, Json::Array(_, _) | Json::Object(_, _));
+        let span = spans.value(pointer);
+
+        container && span.is_some_and(|span| span.start.offset < range.start && range.end < span.end.offset)
+    };
+
+    let mut found   = vec![];
+    let mut pointer = JsonPointer::root();
+    let mut node    = json;
+
+    while encloses(&pointer, node) {
+        found.push(pointer.clone());
+
+        let child = match node {
+            // Elements are in the order of the text, only the first one ending
+            // after the start of the edit can enclose it.
+            Json::Array(elements, _) => {
+                let (mut low, mut high) = (0, elements.len());
+                while low < high {
+                    let middle = (low + high) / 2;
+                    match spans.value(&pointer.child(middle.to_string())) {
+                        Some(span) if span.end.offset <= range.start => low = middle + 1,
+                        _ => high = middle,
+                    }
+                }
+
+                elements.get(low)
+                    .map(|element| (pointer.child(low.to_string()), element))
+                    .filter(|(child, element)| encloses(child, element))
+            },
+            Json::Object(members, _) => members.iter()
+                .map(|(key, value)| (pointer.child(key.as_str()), value))
+                .find(|(child, value)| encloses(child, value)),
+            _ => None,
+        };
+
+        match child {
+            Some((child, value)) => {
+                pointer = child;
+                node    = value;
+            },
+            None => break,
+        }
+    }
+
+    found
+}
+
+// Puts the reparsed node at `pointer`, patches the source of `ancestors`
+// and updates the spans. Only the spans of the node, of its ancestors and of
+// what comes after it are touched.
+fn splice(root: &mut Json, spans: &mut Spans, ancestors: &[JsonPointer], pointer: &JsonPointer, json: Json, edit: &Edit)
+{
+    for ancestor in ancestors {
+        let start = spans.values[ancestor].start.offset;
+
+        if let Ok(Json::Array(_, source)) | Ok(Json::Object(_, source)) = ancestor.resolve_mut(root) {
+            source.replace_range(edit.range.start - start..edit.range.end - start, &edit.text);
+        }
+    }
+
+    let old = spans.values[pointer];
+
+    let mut inner = Spans::default();
+    record(source(&json), old.start, pointer, &mut inner);
+    let new = inner.values[pointer];
+
+    // The node's own key comes before it and stays.
+    let node = pointer.resolve_mut(root).unwrap();
+    forget(node, pointer, spans);
+    spans.keys.extend(inner.keys);
+    spans.values.extend(inner.values);
+    *node = json;
+
+    let root = &*root;
+    for ancestor in ancestors {
+        if let Some(span) = spans.values.get_mut(ancestor) {
+            span.end = shift(span.end, old.end, new.end);
+        }
+
+        // Children starting after the node, the one holding it starts before.
+        for (child, value) in children(ancestor.resolve(root).unwrap(), ancestor) {
+            if spans.values.get(&child).is_some_and(|span| span.start.offset >= old.end.offset) {
+                shift_all(value, &child, spans, old.end, new.end);
+            }
+        }
+    }
+}
+
+fn children<'a>(json: &'a Json, pointer: &JsonPointer) -> Vec<(JsonPointer, &'a Json)>
+{
+    match json {
+        Json::Object(members, _) => members.iter().map(|(key, value)| (pointer.child(key.as_str()), value)).collect(),
+        Json::Array(elements, _) => elements.iter().enumerate().map(|(index, value)| (pointer.child(index.to_string()), value)).collect(),
+        _ => vec![],
+    }
+}
+
+// Drops the spans of `json` and of its children, the key of `json` aside.
+fn forget(json: &Json, pointer: &JsonPointer, spans: &mut Spans)
+{
+    spans.values.remove(pointer);
+
+    for (child, value) in children(json, pointer) {
+        spans.keys.remove(&child);
+        forget(value, &child, spans);
+    }
+}
+
+// Shifts the spans of `json`, its key and its children.
+fn shift_all(json: &Json, pointer: &JsonPointer, spans: &mut Spans, old_end: Position, new_end: Position)
+{
+    for span in spans.values.get_mut(pointer).into_iter().chain(spans.keys.get_mut(pointer)) {
+        span.start = shift(span.start, old_end, new_end);
+        span.end   = shift(span.end, old_end, new_end);
+    }
+
+    for (child, value) in children(json, pointer) {
+        shift_all(value, &child, spans, old_end, new_end);
+    }
+}
+
+// Moves a position after the reparsed node along with the node's end.
+fn shift(position: Position, old_end: Position, new_end: Position) -> Position
+{
+    if position.offset < old_end.offset {
+        return position;
+    }
+
+    let column = match position.line == old_end.line {
+        true  => position.column - old_end.column + new_end.column,
+        false => position.column,
+    };
+
+    Position {
+        offset: position.offset - old_end.offset + new_end.offset,
+        line:   position.line - old_end.line + new_end.line,
+        column,
+    }
+}
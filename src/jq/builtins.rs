@@ -0,0 +1,430 @@
+use std::collections::HashMap;
+
+use crate::json::{ values_equal, Json };
+use crate::number::Number;
+use crate::regex::Regex;
+
+use super::eval::{ binary, compare, eval, float, iterate, runtime_error, sorted_entries, to_text, truthy, type_name, Env };
+use super::{ Ast, JqError, Operator };
+
+// Name and arity of every builtin function.
+const BUILTINS: &[(&str, usize)] = &[
+    ("empty", 0), ("error", 0), ("error", 1), ("not", 0), ("length", 0), ("utf8bytelength", 0),
+    ("keys", 0), ("keys_unsorted", 0), ("values", 0), ("has", 1), ("map", 1), ("map_values", 1),
+    ("select", 1), ("recurse", 0), ("add", 0), ("any", 0), ("any", 1), ("all", 0), ("all", 1),
+    ("range", 1), ("range", 2), ("floor", 0), ("ceil", 0), ("round", 0), ("sqrt", 0), ("abs", 0),
+    ("tostring", 0), ("tonumber", 0), ("type", 0), ("sort", 0), ("sort_by", 1), ("group_by", 1),
+    ("unique", 0), ("unique_by", 1), ("min", 0), ("max", 0), ("min_by", 1), ("max_by", 1),
+    ("reverse", 0), ("contains", 1), ("to_entries", 0), ("from_entries", 0), ("with_entries", 1),
+    ("first", 0), ("last", 0), ("first", 1), ("last", 1), ("limit", 2), ("flatten", 0), ("flatten", 1),
+    ("ascii_downcase", 0), ("ascii_upcase", 0), ("ltrimstr", 1), ("rtrimstr", 1), ("trim", 0),
+    ("startswith", 1), ("endswith", 1), ("split", 1), ("join", 1), ("test", 1), ("tojson", 0),
+    ("fromjson", 0), ("index", 1), ("isempty", 1),
+];
+
+pub fn exists(name: &str, arity: usize) -> bool
+{
+    BUILTINS.contains(&(name, arity))
+}
+
+pub fn split(text: &str, separator: &str) -> Vec<Json>
+{
+    if text.is_empty() {
+        return vec![];
+    }
+
+    if separator.is_empty() {
+        return text.chars().map(|chr| Json::from(chr.to_string())).collect();
+    }
+
+    text.split(separator).map(Json::from).collect()
+}
+
+pub fn call(name: &str, args: &[Ast], input: &Json, env: &Env) -> Result<Vec<Json>, JqError>
+{
+    let single = |value: Json| Ok(vec![value]);
+
+    match (name, args.len()) {
+        ("empty", 0) => Ok(vec![]),
+        ("error", 0) => runtime_error(to_text(input)),
+        ("error", 1) => {
+            let message = eval(&args[0], input, env)?;
+            runtime_error(message.first().map(to_text).unwrap_or_default())
+        },
+        ("not", 0) => single(Json::from(!truthy(input))),
+        ("length", 0) => single(match input {
+            Json::Null(_) => Json::from(0u64),
+            Json::Number(number, _) => float(f64::from(number.clone()).abs()),
+            Json::String(value, _) => Json::from(value.chars().count() as u64),
+            Json::Array(value, _) => Json::from(value.len() as u64),
+            Json::Object(value, _) => Json::from(value.len() as u64),
+            other => { return runtime_error(format!("{} has no length", type_name(other))); },
+        }),
+        ("utf8bytelength", 0) => single(Json::from(string(input)?.len() as u64)),
+        ("keys", 0) | ("keys_unsorted", 0) => single(match input {
+            Json::Object(map, _) => Json::from(sorted_entries(map).into_iter().map(|(key, _)| Json::from(key.as_str())).collect::<Vec<Json>>()),
+            Json::Array(vector, _) => Json::from((0..vector.len() as u64).map(Json::from).collect::<Vec<Json>>()),
+            other => { return runtime_error(format!("{} has no keys", type_name(other))); },
+        }),
+        ("values", 0) => Ok(if let Json::Null(_) = input { vec![] } else { vec![input.clone()] }),
+        ("has", 1) => each(&args[0], input, env, |key| Ok(Json::from(match (input, &key) {
+            (Json::Object(map, _), Json::String(key, _)) => map.contains_key(key),
+            (Json::Array(vector, _), Json::Number(index, _)) => {
+                let index = f64::from(index.clone());
+                index >= 0. && index < vector.len() as f64
+            },
+            (input, key) => { return runtime_error(format!("Cannot check whether {} has a {} key", type_name(input), type_name(key))); },
+        }))),
+        ("map", 1) => {
+            let mut result = vec![];
+            for value in iterate(input)? {
+                result.extend(eval(&args[0], &value, env)?);
+            }
+            single(Json::from(result))
+        },
+        ("map_values", 1) => match input {
+            Json::Object(map, _) => {
+                let mut result = HashMap::new();
+                for (key, value) in map {
+                    if let Some(value) = eval(&args[0], value, env)?.into_iter().next() {
+                        result.insert(key.clone(), value);
+                    }
+                }
+                single(Json::from(result))
+            },
+            Json::Array(vector, _) => {
+                let mut result = vec![];
+                for value in vector {
+                    result.extend(eval(&args[0], value, env)?.into_iter().next());
+                }
+                single(Json::from(result))
+            },
+            other => runtime_error(format!("Cannot iterate over {}", type_name(other))),
+        },
+        ("select", 1) => {
+            let mut result = vec![];
+            for condition in eval(&args[0], input, env)? {
+                if truthy(&condition) {
+                    result.push(input.clone());
+                }
+            }
+            Ok(result)
+        },
+        ("recurse", 0) => eval(&Ast::Recurse, input, env),
+        ("add", 0) => {
+            let mut accumulator = Json::from(());
+            for value in iterate(input)? {
+                accumulator = binary(Operator::Add, &accumulator, &value)?;
+            }
+            single(accumulator)
+        },
+        ("any", 0) => single(Json::from(iterate(input)?.iter().any(truthy))),
+        ("all", 0) => single(Json::from(iterate(input)?.iter().all(truthy))),
+        ("any", 1) | ("all", 1) => {
+            let mut results = vec![];
+            for value in iterate(input)? {
+                results.push(eval(&args[0], &value, env)?.iter().any(truthy));
+            }
+            single(Json::from(if name == "any" { results.iter().any(|r| *r) } else { results.iter().all(|r| *r) }))
+        },
+        ("range", 1) => {
+            let mut result = vec![];
+            for end in eval(&args[0], input, env)? {
+                let end = to_f64(&end)?;
+                let mut current = 0.;
+                while current < end {
+                    result.push(float(current));
+                    current += 1.;
+                }
+            }
+            Ok(result)
+        },
+        ("range", 2) => {
+            let mut result = vec![];
+            for start in eval(&args[0], input, env)? {
+                for end in eval(&args[1], input, env)? {
+                    let (mut current, end) = (to_f64(&start)?, to_f64(&end)?);
+                    while current < end {
+                        result.push(float(current));
+                        current += 1.;
+                    }
+                }
+            }
+            Ok(result)
+        },
+        ("floor", 0) => single(float(to_f64(input)?.floor())),
+        ("ceil", 0)  => single(float(to_f64(input)?.ceil())),
+        ("round", 0) => single(float(to_f64(input)?.round())),
+        ("sqrt", 0)  => single(float(to_f64(input)?.sqrt())),
+        ("abs", 0)   => single(match input {
+            Json::Number(Number::Unsigned(_), _) => input.clone(),
+            Json::Number(number, _) => float(f64::from(number.clone()).abs()),
+            other => { return runtime_error(format!("{} has no absolute value", type_name(other))); },
+        }),
+        ("tostring", 0) => single(Json::from(to_text(input))),
+        ("tojson", 0) => single(Json::from(input.to_source())),
+        ("fromjson", 0) => match Json::parse(string(input)?) {
+            Ok(value) => single(value),
+            Err(error) => runtime_error(format!("{:?} while parsing JSON", error)),
+        },
+        ("tonumber", 0) => match input {
+            Json::Number(_, _) => single(input.clone()),
+            Json::String(text, _) => match text.trim().parse::<f64>() {
+                Ok(value) => match Json::parse(text.trim()) {
+                    Ok(parsed @ Json::Number(_, _)) => single(parsed),
+                    _ => single(float(value)),
+                },
+                Err(_) => runtime_error(format!("Cannot parse '{}' as a number", text)),
+            },
+            other => runtime_error(format!("{} cannot be parsed as a number", type_name(other))),
+        },
+        ("type", 0) => single(Json::from(type_name(input))),
+        ("sort", 0) => {
+            let mut values = array(input)?.clone();
+            values.sort_by(compare);
+            single(Json::from(values))
+        },
+        ("sort_by", 1) | ("group_by", 1) | ("unique_by", 1) | ("min_by", 1) | ("max_by", 1) => {
+            let mut keyed = vec![];
+            for value in array(input)? {
+                keyed.push((Json::from(eval(&args[0], value, env)?), value.clone()));
+            }
+            keyed.sort_by(|left, right| compare(&left.0, &right.0));
+
+            single(match name {
+                "sort_by" => Json::from(keyed.into_iter().map(|(_, value)| value).collect::<Vec<Json>>()),
+                "min_by"  => keyed.into_iter().next().map(|(_, value)| value).unwrap_or_else(|| Json::from(())),
+                "max_by"  => keyed.into_iter().last().map(|(_, value)| value).unwrap_or_else(|| Json::from(())),
+                _ => {
+                    let mut groups: Vec<(Json, Vec<Json>)> = vec![];
+                    for (key, value) in keyed {
+                        match groups.last_mut() {
+                            Some(group) if values_equal(&group.0, &key) => group.1.push(value),
+                            _ => groups.push((key, vec![value])),
+                        }
+                    }
+
+                    if name == "group_by" {
+                        Json::from(groups.into_iter().map(|(_, group)| Json::from(group)).collect::<Vec<Json>>())
+                    } else {
+                        Json::from(groups.into_iter().map(|(_, mut group)| group.remove(0)).collect::<Vec<Json>>())
+                    }
+                },
+            })
+        },
+        ("unique", 0) => {
+            let mut values = array(input)?.clone();
+            values.sort_by(compare);
+            values.dedup_by(|left, right| values_equal(left, right));
+            single(Json::from(values))
+        },
+        ("min", 0) => single(array(input)?.iter().min_by(|left, right| compare(left, right)).cloned().unwrap_or_else(|| Json::from(()))),
+        ("max", 0) => single(array(input)?.iter().max_by(|left, right| compare(left, right)).cloned().unwrap_or_else(|| Json::from(()))),
+        ("reverse", 0) => single(match input {
+            Json::Null(_) => Json::from(Vec::<Json>::new()),
+            Json::String(text, _) => Json::from(text.chars().rev().collect::<String>()),
+            other => Json::from(array(other)?.iter().rev().cloned().collect::<Vec<Json>>()),
+        }),
+        ("contains", 1) => each(&args[0], input, env, |other| Ok(Json::from(contains(input, &other)?))),
+        ("to_entries", 0) => match input {
+            Json::Object(map, _) => single(Json::from(sorted_entries(map).into_iter().map(|(key, value)| {
+                let mut entry = HashMap::new();
+                entry.insert("key".to_string(), Json::from(key.as_str()));
+                entry.insert("value".to_string(), value.clone());
+                Json::from(entry)
+            }).collect::<Vec<Json>>())),
+            other => runtime_error(format!("{} has no keys", type_name(other))),
+        },
+        ("from_entries", 0) => {
+            let mut result = HashMap::new();
+            for entry in array(input)? {
+                let key = ["key", "k", "name", "Name", "Key", "K"].iter()
+                    .filter_map(|name| if let Json::Object(map, _) = entry { map.get(*name) } else { None })
+                    .find(|key| truthy(key));
+                let value = ["value", "v", "Value", "V"].iter()
+                    .filter_map(|name| if let Json::Object(map, _) = entry { map.get(*name) } else { None })
+                    .next()
+                    .cloned()
+                    .unwrap_or_else(|| Json::from(()));
+
+                match key {
+                    Some(key) => { result.insert(to_text(key), value); },
+                    None => { return runtime_error("Cannot use null as object key".to_string()); },
+                }
+            }
+            single(Json::from(result))
+        },
+        ("with_entries", 1) => {
+            let entries = call("to_entries", &[], input, env)?.remove(0);
+            let mut mapped = vec![];
+            for entry in iterate(&entries)? {
+                mapped.extend(eval(&args[0], &entry, env)?);
+            }
+            call("from_entries", &[], &Json::from(mapped), env)
+        },
+        ("first", 0) => single(index_or_null(array(input)?.first())),
+        ("last", 0)  => single(index_or_null(array(input)?.last())),
+        ("first", 1) => Ok(eval(&args[0], input, env)?.into_iter().take(1).collect()),
+        ("last", 1)  => Ok(eval(&args[0], input, env)?.pop().into_iter().collect()),
+        ("limit", 2) => {
+            let mut result = vec![];
+            for limit in eval(&args[0], input, env)? {
+                let limit = to_f64(&limit)?.max(0.) as usize;
+                result.extend(eval(&args[1], input, env)?.into_iter().take(limit));
+            }
+            Ok(result)
+        },
+        ("isempty", 1) => single(Json::from(eval(&args[0], input, env)?.is_empty())),
+        ("flatten", 0) => single(Json::from(flatten(array(input)?, usize::MAX))),
+        ("flatten", 1) => each(&args[0], input, env, |depth| {
+            let depth = to_f64(&depth)?;
+            if depth < 0. {
+                return runtime_error("flatten depth must not be negative".to_string());
+            }
+            Ok(Json::from(flatten(array(input)?, depth as usize)))
+        }),
+        ("ascii_downcase", 0) => single(Json::from(string(input)?.to_ascii_lowercase())),
+        ("ascii_upcase", 0)   => single(Json::from(string(input)?.to_ascii_uppercase())),
+        ("trim", 0)           => single(Json::from(string(input)?.trim())),
+        ("ltrimstr", 1) | ("rtrimstr", 1) => each(&args[0], input, env, |affix| {
+            Ok(match (input, &affix) {
+                (Json::String(text, _), Json::String(affix, _)) => {
+                    let trimmed = if name == "ltrimstr" { text.strip_prefix(affix.as_str()) } else { text.strip_suffix(affix.as_str()) };
+                    Json::from(trimmed.unwrap_or(text))
+                },
+                _ => input.clone(),
+            })
+        }),
+        ("startswith", 1) | ("endswith", 1) => each(&args[0], input, env, |affix| {
+            let (text, affix) = (string(input)?, string(&affix)?);
+            Ok(Json::from(if name == "startswith" { text.starts_with(affix) } else { text.ends_with(affix) }))
+        }),
+        ("split", 1) => each(&args[0], input, env, |separator| {
+            Ok(Json::from(split(string(input)?, string(&separator)?)))
+        }),
+        ("join", 1) => each(&args[0], input, env, |separator| {
+            let separator = string(&separator)?;
+            let mut parts = vec![];
+            for value in array(input)? {
+                parts.push(match value {
+                    Json::Null(_) => String::new(),
+                    Json::Array(_, _) | Json::Object(_, _) => {
+                        return runtime_error(format!("Cannot join with {}", type_name(value)));
+                    },
+                    other => to_text(other),
+                });
+            }
+            Ok(Json::from(parts.join(separator)))
+        }),
+        ("test", 1) => each(&args[0], input, env, |pattern| {
+            match Regex::new(string(&pattern)?) {
+                Ok(regex) => Ok(Json::from(regex.is_match(string(input)?))),
+                Err(error) => runtime_error(format!("invalid regex: {}", error)),
+            }
+        }),
+        ("index", 1) => each(&args[0], input, env, |needle| {
+            Ok(match (input, &needle) {
+                (Json::String(text, _), Json::String(needle, _)) if !needle.is_empty() => {
+                    match text.find(needle.as_str()) {
+                        Some(offset) => Json::from(text[..offset].chars().count() as u64),
+                        None         => Json::from(()),
+                    }
+                },
+                (Json::Object(_, _), _) | (Json::Array(_, _), _) | (Json::Null(_), _) => {
+                    super::eval::index(input, &needle)?
+                },
+                _ => Json::from(()),
+            })
+        }),
+        _ => runtime_error(format!("{}/{} is not defined", name, args.len())),
+    }
+}
+
+// Runs `arg` and applies `apply` to each of its outputs.
+fn each<F>(arg: &Ast, input: &Json, env: &Env, mut apply: F) -> Result<Vec<Json>, JqError>
+    where F: FnMut(Json) -> Result<Json, JqError>
+{
+    let mut result = vec![];
+    for value in eval(arg, input, env)? {
+        result.push(apply(value)?);
+    }
+    Ok(result)
+}
+
+fn index_or_null(value: Option<&Json>) -> Json
+{
+    value.cloned().unwrap_or_else(|| Json::from(()))
+}
+
+fn to_f64(value: &Json) -> Result<f64, JqError>
+{
+    match value {
+        Json::Number(number, _) => Ok(f64::from(number.clone())),
+        other => runtime_error(format!("{} is not a number", type_name(other))),
+    }
+}
+
+fn string(value: &Json) -> Result<&str, JqError>
+{
+    match value {
+        Json::String(text, _) => Ok(text),
+        other => runtime_error(format!("{} is not a string", type_name(other))),
+    }
+}
+
+fn array(value: &Json) -> Result<&Vec<Json>, JqError>
+{
+    match value {
+        Json::Array(vector, _) => Ok(vector),
+        other => runtime_error(format!("{} is not an array", type_name(other))),
+    }
+}
+
+fn flatten(values: &[Json], depth: usize) -> Vec<Json>
+{
+    let mut result = vec![];
+
+    for value in values {
+        match value {
+            Json::Array(inner, _) if depth > 0 => result.extend(flatten(inner, depth - 1)),
+            other => result.push(other.clone()),
+        }
+    }
+
+    result
+}
+
+fn contains(haystack: &Json, needle: &Json) -> Result<bool, JqError>
+{
+    match (haystack, needle) {
+        (Json::Object(haystack, _), Json::Object(needle, _)) => {
+            for (key, value) in needle {
+                match haystack.get(key) {
+                    Some(other) if contains(other, value)? => {},
+                    _ => { return Ok(false); },
+                }
+            }
+            Ok(true)
+        },
+        (Json::Array(haystack, _), Json::Array(needle, _)) => {
+            for value in needle {
+                let mut found = false;
+                for other in haystack {
+                    if contains(other, value)? {
+                        found = true;
+                        break;
+                    }
+                }
+                if !found {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        },
+        (Json::String(haystack, _), Json::String(needle, _)) => Ok(haystack.contains(needle.as_str())),
+        (haystack, needle) if type_name(haystack) == type_name(needle) => Ok(values_equal(haystack, needle)),
+        (haystack, needle) => {
+            runtime_error(format!("{} and {} cannot have their containment checked", type_name(haystack), type_name(needle)))
+        },
+    }
+}
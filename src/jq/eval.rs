@@ -0,0 +1,603 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use crate::json::{ values_equal, Json };
+use crate::number::{ compare_numbers, Number };
+
+use super::builtins;
+use super::{ Ast, JqError, ObjectKey, Operator, StringPart };
+
+/// Variables in scope, as a linked list so that closures over a binding are
+/// cheap to create.
+#[derive(Clone, Default)]
+pub struct Env
+{
+    head: Option<Rc<(String, Json, Env)>>,
+}
+
+impl Env
+{
+    pub fn bind(&self, name: &str, value: Json) -> Env
+    {
+        Env { head: Some(Rc::new((name.to_string(), value, self.clone()))) }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Json>
+    {
+        let mut current = self;
+
+        while let Some(entry) = &current.head {
+            if entry.0 == name {
+                return Some(&entry.1);
+            }
+            current = &entry.2;
+        }
+
+        None
+    }
+}
+
+pub fn runtime_error<T>(message: String) -> Result<T, JqError>
+{
+    Err(JqError::Runtime(message))
+}
+
+pub fn eval(ast: &Ast, input: &Json, env: &Env) -> Result<Vec<Json>, JqError>
+{
+    match ast {
+        Ast::Identity => Ok(vec![input.clone()]),
+        Ast::Recurse => {
+            let mut result = vec![];
+            let mut stack  = vec![input.clone()];
+
+            while let Some(value) = stack.pop() {
+                let mut children = iterate(&value).unwrap_or_default();
+                children.reverse();
+                stack.extend(children);
+                result.push(value);
+            }
+
+            Ok(result)
+        },
+        Ast::Literal(value) => Ok(vec![value.clone()]),
+        Ast::Template(parts) => {
+            let mut results = vec![String::new()];
+
+            for part in parts {
+                match part {
+                    StringPart::Text(text) => {
+                        for result in &mut results {
+                            result.push_str(text);
+                        }
+                    },
+                    StringPart::Interpolation(inner) => {
+                        let values = eval(inner, input, env)?;
+                        let mut next = vec![];
+                        for result in &results {
+                            for value in &values {
+                                next.push(format!("{}{}", result, to_text(value)));
+                            }
+                        }
+                        results = next;
+                    },
+                }
+            }
+
+            Ok(results.into_iter().map(Json::from).collect())
+        },
+        Ast::Variable(name) => match env.lookup(name) {
+            Some(value) => Ok(vec![value.clone()]),
+            None        => runtime_error(format!("${} is not defined", name)),
+        },
+        Ast::Field(base, name) => {
+            let mut result = vec![];
+            for value in eval(base, input, env)? {
+                result.push(index(&value, &Json::from(name.as_str()))?);
+            }
+            Ok(result)
+        },
+        Ast::Index(base, index_ast) => {
+            let mut result = vec![];
+            for value in eval(base, input, env)? {
+                for key in eval(index_ast, input, env)? {
+                    result.push(index(&value, &key)?);
+                }
+            }
+            Ok(result)
+        },
+        Ast::Slice(base, start, end) => {
+            let starts = match start {
+                Some(start) => eval(start, input, env)?,
+                None        => vec![Json::from(())],
+            };
+            let ends = match end {
+                Some(end) => eval(end, input, env)?,
+                None      => vec![Json::from(())],
+            };
+
+            let mut result = vec![];
+            for value in eval(base, input, env)? {
+                for start in &starts {
+                    for end in &ends {
+                        result.push(slice(&value, start, end)?);
+                    }
+                }
+            }
+            Ok(result)
+        },
+        Ast::Iterate(base) => {
+            let mut result = vec![];
+            for value in eval(base, input, env)? {
+                result.extend(iterate(&value)?);
+            }
+            Ok(result)
+        },
+        Ast::Optional(inner) => Ok(eval(inner, input, env).unwrap_or_default()),
+        Ast::Array(inner) => match inner {
+            Some(inner) => Ok(vec![Json::from(eval(inner, input, env)?)]),
+            None        => Ok(vec![Json::from(Vec::<Json>::new())]),
+        },
+        Ast::Object(entries) => {
+            let mut objects = vec![HashMap::new()];
+
+            for (key, value) in entries {
+                let keys = match key {
+                    ObjectKey::Name(name) => vec![name.clone()],
+                    ObjectKey::Template(parts) => {
+                        eval(&Ast::Template(parts.clone()), input, env)?.into_iter().map(String::from).collect()
+                    },
+                    ObjectKey::Expression(key) => {
+                        let mut keys = vec![];
+                        for key in eval(key, input, env)? {
+                            match key {
+                                Json::String(key, _) => keys.push(key),
+                                other => { return runtime_error(format!("object keys must be strings, got {}", type_name(&other))); },
+                            }
+                        }
+                        keys
+                    },
+                };
+
+                let mut next = vec![];
+                for key in keys {
+                    let values = match value {
+                        Some(value) => eval(value, input, env)?,
+                        None        => vec![index(input, &Json::from(key.as_str()))?],
+                    };
+
+                    for object in &objects {
+                        for value in &values {
+                            let mut object = object.clone();
+                            object.insert(key.clone(), value.clone());
+                            next.push(object);
+                        }
+                    }
+                }
+                objects = next;
+            }
+
+            Ok(objects.into_iter().map(Json::from).collect())
+        },
+        Ast::Negate(inner) => {
+            let mut result = vec![];
+            for value in eval(inner, input, env)? {
+                match value {
+                    Json::Number(number, _) => result.push(arithmetic(Operator::Subtract, &Number::Unsigned(0), &number)?),
+                    other => { return runtime_error(format!("{} cannot be negated", type_name(&other))); },
+                }
+            }
+            Ok(result)
+        },
+        Ast::Pipe(left, right) => {
+            let mut result = vec![];
+            for value in eval(left, input, env)? {
+                result.extend(eval(right, &value, env)?);
+            }
+            Ok(result)
+        },
+        Ast::Comma(left, right) => {
+            let mut result = eval(left, input, env)?;
+            result.extend(eval(right, input, env)?);
+            Ok(result)
+        },
+        Ast::Binary(operator, left, right) => {
+            let rights = eval(right, input, env)?;
+            let lefts  = eval(left, input, env)?;
+
+            let mut result = vec![];
+            for right in &rights {
+                for left in &lefts {
+                    result.push(binary(*operator, left, right)?);
+                }
+            }
+            Ok(result)
+        },
+        Ast::And(left, right) => {
+            let mut result = vec![];
+            for left in eval(left, input, env)? {
+                if !truthy(&left) {
+                    result.push(Json::from(false));
+                    continue;
+                }
+                for right in eval(right, input, env)? {
+                    result.push(Json::from(truthy(&right)));
+                }
+            }
+            Ok(result)
+        },
+        Ast::Or(left, right) => {
+            let mut result = vec![];
+            for left in eval(left, input, env)? {
+                if truthy(&left) {
+                    result.push(Json::from(true));
+                    continue;
+                }
+                for right in eval(right, input, env)? {
+                    result.push(Json::from(truthy(&right)));
+                }
+            }
+            Ok(result)
+        },
+        Ast::Alternative(left, right) => {
+            let values: Vec<Json> = eval(left, input, env)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(truthy)
+                .collect();
+
+            if values.is_empty() {
+                return eval(right, input, env);
+            }
+
+            Ok(values)
+        },
+        Ast::If(branches, otherwise) => conditional(branches, otherwise.as_deref(), input, env),
+        Ast::Bind(source, name, body) => {
+            let mut result = vec![];
+            for value in eval(source, input, env)? {
+                result.extend(eval(body, input, &env.bind(name, value))?);
+            }
+            Ok(result)
+        },
+        Ast::Reduce(source, name, init, update) => {
+            let mut result = vec![];
+            for mut accumulator in eval(init, input, env)? {
+                for value in eval(source, input, env)? {
+                    let env = env.bind(name, value);
+                    accumulator = match eval(update, &accumulator, &env)?.pop() {
+                        Some(value) => value,
+                        None        => Json::from(()),
+                    };
+                }
+                result.push(accumulator);
+            }
+            Ok(result)
+        },
+        Ast::Try(body, handler) => match eval(body, input, env) {
+            Ok(values) => Ok(values),
+            Err(JqError::Runtime(message)) => match handler {
+                Some(handler) => eval(handler, &Json::from(message), env),
+                None          => Ok(vec![]),
+            },
+            Err(error) => Err(error),
+        },
+        Ast::Call(name, args) => builtins::call(name, args, input, env),
+    }
+}
+
+fn conditional(branches: &[(Ast, Ast)], otherwise: Option<&Ast>, input: &Json, env: &Env) -> Result<Vec<Json>, JqError>
+{
+    let (condition, body) = match branches.split_first() {
+        Some((first, _)) => first,
+        None => {
+            return match otherwise {
+                Some(otherwise) => eval(otherwise, input, env),
+                None            => Ok(vec![input.clone()]),
+            };
+        },
+    };
+
+    let mut result = vec![];
+    for value in eval(condition, input, env)? {
+        if truthy(&value) {
+            result.extend(eval(body, input, env)?);
+        } else {
+            result.extend(conditional(&branches[1..], otherwise, input, env)?);
+        }
+    }
+
+    Ok(result)
+}
+
+pub fn truthy(value: &Json) -> bool
+{
+    !matches!(value, Json::Null(_) | Json::Boolean(false, _))
+}
+
+pub fn type_name(value: &Json) -> &'static str
+{
+    match value {
+        Json::Null(_)       => "null",
+        Json::Boolean(_, _) => "boolean",
+        Json::Number(_, _)  => "number",
+        Json::String(_, _)  => "string",
+        Json::Array(_, _)   => "array",
+        Json::Object(_, _)  => "object",
+    }
+}
+
+// Text of a value as used by string interpolation, strings are inserted
+// without their quotes.
+pub fn to_text(value: &Json) -> String
+{
+    match value {
+        Json::String(value, _) => value.clone(),
+        other => other.to_source(),
+    }
+}
+
+pub fn number(value: Number) -> Json
+{
+    let source = value.to_string();
+    Json::Number(value, source)
+}
+
+// Object values in key order, which is the order jq uses for iteration.
+pub fn sorted_entries(map: &HashMap<String, Json>) -> Vec<(&String, &Json)>
+{
+    let mut entries: Vec<(&String, &Json)> = map.iter().collect();
+    entries.sort_by(|left, right| left.0.cmp(right.0));
+    entries
+}
+
+pub fn iterate(value: &Json) -> Result<Vec<Json>, JqError>
+{
+    match value {
+        Json::Array(vector, _) => Ok(vector.clone()),
+        Json::Object(map, _)   => Ok(sorted_entries(map).into_iter().map(|(_, value)| value.clone()).collect()),
+        other => runtime_error(format!("Cannot iterate over {}", type_name(other))),
+    }
+}
+
+pub fn index(value: &Json, key: &Json) -> Result<Json, JqError>
+{
+    match (value, key) {
+        (Json::Null(_), _) => Ok(Json::from(())),
+        (Json::Object(map, _), Json::String(key, _)) => Ok(map.get(key).cloned().unwrap_or_else(|| Json::from(()))),
+        (Json::Array(vector, _), Json::Number(number, _)) => {
+            let position = f64::from(number.clone()).floor() as i64;
+            let position = if position < 0 { vector.len() as i64 + position } else { position };
+
+            if position < 0 || position >= vector.len() as i64 {
+                return Ok(Json::from(()));
+            }
+
+            Ok(vector[position as usize].clone())
+        },
+        (value, key) => runtime_error(format!("Cannot index {} with {}", type_name(value), type_name(key))),
+    }
+}
+
+fn slice(value: &Json, start: &Json, end: &Json) -> Result<Json, JqError>
+{
+    let len = match value {
+        Json::Null(_) => { return Ok(Json::from(())); },
+        Json::Array(vector, _) => vector.len(),
+        Json::String(string, _) => string.chars().count(),
+        other => { return runtime_error(format!("Cannot slice {}", type_name(other))); },
+    } as i64;
+
+    let bound = |bound: &Json, default: i64| -> Result<usize, JqError> {
+        let bound = match bound {
+            Json::Null(_) => default,
+            Json::Number(number, _) => f64::from(number.clone()).floor() as i64,
+            other => { return runtime_error(format!("Slice bounds must be numbers, got {}", type_name(other))); },
+        };
+        let bound = if bound < 0 { len + bound } else { bound };
+
+        Ok(bound.max(0).min(len) as usize)
+    };
+
+    let start = bound(start, 0)?;
+    let end   = bound(end, len)?.max(start);
+
+    match value {
+        Json::Array(vector, _)  => Ok(Json::from(vector[start..end].to_vec())),
+        Json::String(string, _) => Ok(Json::from(string.chars().skip(start).take(end - start).collect::<String>())),
+        _ => unreachable!(),
+    }
+}
+
+pub fn binary(operator: Operator, left: &Json, right: &Json) -> Result<Json, JqError>
+{
+    let ordering = || compare(left, right);
+
+    match operator {
+        Operator::Equal        => Ok(Json::from(values_equal(left, right))),
+        Operator::NotEqual     => Ok(Json::from(!values_equal(left, right))),
+        Operator::Less         => Ok(Json::from(ordering() == Ordering::Less)),
+        Operator::LessEqual    => Ok(Json::from(ordering() != Ordering::Greater)),
+        Operator::Greater      => Ok(Json::from(ordering() == Ordering::Greater)),
+        Operator::GreaterEqual => Ok(Json::from(ordering() != Ordering::Less)),
+        _ => combine(operator, left, right),
+    }
+}
+
+fn combine(operator: Operator, left: &Json, right: &Json) -> Result<Json, JqError>
+{
+    match (operator, left, right) {
+        (Operator::Add, Json::Null(_), other) | (Operator::Add, other, Json::Null(_)) => Ok(other.clone()),
+        (_, Json::Number(left, _), Json::Number(right, _)) => arithmetic(operator, left, right),
+        (Operator::Add, Json::String(left, _), Json::String(right, _)) => Ok(Json::from(format!("{}{}", left, right))),
+        (Operator::Add, Json::Array(left, _), Json::Array(right, _)) => {
+            let mut result = left.clone();
+            result.extend(right.iter().cloned());
+            Ok(Json::from(result))
+        },
+        (Operator::Add, Json::Object(left, _), Json::Object(right, _)) => {
+            let mut result = left.clone();
+            result.extend(right.iter().map(|(key, value)| (key.clone(), value.clone())));
+            Ok(Json::from(result))
+        },
+        (Operator::Subtract, Json::Array(left, _), Json::Array(right, _)) => {
+            Ok(Json::from(left.iter().filter(|item| !right.iter().any(|other| values_equal(item, other))).cloned().collect::<Vec<Json>>()))
+        },
+        (Operator::Multiply, Json::Object(_, _), Json::Object(_, _)) => Ok(deep_merge(left, right)),
+        (Operator::Divide, Json::String(left, _), Json::String(right, _)) => {
+            Ok(Json::from(builtins::split(left, right)))
+        },
+        _ => runtime_error(format!(
+            "{} and {} cannot be combined with {:?}", type_name(left), type_name(right), operator
+        )),
+    }
+}
+
+fn deep_merge(left: &Json, right: &Json) -> Json
+{
+    match (left, right) {
+        (Json::Object(left, _), Json::Object(right, _)) => {
+            let mut result = left.clone();
+            for (key, value) in right {
+                let merged = match result.get(key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None           => value.clone(),
+                };
+                result.insert(key.clone(), merged);
+            }
+            Json::from(result)
+        },
+        (_, right) => right.clone(),
+    }
+}
+
+// Integer arithmetic is kept exact while it fits, anything else falls back to
+// floating point like jq does.
+pub fn arithmetic(operator: Operator, left: &Number, right: &Number) -> Result<Json, JqError>
+{
+    let integers = match (left, right) {
+        (Number::Float(_), _) | (_, Number::Float(_)) => None,
+        _ => Some((as_integer(left), as_integer(right))),
+    };
+
+    if let Some((left, right)) = integers {
+        let exact = match operator {
+            Operator::Add      => Some(left + right),
+            Operator::Subtract => Some(left - right),
+            Operator::Multiply => left.checked_mul(right),
+            Operator::Divide if right == 0 => { return runtime_error("cannot divide by zero".to_string()); },
+            Operator::Divide if left % right == 0 => Some(left / right),
+            Operator::Modulo if right == 0 => { return runtime_error("cannot divide by zero".to_string()); },
+            Operator::Modulo => Some(left % right),
+            _ => None,
+        };
+
+        if let Some(exact) = exact {
+            if let Ok(value) = u64::try_from(exact) {
+                return Ok(number(Number::Unsigned(value)));
+            }
+            if let Ok(value) = i64::try_from(exact) {
+                return Ok(number(Number::Integer(value)));
+            }
+        }
+    }
+
+    let left  = f64::from(left.clone());
+    let right = f64::from(right.clone());
+
+    let value = match operator {
+        Operator::Add      => left + right,
+        Operator::Subtract => left - right,
+        Operator::Multiply => left * right,
+        Operator::Divide if right == 0. => { return runtime_error("cannot divide by zero".to_string()); },
+        Operator::Divide   => left / right,
+        Operator::Modulo   => {
+            let (left, right) = (left as i64, right as i64);
+            if right == 0 {
+                return runtime_error("cannot divide by zero".to_string());
+            }
+            (left % right) as f64
+        },
+        _ => unreachable!(),
+    };
+
+    Ok(float(value))
+}
+
+// Floats with no fractional part are turned back into integers, so `1.5 * 2`
+// prints as `3`.
+pub fn float(value: f64) -> Json
+{
+    if value.fract() == 0. && value.abs() < 9007199254740992. {
+        if value >= 0. {
+            return number(Number::Unsigned(value as u64));
+        }
+        return number(Number::Integer(value as i64));
+    }
+
+    number(Number::Float(value))
+}
+
+fn as_integer(number: &Number) -> i128
+{
+    match number {
+        Number::Unsigned(value) => i128::from(*value),
+        Number::Integer(value)  => i128::from(*value),
+        Number::Float(value)    => *value as i128,
+    }
+}
+
+fn type_rank(value: &Json) -> u8
+{
+    match value {
+        Json::Null(_)           => 0,
+        Json::Boolean(false, _) => 1,
+        Json::Boolean(true, _)  => 2,
+        Json::Number(_, _)      => 3,
+        Json::String(_, _)      => 4,
+        Json::Array(_, _)       => 5,
+        Json::Object(_, _)      => 6,
+    }
+}
+
+/// jq's ordering: null < false < true < numbers < strings < arrays < objects.
+/// Objects are compared by their sorted keys first and then by their values.
+pub fn compare(left: &Json, right: &Json) -> Ordering
+{
+    match (left, right) {
+        (Json::Number(left, _), Json::Number(right, _)) => {
+            compare_numbers(left, right).unwrap_or(Ordering::Equal)
+        },
+        (Json::String(left, _), Json::String(right, _)) => left.cmp(right),
+        (Json::Array(left, _), Json::Array(right, _)) => {
+            for (left, right) in left.iter().zip(right) {
+                match compare(left, right) {
+                    Ordering::Equal => {},
+                    other => { return other; },
+                }
+            }
+            left.len().cmp(&right.len())
+        },
+        (Json::Object(left, _), Json::Object(right, _)) => {
+            let mut left_keys: Vec<&String> = left.keys().collect();
+            let mut right_keys: Vec<&String> = right.keys().collect();
+            left_keys.sort();
+            right_keys.sort();
+
+            match left_keys.cmp(&right_keys) {
+                Ordering::Equal => {},
+                other => { return other; },
+            }
+
+            for key in left_keys {
+                match compare(&left[key], &right[key]) {
+                    Ordering::Equal => {},
+                    other => { return other; },
+                }
+            }
+            Ordering::Equal
+        },
+        _ => type_rank(left).cmp(&type_rank(right)),
+    }
+}
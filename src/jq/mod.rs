@@ -0,0 +1,132 @@
+//! A small jq-like language for transforming `Json` values.
+//!
+//! Programs are compiled once with `compile` and can then be run against any
+//! number of inputs. Every run produces the stream of values emitted by the
+//! program, so `.items[] | .name` returns one `Json` per item.
+
+mod builtins;
+mod eval;
+mod parser;
+
+use std::fmt;
+
+use crate::json::Json;
+
+/// A compiled program.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Program
+{
+    ast: Ast,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum JqError {
+    /// The program text is invalid, `position` is the offending character.
+    Compile { position: usize, message: String },
+    /// The program failed while running, either from a type error or from a
+    /// call to `error`.
+    Runtime(String),
+}
+
+impl fmt::Display for JqError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self {
+            JqError::Compile { position, message } => write!(f, "{} at position {}", message, position),
+            JqError::Runtime(message) => f.write_str(message),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum StringPart {
+    Text(String),
+    Interpolation(Ast),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum ObjectKey {
+    Name(String),
+    Template(Vec<StringPart>),
+    Expression(Ast),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Ast {
+    Identity,
+    Recurse,
+    Literal(Json),
+    Template(Vec<StringPart>),
+    Variable(String),
+    Field(Box<Ast>, String),
+    Index(Box<Ast>, Box<Ast>),
+    Slice(Box<Ast>, Option<Box<Ast>>, Option<Box<Ast>>),
+    Iterate(Box<Ast>),
+    Optional(Box<Ast>),
+    Array(Option<Box<Ast>>),
+    Object(Vec<(ObjectKey, Option<Ast>)>),
+    Negate(Box<Ast>),
+    Pipe(Box<Ast>, Box<Ast>),
+    Comma(Box<Ast>, Box<Ast>),
+    Binary(Operator, Box<Ast>, Box<Ast>),
+    And(Box<Ast>, Box<Ast>),
+    Or(Box<Ast>, Box<Ast>),
+    Alternative(Box<Ast>, Box<Ast>),
+    If(Vec<(Ast, Ast)>, Option<Box<Ast>>),
+    Bind(Box<Ast>, String, Box<Ast>),
+    Reduce(Box<Ast>, String, Box<Ast>, Box<Ast>),
+    Try(Box<Ast>, Option<Box<Ast>>),
+    Call(String, Vec<Ast>),
+}
+
+/// Compiles a program, checking its syntax and the names and arities of the
+/// functions it calls.
+pub fn compile(text: &str) -> Result<Program, JqError>
+{
+    Ok(Program { ast: parser::parse(text)? })
+}
+
+impl Program
+{
+    pub fn run(&self, input: &Json) -> Result<Vec<Json>, JqError>
+    {
+        self.run_with(input, &[])
+    }
+
+    /// Runs the program with some predefined `$variables`.
+    pub fn run_with(&self, input: &Json, variables: &[(&str, Json)]) -> Result<Vec<Json>, JqError>
+    {
+        let mut env = eval::Env::default();
+
+        for (name, value) in variables {
+            env = env.bind(name, value.clone());
+        }
+
+        eval::eval(&self.ast, input, &env)
+    }
+}
+
+impl Json
+{
+    /// Compiles `program` and runs it with this value as input.
+    pub fn jq(&self, program: &str) -> Result<Vec<Json>, JqError>
+    {
+        compile(program)?.run(self)
+    }
+}
@@ -0,0 +1,738 @@
+use crate::json::Json;
+use crate::number::Number;
+
+use super::builtins;
+use super::{ Ast, JqError, ObjectKey, Operator, StringPart };
+
+const KEYWORDS: &[&str] = &[
+    "if", "then", "elif", "else", "end", "as", "reduce", "try", "catch", "and", "or",
+];
+
+pub fn parse(text: &str) -> Result<Ast, JqError>
+{
+    let mut parser = Parser { chars: text.chars().collect(), pos: 0 };
+
+    let ast = parser.pipe(true)?;
+    parser.skip_whitespace();
+
+    if parser.pos != parser.chars.len() {
+        return Err(parser.error("unexpected character"));
+    }
+
+    Ok(ast)
+}
+
+struct Parser
+{
+    chars: Vec<char>,
+    pos:   usize,
+}
+
+impl Parser
+{
+    fn error(&self, message: &str) -> JqError
+    {
+        JqError::Compile { position: self.pos, message: message.to_string() }
+    }
+
+    fn peek(&self) -> Option<char>
+    {
+        self.chars.get(self.pos).cloned()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char>
+    {
+        self.chars.get(self.pos + offset).cloned()
+    }
+
+    fn skip_whitespace(&mut self)
+    {
+        while let Some(current) = self.peek() {
+            match current {
+                ' ' | '\t' | '\r' | '\n' => { self.pos += 1; },
+                '#' => {
+                    while let Some(current) = self.peek() {
+                        if current == '\n' {
+                            break;
+                        }
+                        self.pos += 1;
+                    }
+                },
+                _ => { break; },
+            }
+        }
+    }
+
+    // Consumes `token` if it comes next, after skipping whitespace.
+    fn eat(&mut self, token: &str) -> bool
+    {
+        self.skip_whitespace();
+        let len = token.chars().count();
+
+        if self.pos + len <= self.chars.len()
+            && self.chars[self.pos..self.pos + len].iter().cloned().eq(token.chars())
+        {
+            self.pos += len;
+            return true;
+        }
+
+        false
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), JqError>
+    {
+        if self.eat(token) {
+            return Ok(());
+        }
+
+        Err(self.error(&format!("expected '{}'", token)))
+    }
+
+    // Like `eat`, but only matches a whole word.
+    fn keyword(&mut self, keyword: &str) -> bool
+    {
+        let start = self.pos;
+        self.skip_whitespace();
+
+        if self.identifier().as_deref() == Some(keyword) {
+            return true;
+        }
+
+        self.pos = start;
+        false
+    }
+
+    fn identifier(&mut self) -> Option<String>
+    {
+        let mut name = String::new();
+
+        match self.peek() {
+            Some(chr) if chr.is_ascii_alphabetic() || chr == '_' => {},
+            _ => { return None; },
+        }
+
+        while let Some(chr) = self.peek() {
+            if !chr.is_ascii_alphanumeric() && chr != '_' {
+                break;
+            }
+
+            name.push(chr);
+            self.pos += 1;
+        }
+
+        Some(name)
+    }
+
+    fn variable_name(&mut self) -> Result<String, JqError>
+    {
+        self.expect("$")?;
+
+        match self.identifier() {
+            Some(name) if !KEYWORDS.contains(&name.as_str()) => Ok(name),
+            _ => Err(self.error("expected variable name")),
+        }
+    }
+
+    fn pipe(&mut self, allow_comma: bool) -> Result<Ast, JqError>
+    {
+        let left = if allow_comma { self.comma()? } else { self.alternative()? };
+
+        self.skip_whitespace();
+        if self.peek() == Some('|') && self.peek_at(1) != Some('=') {
+            self.pos += 1;
+            let right = self.pipe(allow_comma)?;
+            return Ok(Ast::Pipe(Box::new(left), Box::new(right)));
+        }
+
+        Ok(left)
+    }
+
+    fn comma(&mut self) -> Result<Ast, JqError>
+    {
+        let mut left = self.alternative()?;
+
+        while self.eat(",") {
+            let right = self.alternative()?;
+            left = Ast::Comma(Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn alternative(&mut self) -> Result<Ast, JqError>
+    {
+        let left = self.or()?;
+
+        if self.eat("//") {
+            let right = self.alternative()?;
+            return Ok(Ast::Alternative(Box::new(left), Box::new(right)));
+        }
+
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Ast, JqError>
+    {
+        let mut left = self.and()?;
+
+        while self.keyword("or") {
+            let right = self.and()?;
+            left = Ast::Or(Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Ast, JqError>
+    {
+        let mut left = self.comparison()?;
+
+        while self.keyword("and") {
+            let right = self.comparison()?;
+            left = Ast::And(Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Ast, JqError>
+    {
+        let left = self.additive()?;
+
+        for (token, operator) in &[
+            ("==", Operator::Equal),
+            ("!=", Operator::NotEqual),
+            ("<=", Operator::LessEqual),
+            (">=", Operator::GreaterEqual),
+            ("<",  Operator::Less),
+            (">",  Operator::Greater),
+        ] {
+            if self.eat(token) {
+                let right = self.additive()?;
+                return Ok(Ast::Binary(*operator, Box::new(left), Box::new(right)));
+            }
+        }
+
+        Ok(left)
+    }
+
+    fn additive(&mut self) -> Result<Ast, JqError>
+    {
+        let mut left = self.multiplicative()?;
+
+        loop {
+            self.skip_whitespace();
+
+            let operator = match (self.peek(), self.peek_at(1)) {
+                (Some('+'), Some('=')) | (Some('-'), Some('=')) => { break; },
+                (Some('+'), _) => Operator::Add,
+                (Some('-'), _) => Operator::Subtract,
+                _ => { break; },
+            };
+            self.pos += 1;
+
+            let right = self.multiplicative()?;
+            left = Ast::Binary(operator, Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn multiplicative(&mut self) -> Result<Ast, JqError>
+    {
+        let mut left = self.unary()?;
+
+        loop {
+            self.skip_whitespace();
+
+            let operator = match (self.peek(), self.peek_at(1)) {
+                (Some('/'), Some('/')) | (Some(_), Some('=')) => { break; },
+                (Some('*'), _) => Operator::Multiply,
+                (Some('/'), _) => Operator::Divide,
+                (Some('%'), _) => Operator::Modulo,
+                _ => { break; },
+            };
+            self.pos += 1;
+
+            let right = self.unary()?;
+            left = Ast::Binary(operator, Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Ast, JqError>
+    {
+        if self.eat("-") {
+            return Ok(Ast::Negate(Box::new(self.unary()?)));
+        }
+
+        self.postfix()
+    }
+
+    // `term as $name | body` binds the variable for the rest of the pipe.
+    fn postfix(&mut self) -> Result<Ast, JqError>
+    {
+        let term = self.suffixed()?;
+
+        if self.keyword("as") {
+            let name = self.variable_name()?;
+            self.expect("|")?;
+            let body = self.pipe(true)?;
+
+            return Ok(Ast::Bind(Box::new(term), name, Box::new(body)));
+        }
+
+        Ok(term)
+    }
+
+    fn suffixed(&mut self) -> Result<Ast, JqError>
+    {
+        let mut ast = self.term()?;
+
+        loop {
+            // Suffixes must follow the term directly, `. foo` is not `.foo`.
+            match (self.peek(), self.peek_at(1)) {
+                (Some('.'), Some('[')) => {
+                    self.pos += 1;
+                    ast = self.bracket_suffix(ast)?;
+                },
+                (Some('.'), Some('"')) => {
+                    self.pos += 1;
+                    ast = Ast::Field(Box::new(ast), self.plain_string()?);
+                },
+                (Some('.'), Some(chr)) if chr.is_ascii_alphabetic() || chr == '_' => {
+                    self.pos += 1;
+                    let name = self.identifier().unwrap();
+                    ast = Ast::Field(Box::new(ast), name);
+                },
+                (Some('['), _) => {
+                    ast = self.bracket_suffix(ast)?;
+                },
+                (Some('?'), _) => {
+                    self.pos += 1;
+                    ast = Ast::Optional(Box::new(ast));
+                },
+                _ => {
+                    let start = self.pos;
+                    self.skip_whitespace();
+
+                    // Whitespace is allowed before `[` and `?`.
+                    match self.peek() {
+                        Some('[') if start != self.pos => { ast = self.bracket_suffix(ast)?; },
+                        Some('?') if start != self.pos => { self.pos += 1; ast = Ast::Optional(Box::new(ast)); },
+                        _ => {
+                            self.pos = start;
+                            break;
+                        },
+                    }
+                },
+            }
+        }
+
+        Ok(ast)
+    }
+
+    fn bracket_suffix(&mut self, base: Ast) -> Result<Ast, JqError>
+    {
+        self.expect("[")?;
+
+        if self.eat("]") {
+            return Ok(Ast::Iterate(Box::new(base)));
+        }
+
+        if self.eat(":") {
+            let end = self.pipe(true)?;
+            self.expect("]")?;
+            return Ok(Ast::Slice(Box::new(base), None, Some(Box::new(end))));
+        }
+
+        let index = self.pipe(true)?;
+
+        if self.eat(":") {
+            let end = if self.eat("]") {
+                None
+            } else {
+                let end = self.pipe(true)?;
+                self.expect("]")?;
+                Some(Box::new(end))
+            };
+
+            return Ok(Ast::Slice(Box::new(base), Some(Box::new(index)), end));
+        }
+
+        self.expect("]")?;
+        Ok(Ast::Index(Box::new(base), Box::new(index)))
+    }
+
+    fn term(&mut self) -> Result<Ast, JqError>
+    {
+        self.skip_whitespace();
+
+        let current = match self.peek() {
+            Some(chr) => chr,
+            None      => { return Err(self.error("unexpected end of program")); },
+        };
+
+        match current {
+            '.' => {
+                match self.peek_at(1) {
+                    Some('.') => {
+                        self.pos += 2;
+                        Ok(Ast::Recurse)
+                    },
+                    Some('"') => {
+                        self.pos += 1;
+                        Ok(Ast::Field(Box::new(Ast::Identity), self.plain_string()?))
+                    },
+                    Some(chr) if chr.is_ascii_alphabetic() || chr == '_' => {
+                        self.pos += 1;
+                        let name = self.identifier().unwrap();
+                        Ok(Ast::Field(Box::new(Ast::Identity), name))
+                    },
+                    Some('[') => {
+                        self.pos += 1;
+                        self.bracket_suffix(Ast::Identity)
+                    },
+                    _ => {
+                        self.pos += 1;
+                        Ok(Ast::Identity)
+                    },
+                }
+            },
+            '0'..='9' => self.number(),
+            '"' => {
+                let parts = self.template()?;
+                Ok(template_ast(parts))
+            },
+            '$' => Ok(Ast::Variable(self.variable_name()?)),
+            '(' => {
+                self.pos += 1;
+                let inner = self.pipe(true)?;
+                self.expect(")")?;
+                Ok(inner)
+            },
+            '[' => {
+                self.pos += 1;
+                if self.eat("]") {
+                    return Ok(Ast::Array(None));
+                }
+                let inner = self.pipe(true)?;
+                self.expect("]")?;
+                Ok(Ast::Array(Some(Box::new(inner))))
+            },
+            '{' => self.object(),
+            _ => self.word(),
+        }
+    }
+
+    fn word(&mut self) -> Result<Ast, JqError>
+    {
+        let start = self.pos;
+
+        let name = match self.identifier() {
+            Some(name) => name,
+            None       => { return Err(self.error("unexpected character")); },
+        };
+
+        match name.as_str() {
+            "true"   => Ok(Ast::Literal(Json::from(true))),
+            "false"  => Ok(Ast::Literal(Json::from(false))),
+            "null"   => Ok(Ast::Literal(Json::from(()))),
+            "if"     => self.conditional(),
+            "reduce" => self.reduce(),
+            "try"    => {
+                let body = self.suffixed()?;
+                let handler = if self.keyword("catch") {
+                    Some(Box::new(self.suffixed()?))
+                } else {
+                    None
+                };
+
+                Ok(Ast::Try(Box::new(body), handler))
+            },
+            _ if KEYWORDS.contains(&name.as_str()) => {
+                Err(JqError::Compile { position: start, message: format!("unexpected keyword {}", name) })
+            },
+            _ => {
+                let mut args = vec![];
+
+                if self.peek() == Some('(') {
+                    self.pos += 1;
+                    loop {
+                        args.push(self.pipe(true)?);
+                        if self.eat(")") {
+                            break;
+                        }
+                        self.expect(";")?;
+                    }
+                }
+
+                if !builtins::exists(&name, args.len()) {
+                    return Err(JqError::Compile {
+                        position: start,
+                        message:  format!("{}/{} is not defined", name, args.len()),
+                    });
+                }
+
+                Ok(Ast::Call(name, args))
+            },
+        }
+    }
+
+    fn conditional(&mut self) -> Result<Ast, JqError>
+    {
+        let mut branches = vec![];
+
+        loop {
+            let condition = self.pipe(true)?;
+            if !self.keyword("then") {
+                return Err(self.error("expected 'then'"));
+            }
+            let body = self.pipe(true)?;
+            branches.push((condition, body));
+
+            if self.keyword("elif") {
+                continue;
+            }
+
+            let otherwise = if self.keyword("else") {
+                Some(Box::new(self.pipe(true)?))
+            } else {
+                None
+            };
+
+            if !self.keyword("end") {
+                return Err(self.error("expected 'end'"));
+            }
+
+            return Ok(Ast::If(branches, otherwise));
+        }
+    }
+
+    fn reduce(&mut self) -> Result<Ast, JqError>
+    {
+        let source = self.suffixed()?;
+
+        if !self.keyword("as") {
+            return Err(self.error("expected 'as'"));
+        }
+
+        let name = self.variable_name()?;
+        self.expect("(")?;
+        let init = self.pipe(true)?;
+        self.expect(";")?;
+        let update = self.pipe(true)?;
+        self.expect(")")?;
+
+        Ok(Ast::Reduce(Box::new(source), name, Box::new(init), Box::new(update)))
+    }
+
+    fn object(&mut self) -> Result<Ast, JqError>
+    {
+        self.expect("{")?;
+        let mut entries = vec![];
+
+        if self.eat("}") {
+            return Ok(Ast::Object(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+
+            let entry = match self.peek() {
+                Some('$') => {
+                    let name = self.variable_name()?;
+                    (ObjectKey::Name(name.clone()), Some(Ast::Variable(name)))
+                },
+                Some('"') => {
+                    let key = match self.template()? {
+                        parts if parts.iter().all(|part| matches!(part, StringPart::Text(_))) => {
+                            ObjectKey::Name(parts.into_iter().map(|part| match part {
+                                StringPart::Text(text) => text,
+                                _ => unreachable!(),
+                            }).collect())
+                        },
+                        parts => ObjectKey::Template(parts),
+                    };
+                    (key, self.object_value()?)
+                },
+                Some('(') => {
+                    self.pos += 1;
+                    let key = self.pipe(true)?;
+                    self.expect(")")?;
+                    self.expect(":")?;
+                    (ObjectKey::Expression(key), Some(self.pipe(false)?))
+                },
+                _ => match self.identifier() {
+                    Some(name) => (ObjectKey::Name(name), self.object_value()?),
+                    None => { return Err(self.error("expected object key")); },
+                },
+            };
+
+            entries.push(entry);
+
+            if self.eat("}") {
+                break;
+            }
+            self.expect(",")?;
+        }
+
+        Ok(Ast::Object(entries))
+    }
+
+    fn object_value(&mut self) -> Result<Option<Ast>, JqError>
+    {
+        if self.eat(":") {
+            return Ok(Some(self.pipe(false)?));
+        }
+
+        Ok(None)
+    }
+
+    fn number(&mut self) -> Result<Ast, JqError>
+    {
+        let start = self.pos;
+        let mut is_integer = true;
+
+        while let Some('0'..='9') = self.peek() {
+            self.pos += 1;
+        }
+
+        if self.peek() == Some('.') && matches!(self.peek_at(1), Some('0'..='9')) {
+            is_integer = false;
+            self.pos += 1;
+            while let Some('0'..='9') = self.peek() {
+                self.pos += 1;
+            }
+        }
+
+        if let Some('e') | Some('E') = self.peek() {
+            is_integer = false;
+            self.pos += 1;
+            if let Some('+') | Some('-') = self.peek() {
+                self.pos += 1;
+            }
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(self.error("invalid number"));
+            }
+            while let Some('0'..='9') = self.peek() {
+                self.pos += 1;
+            }
+        }
+
+        let source: String = self.chars[start..self.pos].iter().collect();
+
+        let number = match source.parse::<u64>() {
+            Ok(value) if is_integer => Number::Unsigned(value),
+            _ => Number::Float(source.parse().map_err(|_| self.error("invalid number"))?),
+        };
+
+        Ok(Ast::Literal(Json::Number(number, source)))
+    }
+
+    // A string without interpolations, as used in `."field"`.
+    fn plain_string(&mut self) -> Result<String, JqError>
+    {
+        let start = self.pos;
+        let parts = self.template()?;
+
+        let mut result = String::new();
+        for part in parts {
+            match part {
+                StringPart::Text(text) => result.push_str(&text),
+                StringPart::Interpolation(_) => {
+                    return Err(JqError::Compile { position: start, message: "interpolation not allowed here".to_string() });
+                },
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn template(&mut self) -> Result<Vec<StringPart>, JqError>
+    {
+        self.skip_whitespace();
+        if self.peek() != Some('"') {
+            return Err(self.error("expected string"));
+        }
+        self.pos += 1;
+
+        let mut parts = vec![];
+        let mut text  = String::new();
+
+        loop {
+            let current = match self.peek() {
+                Some(chr) => chr,
+                None      => { return Err(self.error("unterminated string")); },
+            };
+            self.pos += 1;
+
+            match current {
+                '"' => { break; },
+                '\\' => {
+                    let escaped = match self.peek() {
+                        Some(chr) => chr,
+                        None      => { return Err(self.error("unterminated string")); },
+                    };
+                    self.pos += 1;
+
+                    match escaped {
+                        '"'  => text.push('"'),
+                        '\\' => text.push('\\'),
+                        '/'  => text.push('/'),
+                        'b'  => text.push('\u{0008}'),
+                        'f'  => text.push('\u{000C}'),
+                        'n'  => text.push('\n'),
+                        'r'  => text.push('\r'),
+                        't'  => text.push('\t'),
+                        'u'  => {
+                            let digits: String = self.chars.iter().skip(self.pos).take(4).collect();
+                            let code = u32::from_str_radix(&digits, 16).ok().and_then(std::char::from_u32);
+                            match code {
+                                Some(chr) if digits.len() == 4 => { text.push(chr); self.pos += 4; },
+                                _ => { return Err(self.error("invalid unicode escape")); },
+                            }
+                        },
+                        '(' => {
+                            if !text.is_empty() {
+                                parts.push(StringPart::Text(std::mem::take(&mut text)));
+                            }
+                            let inner = self.pipe(true)?;
+                            self.expect(")")?;
+                            parts.push(StringPart::Interpolation(inner));
+                        },
+                        _ => { return Err(self.error("invalid escape")); },
+                    }
+                },
+                chr => text.push(chr),
+            }
+        }
+
+        if !text.is_empty() || parts.is_empty() {
+            parts.push(StringPart::Text(text));
+        }
+
+        Ok(parts)
+    }
+}
+
+fn template_ast(mut parts: Vec<StringPart>) -> Ast
+{
+    if parts.len() == 1 {
+        if let StringPart::Text(text) = &parts[0] {
+            return Ast::Literal(Json::from(text.clone()));
+        }
+    }
+
+    if parts.iter().all(|part| matches!(part, StringPart::Text(_))) {
+        let text = parts.drain(..).map(|part| match part {
+            StringPart::Text(text) => text,
+            _ => unreachable!(),
+        }).collect::<String>();
+
+        return Ast::Literal(Json::from(text));
+    }
+
+    Ast::Template(parts)
+}
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
+use std::fmt;
 
 
-use crate::error::Error;
+use crate::error::{ Error, ParseError };
 use crate::parser::node;
+use crate::equality::{ equal, NumberEquivalence };
 use crate::number::Number;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -17,12 +19,95 @@ pub enum Json {
 }
 
 impl Json {
+    /// Parses a document, a single value optionally surrounded by whitespace.
     pub fn parse(text: &str) -> Result<Json, Error>
     {
-        let mut slice    = text.chars();
+        Json::parse_with_position(text).map_err(|error| error.error)
+    }
+
+    /// Same as `parse`, but errors tell where in `text` parsing stopped.
+    pub fn parse_with_position(text: &str) -> Result<Json, ParseError>
+    {
+        let start = skip_whitespace(text, 0);
+
+        let mut slice    = text[start..].chars();
         let mut peekable = (&mut slice).peekable();
 
-        node(&mut peekable)
+        let result  = node(&mut peekable);
+        // Whatever was peeked has already been taken out of `slice`.
+        let pending = peekable.peek().map_or(0, |chr| chr.len_utf8());
+        let offset  = text.len() - slice.as_str().len() - pending;
+
+        let json = result.map_err(|error| ParseError::new(error, text, offset))?;
+        let end  = skip_whitespace(text, offset);
+
+        match text[end..].chars().next() {
+            Some(chr) => Err(ParseError::new(Error::InvalidCharacter(chr.to_string()), text, end)),
+            None      => Ok(json),
+        }
+    }
+
+    /// Regenerates the source of an object or array from the sources of its
+    /// children. Scalars are left untouched.
+    ///
+    /// Call this after mutating the children of a container in place, so
+    /// that `to_source` reflects the new contents.
+    pub fn refresh_source(&mut self)
+    {
+        match self {
+            Json::Object(value, source) => {
+                *source = object_source(value);
+            },
+            Json::Array(value, source) => {
+                *source = array_source(value);
+            },
+            _ => {},
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&HashMap<String, Json>>
+    {
+        match self {
+            Json::Object(value, _) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]>
+    {
+        match self {
+            Json::Array(value, _) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str>
+    {
+        match self {
+            Json::String(value, _) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<&Number>
+    {
+        match self {
+            Json::Number(value, _) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool>
+    {
+        match self {
+            Json::Boolean(value, _) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool
+    {
+        matches!(self, Json::Null(_))
     }
 
     pub fn to_source(&self) -> String
@@ -48,38 +133,167 @@ impl Json {
             },
         }
     }
+}
 
-    pub fn to_string(&self) -> String
+impl fmt::Display for Json
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
     {
-        self.to_source()
+        f.write_str(&self.to_source())
     }
 }
 
-impl From<HashMap<String, Json>> for Json
+// Compares two values ignoring their sources, numbers of different kinds are
+// equal when they hold the same value.
+pub(crate) fn values_equal(left: &Json, right: &Json) -> bool
 {
-    fn from(map: HashMap<String, Json>) -> Json
-    {
-        let mut source_string = String::new();
-        source_string.push('{');
-        let mut iterator = map.iter();
-        match iterator.next() {
-            Some((key, val)) => {
-                let part = format!("\"{}\":{}", key, &val.to_source());
-                source_string.push_str(&part);
-            },
-            None => {
-                source_string.push('}');
-                return Json::Object(map, source_string);
+    equal(left, right, NumberEquivalence::Value)
+}
+
+// Position of the first non-whitespace character at or after `position`.
+pub(crate) fn skip_whitespace(source: &str, position: usize) -> usize
+{
+    let rest = &source[position.min(source.len())..];
+    source.len() - rest.trim_start_matches([' ', '\t', '\r', '\n']).len()
+}
+
+// A member of an object, with offsets within the source of the object.
+pub(crate) struct Member
+{
+    pub key:         String,
+    pub value_start: usize,
+    pub value_end:   usize,
+}
+
+// Members of an object in the order they appear in its source, repeated keys
+// included. Values are skipped over, not parsed.
+pub(crate) fn member_offsets(source: &str) -> Option<Vec<Member>>
+{
+    let mut members  = vec![];
+    let mut position = skip_whitespace(source, 1);
+
+    while source[position..].starts_with('"') {
+        let (key, key_end) = key_at(source, position)?;
+        position = skip_whitespace(source, key_end);
+        position = skip_whitespace(source, position + 1);
+
+        let value_start = position;
+        let value_end   = value_end(source, position)?;
+        members.push(Member { key, value_start, value_end });
+
+        position = skip_whitespace(source, value_end);
+        position = skip_whitespace(source, position + source[position..].starts_with(',') as usize);
+    }
+
+    Some(members)
+}
+
+// The key at `position` and the offset after its closing quote.
+pub(crate) fn key_at(source: &str, position: usize) -> Option<(String, usize)>
+{
+    let end = string_end(source, position)?;
+    let mut slice = source[position..end].chars();
+
+    match crate::parser::string(&mut (&mut slice).peekable()) {
+        Ok(Json::String(key, _)) => Some((key, end)),
+        _ => None,
+    }
+}
+
+// Offset after the value at `position`, found by matching brackets and
+// quotes. The value is assumed to be valid.
+pub(crate) fn value_end(source: &str, position: usize) -> Option<usize>
+{
+    let bytes = source.as_bytes();
+
+    match bytes.get(position)? {
+        b'"' => string_end(source, position),
+        b'[' | b'{' => {
+            let (mut index, mut depth) = (position, 0);
+
+            loop {
+                match bytes.get(index)? {
+                    b'"' => index = string_end(source, index)? - 1,
+                    b'[' | b'{' => depth += 1,
+                    b']' | b'}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(index + 1);
+                        }
+                    },
+                    _ => {},
+                }
+                index += 1;
             }
-        };
+        },
+        _ => {
+            let rest = &source[position..];
+            let end  = rest.find([',', ']', '}', ' ', '\t', '\r', '\n']).unwrap_or(rest.len());
+
+            match end {
+                0 => None,
+                _ => Some(position + end),
+            }
+        },
+    }
+}
 
-        while let Some((key, val)) = iterator.next() {
-            let part = format!(",\"{}\":{}", key, &val.to_source());
-            source_string.push_str(&part);
+// Offset after the closing quote of the string at `position`.
+fn string_end(source: &str, position: usize) -> Option<usize>
+{
+    let bytes = source.as_bytes();
+    let mut index = position + 1;
+
+    loop {
+        match bytes.get(index)? {
+            b'"'  => return Some(index + 1),
+            b'\\' => index += 2,
+            _     => index += 1,
         }
+    }
+}
 
-        source_string.push('}');
-        Json::Object(map, source_string)
+// Builds an object whose source lists the members in the given order.
+pub(crate) fn object(members: Vec<(String, Json)>) -> Json
+{
+    let source = format!("{{{}}}", members.iter()
+        .map(|(key, value)| format!("{}:{}", quote(key), value.to_source()))
+        .collect::<Vec<String>>()
+        .join(","));
+
+    Json::Object(members.into_iter().collect::<HashMap<String, Json>>(), source)
+}
+
+// Writes `text` as a JSON string literal, escaping it where needed.
+pub(crate) fn quote(text: &str) -> String
+{
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+
+    for chr in text.chars() {
+        match chr {
+            '"'  => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\u{0008}' => quoted.push_str("\\b"),
+            '\u{000C}' => quoted.push_str("\\f"),
+            chr if (chr as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", chr as u32)),
+            chr => quoted.push(chr),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+impl From<HashMap<String, Json>> for Json
+{
+    fn from(map: HashMap<String, Json>) -> Json
+    {
+        let source = object_source(&map);
+        Json::Object(map, source)
     }
 }
 
@@ -87,38 +301,66 @@ impl From<Vec<Json>> for Json
 {
     fn from(vector: Vec<Json>) -> Json
     {
-        let mut source_string = String::new();
-        source_string.push('[');
-        let mut iterator = vector.iter();
-        match iterator.next() {
-            Some(val) => {
-                source_string.push_str(&val.to_source());
-            },
-            None => {
-                source_string.push(']');
-                return Json::Array(vector, source_string);
-            }
-        };
+        let source = array_source(&vector);
+        Json::Array(vector, source)
+    }
+}
+
+fn object_source(map: &HashMap<String, Json>) -> String
+{
+    let mut source_string = String::new();
+    source_string.push('{');
+    let mut iterator = map.iter();
+    match iterator.next() {
+        Some((key, val)) => {
+            let part = format!("{}:{}", quote(key), &val.to_source());
+            source_string.push_str(&part);
+        },
+        None => {
+            source_string.push('}');
+            return source_string;
+        }
+    };
 
-        while let Some(val) = iterator.next() {
-            source_string.push(',');
+    for (key, val) in iterator {
+        let part = format!(",{}:{}", quote(key), &val.to_source());
+        source_string.push_str(&part);
+    }
+
+    source_string.push('}');
+    source_string
+}
+
+fn array_source(vector: &[Json]) -> String
+{
+    let mut source_string = String::new();
+    source_string.push('[');
+    let mut iterator = vector.iter();
+    match iterator.next() {
+        Some(val) => {
             source_string.push_str(&val.to_source());
+        },
+        None => {
+            source_string.push(']');
+            return source_string;
         }
+    };
 
-        source_string.push(']');
-        return Json::Array(vector, source_string);
+    for val in iterator {
+        source_string.push(',');
+        source_string.push_str(&val.to_source());
     }
+
+    source_string.push(']');
+    source_string
 }
 
 impl From<String> for Json
 {
     fn from(string: String) -> Json
     {
-        let mut source_string = String::new();
-        source_string.push('\"');
-        source_string.push_str(&string);
-        source_string.push('\"');
-        Json::String(string, source_string)
+        let source = quote(&string);
+        Json::String(string, source)
     }
 }
 
@@ -126,11 +368,7 @@ impl<'a> From<&'a str> for Json
 {
     fn from(string: &'a str) -> Json
     {
-        let mut source_string = String::new();
-        source_string.push('\"');
-        source_string.push_str(&string);
-        source_string.push('\"');
-        Json::String(String::from(string), source_string)
+        Json::String(String::from(string), quote(string))
     }
 }
 
@@ -162,7 +400,7 @@ impl From<f64> for Json
 {
     fn from(number: f64) -> Json
     {
-        Json::Number(Number::Float(number), number.to_string())
+        Json::Number(Number::Float(number), Number::Float(number).to_string())
     }
 }
 
@@ -192,7 +430,7 @@ impl From<Json> for HashMap<String, Json>
     fn from(json: Json) -> HashMap<String, Json>
     {
         if let Json::Object(ref value, _) = json {
-            return value.clone();
+            value.clone()
 
         } else {
             panic!("Expecting Json::Boolean, got {:?}", json);
@@ -205,7 +443,7 @@ impl From<Json> for Vec<Json>
     fn from(json: Json) -> Vec<Json>
     {
         if let Json::Array(ref value, _) = json {
-            return value.clone();
+            value.clone()
 
         } else {
             panic!("Expecting Json::Boolean, got {:?}", json);
@@ -218,7 +456,7 @@ impl From<Json> for String
     fn from(json: Json) -> String
     {
         if let Json::String(ref value, _) = json {
-            return value.clone();
+            value.clone()
 
         } else {
             panic!("Expecting Json::String, got {:?}", json);
@@ -270,7 +508,7 @@ impl From<Json> for bool
     fn from(json: Json) -> bool
     {
         if let Json::Boolean(value, _) = json {
-            return value;
+            value
 
         } else {
             panic!("Expecting Json::Boolean, got {:?}", json);
@@ -0,0 +1,299 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+use crate::json::{ values_equal, Json };
+use crate::number::compare_numbers;
+use crate::regex::Regex;
+
+use super::{ Argument, Comparable, Function, Logical, NormalizedPath, Operator, PathElement, Query, QueryMatch, Segment, Selector };
+
+type Node<'a> = (NormalizedPath, &'a Json);
+
+pub fn query<'a>(segments: &[Segment], root: &'a Json) -> Vec<QueryMatch<'a>>
+{
+    select(segments, root, vec![(NormalizedPath::default(), root)])
+        .into_iter()
+        .map(|(path, value)| QueryMatch { path, value })
+        .collect()
+}
+
+fn select<'a>(segments: &[Segment], root: &'a Json, mut nodes: Vec<Node<'a>>) -> Vec<Node<'a>>
+{
+    for segment in segments {
+        let mut selected = vec![];
+
+        for (path, value) in &nodes {
+            match segment {
+                Segment::Child(selectors) => {
+                    for selector in selectors {
+                        apply(selector, root, path, value, &mut selected);
+                    }
+                },
+                Segment::Descendant(selectors) => {
+                    for (path, value) in descendants(path, value) {
+                        for selector in selectors {
+                            apply(selector, root, &path, value, &mut selected);
+                        }
+                    }
+                },
+            }
+        }
+
+        nodes = selected;
+    }
+
+    nodes
+}
+
+// The node itself followed by all of its descendants, in document order.
+fn descendants<'a>(path: &NormalizedPath, value: &'a Json) -> Vec<Node<'a>>
+{
+    let mut result = vec![];
+    let mut stack  = vec![(path.clone(), value)];
+
+    while let Some((path, value)) = stack.pop() {
+        let mut children = children(&path, value);
+        children.reverse();
+        stack.extend(children);
+        result.push((path, value));
+    }
+
+    result
+}
+
+fn children<'a>(path: &NormalizedPath, value: &'a Json) -> Vec<Node<'a>>
+{
+    match value {
+        Json::Array(vector, _) => {
+            vector.iter()
+                .enumerate()
+                .map(|(index, child)| (path.child(PathElement::Index(index)), child))
+                .collect()
+        },
+        Json::Object(map, _) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+
+            keys.into_iter()
+                .map(|key| (path.child(PathElement::Name(key.clone())), &map[key]))
+                .collect()
+        },
+        _ => vec![],
+    }
+}
+
+fn apply<'a>(selector: &Selector, root: &'a Json, path: &NormalizedPath, value: &'a Json, selected: &mut Vec<Node<'a>>)
+{
+    match selector {
+        Selector::Name(name) => {
+            if let Json::Object(map, _) = value {
+                if let Some(child) = map.get(name) {
+                    selected.push((path.child(PathElement::Name(name.clone())), child));
+                }
+            }
+        },
+        Selector::Wildcard => {
+            selected.extend(children(path, value));
+        },
+        Selector::Index(index) => {
+            if let Json::Array(vector, _) = value {
+                let len   = vector.len() as i64;
+                let index = if *index < 0 { len + index } else { *index };
+
+                if 0 <= index && index < len {
+                    selected.push((path.child(PathElement::Index(index as usize)), &vector[index as usize]));
+                }
+            }
+        },
+        Selector::Slice(start, end, step) => {
+            if let Json::Array(vector, _) = value {
+                for index in slice_indices(vector.len() as i64, *start, *end, step.unwrap_or(1)) {
+                    selected.push((path.child(PathElement::Index(index)), &vector[index]));
+                }
+            }
+        },
+        Selector::Filter(logical) => {
+            for (path, child) in children(path, value) {
+                if test(logical, root, child) {
+                    selected.push((path, child));
+                }
+            }
+        },
+    }
+}
+
+// Array slice semantics from RFC 9535, section 2.3.4.2.2.
+fn slice_indices(len: i64, start: Option<i64>, end: Option<i64>, step: i64) -> Vec<usize>
+{
+    let normalize = |index: i64| if index >= 0 { index } else { len + index };
+    let mut indices = vec![];
+
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).max(0).min(len);
+        let upper = normalize(end.unwrap_or(len)).max(0).min(len);
+
+        let mut index = lower;
+        while index < upper {
+            indices.push(index as usize);
+            index += step;
+        }
+    } else if step < 0 {
+        let upper = start.map_or(len - 1, normalize).max(-1).min(len - 1);
+        let lower = end.map_or(-1, normalize).max(-1).min(len - 1);
+
+        let mut index = upper;
+        while lower < index {
+            indices.push(index as usize);
+            index += step;
+        }
+    }
+
+    indices
+}
+
+fn nodes<'a>(query: &Query, root: &'a Json, current: &'a Json) -> Vec<&'a Json>
+{
+    let start = if query.absolute { root } else { current };
+
+    select(&query.segments, root, vec![(NormalizedPath::default(), start)])
+        .into_iter()
+        .map(|(_, value)| value)
+        .collect()
+}
+
+fn test(logical: &Logical, root: &Json, current: &Json) -> bool
+{
+    match logical {
+        Logical::Or(operands)  => operands.iter().any(|operand| test(operand, root, current)),
+        Logical::And(operands) => operands.iter().all(|operand| test(operand, root, current)),
+        Logical::Not(operand)  => !test(operand, root, current),
+        Logical::Exists(query) => !nodes(query, root, current).is_empty(),
+        Logical::Function(function) => logical_function(function, root, current),
+        Logical::Compare(left, operator, right) => {
+            let left  = comparable(left, root, current);
+            let right = comparable(right, root, current);
+
+            compare(left.as_deref(), *operator, right.as_deref())
+        },
+    }
+}
+
+fn comparable<'a>(comparable: &'a Comparable, root: &'a Json, current: &'a Json) -> Option<Cow<'a, Json>>
+{
+    match comparable {
+        Comparable::Literal(literal)   => Some(Cow::Borrowed(literal)),
+        Comparable::Query(query)       => nodes(query, root, current).first().map(|value| Cow::Borrowed(*value)),
+        Comparable::Function(function) => value_function(function, root, current),
+    }
+}
+
+fn compare(left: Option<&Json>, operator: Operator, right: Option<&Json>) -> bool
+{
+    match operator {
+        Operator::Equal        => equal(left, right),
+        Operator::NotEqual     => !equal(left, right),
+        Operator::Less         => less(left, right),
+        Operator::LessEqual    => less(left, right) || equal(left, right),
+        Operator::Greater      => less(right, left),
+        Operator::GreaterEqual => less(right, left) || equal(left, right),
+    }
+}
+
+fn equal(left: Option<&Json>, right: Option<&Json>) -> bool
+{
+    match (left, right) {
+        (None, None) => true,
+        (Some(left), Some(right)) => values_equal(left, right),
+        _ => false,
+    }
+}
+
+fn less(left: Option<&Json>, right: Option<&Json>) -> bool
+{
+    match (left, right) {
+        (Some(Json::Number(left, _)), Some(Json::Number(right, _))) => {
+            compare_numbers(left, right) == Some(Ordering::Less)
+        },
+        (Some(Json::String(left, _)), Some(Json::String(right, _))) => left < right,
+        _ => false,
+    }
+}
+
+fn argument_value<'a>(argument: &'a Argument, root: &'a Json, current: &'a Json) -> Option<Cow<'a, Json>>
+{
+    match argument {
+        Argument::Literal(literal)   => Some(Cow::Borrowed(literal)),
+        Argument::Query(query)       => nodes(query, root, current).first().map(|value| Cow::Borrowed(*value)),
+        Argument::Function(function) => value_function(function, root, current),
+        Argument::Logical(_)         => None,
+    }
+}
+
+fn argument_nodes<'a>(argument: &'a Argument, root: &'a Json, current: &'a Json) -> Vec<&'a Json>
+{
+    match argument {
+        Argument::Query(query) => nodes(query, root, current),
+        _ => vec![],
+    }
+}
+
+fn value_function<'a>(function: &'a Function, root: &'a Json, current: &'a Json) -> Option<Cow<'a, Json>>
+{
+    match function.name.as_str() {
+        "length" => {
+            let length = match argument_value(&function.args[0], root, current)?.as_ref() {
+                Json::String(value, _) => value.chars().count(),
+                Json::Array(value, _)  => value.len(),
+                Json::Object(value, _) => value.len(),
+                _ => { return None; },
+            };
+
+            Some(Cow::Owned(Json::from(length as u64)))
+        },
+        "count" => {
+            let count = argument_nodes(&function.args[0], root, current).len();
+            Some(Cow::Owned(Json::from(count as u64)))
+        },
+        "value" => {
+            let nodes = argument_nodes(&function.args[0], root, current);
+
+            if nodes.len() == 1 {
+                return Some(Cow::Borrowed(nodes[0]));
+            }
+
+            None
+        },
+        _ => None,
+    }
+}
+
+fn logical_function(function: &Function, root: &Json, current: &Json) -> bool
+{
+    match function.name.as_str() {
+        "match" | "search" => {
+            let text    = argument_value(&function.args[0], root, current);
+            let pattern = argument_value(&function.args[1], root, current);
+
+            let (text, pattern) = match (text.as_deref(), pattern.as_deref()) {
+                (Some(Json::String(text, _)), Some(Json::String(pattern, _))) => (text, pattern),
+                _ => { return false; },
+            };
+
+            let compiled;
+            let regex = match &function.regex {
+                Some(regex) => regex.as_ref(),
+                None => {
+                    compiled = Regex::new(pattern).ok();
+                    compiled.as_ref()
+                },
+            };
+
+            match regex {
+                Some(regex) if function.name == "match" => regex.is_full_match(text),
+                Some(regex) => regex.is_match(text),
+                None        => false,
+            }
+        },
+        _ => false,
+    }
+}
@@ -0,0 +1,201 @@
+//! JSONPath queries, following the semantics of RFC 9535.
+
+mod eval;
+mod parser;
+
+use std::fmt;
+
+use crate::json::Json;
+use crate::pointer::JsonPointer;
+use crate::regex::Regex;
+
+/// A compiled JSONPath query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonPath
+{
+    segments: Vec<Segment>,
+}
+
+/// Error produced when a query can't be compiled. It carries the character
+/// position where the problem was found.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathError
+{
+    pub position: usize,
+    pub message:  String,
+}
+
+impl fmt::Display for PathError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+/// One step of a normalized path.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PathElement {
+    Name(String),
+    Index(usize),
+}
+
+/// The normalized path of a node, printed as `$['store']['book'][0]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct NormalizedPath(pub Vec<PathElement>);
+
+/// A node selected by a query, along with its location in the document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryMatch<'a>
+{
+    pub path:  NormalizedPath,
+    pub value: &'a Json,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Child(Vec<Selector>),
+    Descendant(Vec<Selector>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(Logical),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Logical {
+    Or(Vec<Logical>),
+    And(Vec<Logical>),
+    Not(Box<Logical>),
+    Compare(Comparable, Operator, Comparable),
+    Exists(Query),
+    Function(Function),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Comparable {
+    Literal(Json),
+    Query(Query),
+    Function(Function),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Query
+{
+    absolute: bool,
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Function
+{
+    name:  String,
+    args:  Vec<Argument>,
+    // The pattern of match() and search() when it is a literal, compiled once
+    // with the query. `None` when it isn't a valid pattern.
+    regex: Option<Option<Regex>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Argument {
+    Literal(Json),
+    Query(Query),
+    Logical(Logical),
+    Function(Function),
+}
+
+impl JsonPath
+{
+    pub fn parse(query: &str) -> Result<JsonPath, PathError>
+    {
+        parser::parse(query)
+    }
+
+    /// Runs the query against `json`, returning the selected nodes in
+    /// document order. Object members are visited in key order.
+    pub fn query<'a>(&self, json: &'a Json) -> Vec<QueryMatch<'a>>
+    {
+        eval::query(&self.segments, json)
+    }
+}
+
+impl NormalizedPath
+{
+    pub fn elements(&self) -> &[PathElement]
+    {
+        &self.0
+    }
+
+    /// The same location as a JSON Pointer.
+    pub fn to_pointer(&self) -> JsonPointer
+    {
+        JsonPointer::from_tokens(self.0.iter().map(|element| match element {
+            PathElement::Name(name)   => name.clone(),
+            PathElement::Index(index) => index.to_string(),
+        }))
+    }
+
+    fn child(&self, element: PathElement) -> NormalizedPath
+    {
+        let mut elements = self.0.clone();
+        elements.push(element);
+        NormalizedPath(elements)
+    }
+}
+
+impl fmt::Display for NormalizedPath
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.write_str("$")?;
+
+        for element in &self.0 {
+            match element {
+                PathElement::Index(index) => write!(f, "[{}]", index)?,
+                PathElement::Name(name) => {
+                    f.write_str("['")?;
+                    for chr in name.chars() {
+                        match chr {
+                            '\'' => f.write_str("\\'")?,
+                            '\\' => f.write_str("\\\\")?,
+                            '\u{0008}' => f.write_str("\\b")?,
+                            '\u{000C}' => f.write_str("\\f")?,
+                            '\n' => f.write_str("\\n")?,
+                            '\r' => f.write_str("\\r")?,
+                            '\t' => f.write_str("\\t")?,
+                            chr if (chr as u32) < 0x20 => write!(f, "\\u{:04x}", chr as u32)?,
+                            chr => write!(f, "{}", chr)?,
+                        }
+                    }
+                    f.write_str("']")?;
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Json
+{
+    /// Compiles `query` and runs it against this document.
+    pub fn query(&self, query: &str) -> Result<Vec<QueryMatch<'_>>, PathError>
+    {
+        Ok(JsonPath::parse(query)?.query(self))
+    }
+}
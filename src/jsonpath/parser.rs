@@ -0,0 +1,745 @@
+use crate::json::Json;
+use crate::number::Number;
+use crate::regex::Regex;
+
+use super::{ Argument, Comparable, Function, JsonPath, Logical, Operator, PathError, Query, Segment, Selector };
+
+// Largest integer that can be represented exactly by an I-JSON number.
+const MAX_INTEGER: i64 = (1 << 53) - 1;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Type {
+    Value,
+    Logical,
+    Nodes,
+}
+
+pub fn parse(text: &str) -> Result<JsonPath, PathError>
+{
+    let mut parser = Parser { chars: text.chars().collect(), pos: 0 };
+
+    if !parser.eat('$') {
+        return Err(parser.error("expected '$'"));
+    }
+
+    let segments = parser.segments()?;
+
+    if parser.pos != parser.chars.len() {
+        return Err(parser.error("unexpected character"));
+    }
+
+    Ok(JsonPath { segments })
+}
+
+struct Parser
+{
+    chars: Vec<char>,
+    pos:   usize,
+}
+
+impl Parser
+{
+    fn error(&self, message: &str) -> PathError
+    {
+        PathError { position: self.pos, message: message.to_string() }
+    }
+
+    fn peek(&self) -> Option<char>
+    {
+        self.chars.get(self.pos).cloned()
+    }
+
+    fn eat(&mut self, expected: char) -> bool
+    {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            return true;
+        }
+
+        false
+    }
+
+    fn eat_str(&mut self, expected: &str) -> bool
+    {
+        let len = expected.chars().count();
+
+        if self.pos + len <= self.chars.len()
+            && self.chars[self.pos..self.pos + len].iter().cloned().eq(expected.chars())
+        {
+            self.pos += len;
+            return true;
+        }
+
+        false
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), PathError>
+    {
+        if self.eat(expected) {
+            return Ok(());
+        }
+
+        Err(self.error(&format!("expected '{}'", expected)))
+    }
+
+    fn skip_whitespace(&mut self)
+    {
+        while let Some(' ') | Some('\t') | Some('\n') | Some('\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn segments(&mut self) -> Result<Vec<Segment>, PathError>
+    {
+        let mut segments = vec![];
+
+        loop {
+            let start = self.pos;
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some('.') | Some('[') => segments.push(self.segment()?),
+                _ => {
+                    self.pos = start;
+                    break;
+                },
+            }
+        }
+
+        Ok(segments)
+    }
+
+    fn segment(&mut self) -> Result<Segment, PathError>
+    {
+        if self.eat_str("..") {
+            let selectors = match self.peek() {
+                Some('[') => self.bracketed()?,
+                Some('*') => { self.pos += 1; vec![Selector::Wildcard] },
+                _         => vec![Selector::Name(self.shorthand()?)],
+            };
+
+            return Ok(Segment::Descendant(selectors));
+        }
+
+        if self.eat('.') {
+            if self.eat('*') {
+                return Ok(Segment::Child(vec![Selector::Wildcard]));
+            }
+
+            return Ok(Segment::Child(vec![Selector::Name(self.shorthand()?)]));
+        }
+
+        Ok(Segment::Child(self.bracketed()?))
+    }
+
+    fn shorthand(&mut self) -> Result<String, PathError>
+    {
+        let mut name = String::new();
+
+        match self.peek() {
+            Some(chr) if is_name_first(chr) => { name.push(chr); self.pos += 1; },
+            _ => { return Err(self.error("expected member name")); },
+        }
+
+        while let Some(chr) = self.peek() {
+            if !is_name_first(chr) && !chr.is_ascii_digit() {
+                break;
+            }
+
+            name.push(chr);
+            self.pos += 1;
+        }
+
+        Ok(name)
+    }
+
+    fn bracketed(&mut self) -> Result<Vec<Selector>, PathError>
+    {
+        self.expect('[')?;
+        let mut selectors = vec![];
+
+        loop {
+            self.skip_whitespace();
+            selectors.push(self.selector()?);
+            self.skip_whitespace();
+
+            if self.eat(']') {
+                break;
+            }
+
+            self.expect(',')?;
+        }
+
+        Ok(selectors)
+    }
+
+    fn selector(&mut self) -> Result<Selector, PathError>
+    {
+        match self.peek() {
+            Some('\'') | Some('"') => Ok(Selector::Name(self.string()?)),
+            Some('*') => { self.pos += 1; Ok(Selector::Wildcard) },
+            Some('?') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                Ok(Selector::Filter(self.logical_or()?))
+            },
+            _ => self.index_or_slice(),
+        }
+    }
+
+    fn index_or_slice(&mut self) -> Result<Selector, PathError>
+    {
+        let start = self.optional_integer()?;
+        self.skip_whitespace();
+
+        if !self.eat(':') {
+            return match start {
+                Some(index) => Ok(Selector::Index(index)),
+                None        => Err(self.error("expected selector")),
+            };
+        }
+
+        self.skip_whitespace();
+        let end = self.optional_integer()?;
+        self.skip_whitespace();
+
+        let mut step = None;
+        if self.eat(':') {
+            self.skip_whitespace();
+            step = self.optional_integer()?;
+        }
+
+        Ok(Selector::Slice(start, end, step))
+    }
+
+    fn optional_integer(&mut self) -> Result<Option<i64>, PathError>
+    {
+        match self.peek() {
+            Some('-') | Some('0'..='9') => Ok(Some(self.integer()?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn integer(&mut self) -> Result<i64, PathError>
+    {
+        let start    = self.pos;
+        let negative = self.eat('-');
+
+        match self.peek() {
+            Some('0') if !negative => { self.pos += 1; },
+            Some('1'..='9') => {
+                while let Some('0'..='9') = self.peek() {
+                    self.pos += 1;
+                }
+            },
+            _ => { return Err(self.error("invalid integer")); },
+        }
+
+        let digits: String = self.chars[start..self.pos].iter().collect();
+
+        match digits.parse::<i64>() {
+            Ok(value) if (-MAX_INTEGER..=MAX_INTEGER).contains(&value) => Ok(value),
+            _ => Err(PathError { position: start, message: "integer out of range".to_string() }),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, PathError>
+    {
+        let quote = match self.peek() {
+            Some(quote @ '\'') | Some(quote @ '"') => quote,
+            _ => { return Err(self.error("expected string")); },
+        };
+        self.pos += 1;
+
+        let mut value = String::new();
+
+        loop {
+            let current = match self.peek() {
+                Some(chr) => chr,
+                None      => { return Err(self.error("unterminated string")); },
+            };
+            self.pos += 1;
+
+            match current {
+                chr if chr == quote => { break; },
+                '\\' => {
+                    let escaped = match self.peek() {
+                        Some(chr) => chr,
+                        None      => { return Err(self.error("unterminated string")); },
+                    };
+                    self.pos += 1;
+
+                    match escaped {
+                        'b'  => value.push('\u{0008}'),
+                        'f'  => value.push('\u{000C}'),
+                        'n'  => value.push('\n'),
+                        'r'  => value.push('\r'),
+                        't'  => value.push('\t'),
+                        '/'  => value.push('/'),
+                        '\\' => value.push('\\'),
+                        'u'  => value.push(self.unicode_escape()?),
+                        chr if chr == quote => value.push(chr),
+                        _ => { return Err(self.error("invalid escape")); },
+                    }
+                },
+                chr if (chr as u32) < 0x20 => { return Err(self.error("unescaped control character")); },
+                chr => value.push(chr),
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn hex4(&mut self) -> Result<u32, PathError>
+    {
+        if self.pos + 4 > self.chars.len() {
+            return Err(self.error("invalid unicode escape"));
+        }
+
+        let digits: String = self.chars[self.pos..self.pos + 4].iter().collect();
+
+        match u32::from_str_radix(&digits, 16) {
+            Ok(code) if digits.chars().all(|c| c.is_ascii_hexdigit()) => {
+                self.pos += 4;
+                Ok(code)
+            },
+            _ => Err(self.error("invalid unicode escape")),
+        }
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, PathError>
+    {
+        let high = self.hex4()?;
+
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.eat_str("\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate"));
+            }
+
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else if (0xDC00..0xE000).contains(&high) {
+            return Err(self.error("unpaired surrogate"));
+        } else {
+            high
+        };
+
+        std::char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn number(&mut self) -> Result<Json, PathError>
+    {
+        let start = self.pos;
+        let mut is_integer = true;
+
+        self.eat('-');
+
+        match self.peek() {
+            Some('0') => { self.pos += 1; },
+            Some('1'..='9') => {
+                while let Some('0'..='9') = self.peek() {
+                    self.pos += 1;
+                }
+            },
+            _ => { return Err(self.error("invalid number")); },
+        }
+
+        if self.peek() == Some('.') {
+            is_integer = false;
+            self.pos += 1;
+            if !self.digits() {
+                return Err(self.error("invalid number"));
+            }
+        }
+
+        if let Some('e') | Some('E') = self.peek() {
+            is_integer = false;
+            self.pos += 1;
+            if let Some('+') | Some('-') = self.peek() {
+                self.pos += 1;
+            }
+            if !self.digits() {
+                return Err(self.error("invalid number"));
+            }
+        }
+
+        let source: String = self.chars[start..self.pos].iter().collect();
+
+        let number = if is_integer && !source.starts_with('-') && source.parse::<u64>().is_ok() {
+            Number::Unsigned(source.parse().unwrap())
+        } else if is_integer && source.parse::<i64>().is_ok() {
+            Number::Integer(source.parse().unwrap())
+        } else {
+            Number::Float(source.parse().map_err(|_| self.error("invalid number"))?)
+        };
+
+        Ok(Json::Number(number, source))
+    }
+
+    fn digits(&mut self) -> bool
+    {
+        let start = self.pos;
+
+        while let Some('0'..='9') = self.peek() {
+            self.pos += 1;
+        }
+
+        self.pos > start
+    }
+
+    // Literals and function names can share a prefix (`true` and `trim()`),
+    // so keywords only count when they aren't followed by a name character.
+    fn keyword(&mut self, keyword: &str) -> bool
+    {
+        let start = self.pos;
+
+        if self.eat_str(keyword) {
+            match self.peek() {
+                Some(chr) if chr.is_ascii_alphanumeric() || chr == '_' || chr == '(' => {},
+                _ => { return true; },
+            }
+        }
+
+        self.pos = start;
+        false
+    }
+
+    fn literal(&mut self) -> Result<Option<Json>, PathError>
+    {
+        match self.peek() {
+            Some('\'') | Some('"') => Ok(Some(Json::from(self.string()?))),
+            Some('-') | Some('0'..='9') => Ok(Some(self.number()?)),
+            _ if self.keyword("true")  => Ok(Some(Json::from(true))),
+            _ if self.keyword("false") => Ok(Some(Json::from(false))),
+            _ if self.keyword("null")  => Ok(Some(Json::from(()))),
+            _ => Ok(None),
+        }
+    }
+
+    fn logical_or(&mut self) -> Result<Logical, PathError>
+    {
+        let mut operands = vec![self.logical_and()?];
+
+        loop {
+            let start = self.pos;
+            self.skip_whitespace();
+
+            if !self.eat_str("||") {
+                self.pos = start;
+                break;
+            }
+
+            self.skip_whitespace();
+            operands.push(self.logical_and()?);
+        }
+
+        if operands.len() == 1 {
+            return Ok(operands.pop().unwrap());
+        }
+
+        Ok(Logical::Or(operands))
+    }
+
+    fn logical_and(&mut self) -> Result<Logical, PathError>
+    {
+        let mut operands = vec![self.basic()?];
+
+        loop {
+            let start = self.pos;
+            self.skip_whitespace();
+
+            if !self.eat_str("&&") {
+                self.pos = start;
+                break;
+            }
+
+            self.skip_whitespace();
+            operands.push(self.basic()?);
+        }
+
+        if operands.len() == 1 {
+            return Ok(operands.pop().unwrap());
+        }
+
+        Ok(Logical::And(operands))
+    }
+
+    fn basic(&mut self) -> Result<Logical, PathError>
+    {
+        if self.eat('!') {
+            self.skip_whitespace();
+
+            let operand = if self.peek() == Some('(') {
+                self.parenthesized()?
+            } else {
+                let start = self.pos;
+                match self.comparable()? {
+                    Comparable::Query(query) => Logical::Exists(query),
+                    Comparable::Function(function) => self.test_function(function, start)?,
+                    Comparable::Literal(_) => {
+                        return Err(PathError { position: start, message: "expected query or function".to_string() });
+                    },
+                }
+            };
+
+            return Ok(Logical::Not(Box::new(operand)));
+        }
+
+        if self.peek() == Some('(') {
+            return self.parenthesized();
+        }
+
+        let start = self.pos;
+        let left  = self.comparable()?;
+
+        let before_operator = self.pos;
+        self.skip_whitespace();
+
+        match self.operator() {
+            Some(operator) => {
+                self.skip_whitespace();
+                let right_start = self.pos;
+                let right = self.comparable()?;
+
+                self.check_comparable(&left, start)?;
+                self.check_comparable(&right, right_start)?;
+
+                Ok(Logical::Compare(left, operator, right))
+            },
+            None => {
+                self.pos = before_operator;
+
+                match left {
+                    Comparable::Query(query) => Ok(Logical::Exists(query)),
+                    Comparable::Function(function) => self.test_function(function, start),
+                    Comparable::Literal(_) => {
+                        Err(PathError { position: start, message: "literal must be compared".to_string() })
+                    },
+                }
+            },
+        }
+    }
+
+    fn parenthesized(&mut self) -> Result<Logical, PathError>
+    {
+        self.expect('(')?;
+        self.skip_whitespace();
+        let inner = self.logical_or()?;
+        self.skip_whitespace();
+        self.expect(')')?;
+
+        Ok(inner)
+    }
+
+    fn test_function(&self, function: Function, start: usize) -> Result<Logical, PathError>
+    {
+        match result_type(&function.name) {
+            Type::Logical | Type::Nodes => Ok(Logical::Function(function)),
+            Type::Value => Err(PathError { position: start, message: format!("{}() result must be compared", function.name) }),
+        }
+    }
+
+    fn check_comparable(&self, comparable: &Comparable, start: usize) -> Result<(), PathError>
+    {
+        match comparable {
+            Comparable::Query(query) if !is_singular(query) => {
+                Err(PathError { position: start, message: "query in comparison must be singular".to_string() })
+            },
+            Comparable::Function(function) if result_type(&function.name) != Type::Value => {
+                Err(PathError { position: start, message: format!("{}() can't be compared", function.name) })
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn operator(&mut self) -> Option<Operator>
+    {
+        for (text, operator) in &[
+            ("==", Operator::Equal),
+            ("!=", Operator::NotEqual),
+            ("<=", Operator::LessEqual),
+            (">=", Operator::GreaterEqual),
+            ("<",  Operator::Less),
+            (">",  Operator::Greater),
+        ] {
+            if self.eat_str(text) {
+                return Some(*operator);
+            }
+        }
+
+        None
+    }
+
+    fn comparable(&mut self) -> Result<Comparable, PathError>
+    {
+        if let Some(literal) = self.literal()? {
+            return Ok(Comparable::Literal(literal));
+        }
+
+        match self.peek() {
+            Some('@') | Some('$') => Ok(Comparable::Query(self.query()?)),
+            Some('a'..='z') => Ok(Comparable::Function(self.function()?)),
+            _ => Err(self.error("expected comparable")),
+        }
+    }
+
+    fn query(&mut self) -> Result<Query, PathError>
+    {
+        let absolute = match self.peek() {
+            Some('$') => true,
+            Some('@') => false,
+            _ => { return Err(self.error("expected query")); },
+        };
+        self.pos += 1;
+
+        Ok(Query { absolute, segments: self.segments()? })
+    }
+
+    fn function(&mut self) -> Result<Function, PathError>
+    {
+        let start = self.pos;
+        let mut name = String::new();
+
+        while let Some(chr) = self.peek() {
+            if !(chr.is_ascii_lowercase() || chr.is_ascii_digit() || chr == '_') {
+                break;
+            }
+
+            name.push(chr);
+            self.pos += 1;
+        }
+
+        let parameters: &[Type] = match name.as_str() {
+            "length" => &[Type::Value],
+            "count"  => &[Type::Nodes],
+            "match"  => &[Type::Value, Type::Value],
+            "search" => &[Type::Value, Type::Value],
+            "value"  => &[Type::Nodes],
+            _ => {
+                return Err(PathError { position: start, message: format!("unknown function {}()", name) });
+            },
+        };
+
+        self.expect('(')?;
+        self.skip_whitespace();
+
+        let mut args = vec![];
+
+        if !self.eat(')') {
+            loop {
+                let arg_start = self.pos;
+                let arg = self.argument()?;
+
+                match parameters.get(args.len()) {
+                    Some(parameter) => check_argument(&arg, *parameter, arg_start)?,
+                    None => {
+                        return Err(PathError { position: arg_start, message: format!("too many arguments for {}()", name) });
+                    },
+                }
+
+                args.push(arg);
+                self.skip_whitespace();
+
+                if self.eat(')') {
+                    break;
+                }
+
+                self.expect(',')?;
+                self.skip_whitespace();
+            }
+        }
+
+        if args.len() != parameters.len() {
+            return Err(PathError { position: start, message: format!("wrong number of arguments for {}()", name) });
+        }
+
+        let regex = match (name.as_str(), args.get(1)) {
+            ("match", Some(Argument::Literal(Json::String(pattern, _))))
+            | ("search", Some(Argument::Literal(Json::String(pattern, _)))) => Some(Regex::new(pattern).ok()),
+            _ => None,
+        };
+
+        Ok(Function { name, args, regex })
+    }
+
+    fn argument(&mut self) -> Result<Argument, PathError>
+    {
+        let start = self.pos;
+
+        // A literal on its own is a value, otherwise it's the start of a
+        // comparison.
+        if let Some(literal) = self.literal()? {
+            self.skip_whitespace();
+            if let Some(',') | Some(')') = self.peek() {
+                return Ok(Argument::Literal(literal));
+            }
+            self.pos = start;
+        }
+
+        // Same for functions, which may return a value that can only be used
+        // as an argument or in a comparison.
+        if let Some('a'..='z') = self.peek() {
+            let function = self.function()?;
+            self.skip_whitespace();
+            if let Some(',') | Some(')') = self.peek() {
+                return Ok(Argument::Function(function));
+            }
+            self.pos = start;
+        }
+
+        Ok(match self.logical_or()? {
+            Logical::Exists(query) => Argument::Query(query),
+            logical => Argument::Logical(logical),
+        })
+    }
+}
+
+// Arguments are checked against the declared parameter types of the
+// function, following the well-typedness rules of RFC 9535, section 2.4.3.
+fn check_argument(arg: &Argument, parameter: Type, start: usize) -> Result<(), PathError>
+{
+    let valid = match (arg, parameter) {
+        (Argument::Literal(_), Type::Value) => true,
+        (Argument::Query(query), Type::Value) => is_singular(query),
+        (Argument::Function(function), Type::Value) => result_type(&function.name) == Type::Value,
+        (Argument::Query(_), Type::Nodes) => true,
+        (Argument::Function(function), Type::Nodes) => result_type(&function.name) == Type::Nodes,
+        (Argument::Logical(_), Type::Logical) => true,
+        (Argument::Query(_), Type::Logical) => true,
+        (Argument::Function(function), Type::Logical) => result_type(&function.name) != Type::Value,
+        _ => false,
+    };
+
+    if !valid {
+        return Err(PathError { position: start, message: "argument has the wrong type".to_string() });
+    }
+
+    Ok(())
+}
+
+fn result_type(name: &str) -> Type
+{
+    match name {
+        "match" | "search" => Type::Logical,
+        _ => Type::Value,
+    }
+}
+
+fn is_singular(query: &Query) -> bool
+{
+    query.segments.iter().all(|segment| match segment {
+        Segment::Child(selectors) => {
+            selectors.len() == 1 && matches!(selectors[0], Selector::Name(_) | Selector::Index(_))
+        },
+        Segment::Descendant(_) => false,
+    })
+}
+
+fn is_name_first(chr: char) -> bool
+{
+    chr.is_ascii_alphabetic() || chr == '_' || (chr as u32) >= 0x80
+}
@@ -1,10 +1,47 @@
+mod canonical;
+pub mod config;
+pub mod convert;
+pub mod diff;
+pub mod equality;
 mod error;
+pub mod flatten;
+pub mod format;
+mod hash;
+pub mod incremental;
+pub mod jq;
 mod json;
+pub mod jsonpath;
+#[doc(hidden)]
+pub mod macros;
+pub mod merge;
+mod merge_patch;
 mod number;
+mod order;
 mod parser;
+pub mod patch;
+pub mod pointer;
+pub mod recover;
+mod regex;
+pub mod schema;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod sha256;
+pub mod span;
+pub mod visit;
+pub mod walk;
 
-pub use self::error::Error;
+pub use self::convert::{ FromJson, ToJson };
+pub use self::error::{ Error, ParseError };
+pub use self::hash::Semantic;
 pub use self::json::Json;
+pub use self::jsonpath::JsonPath;
 pub use self::number::Number;
+pub use self::patch::Patch;
+pub use self::pointer::JsonPointer;
+pub use self::schema::Schema;
+#[cfg(feature = "serde")]
+pub use self::serde::{ from_str, to_string };
+#[cfg(feature = "derive")]
+pub use simple_json_derive::{ FromJson, ToJson };
 
 pub use self::parser::*;
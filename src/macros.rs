@@ -0,0 +1,91 @@
+//! The `json!` macro and the helpers its expansion calls.
+
+use crate::json::{ object as ordered_object, Json };
+use crate::number::Number;
+
+/// Builds a `Json` value from JSON-like syntax.
+///
+/// Any other Rust expression is converted through `Json::from`, so variables
+/// and nested `json!` calls can be interpolated. Keys are string literals or
+/// parenthesized expressions, and members keep their order in the source.
+///
+/// ```
+/// # #[macro_use] extern crate simple_json;
+/// # fn main() {
+/// let name = "simple_json";
+/// let json = json!({ "name": name, "tags": ["json", null], "stars": 5 });
+///
+/// assert_eq!(json.to_source(), "{\"name\":\"simple_json\",\"tags\":[\"json\",null],\"stars\":5}");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! json {
+    (null)  => { $crate::macros::value(()) };
+    (true)  => { $crate::macros::value(true) };
+    (false) => { $crate::macros::value(false) };
+
+    ([]) => { $crate::macros::array(::std::vec::Vec::new()) };
+    ([ $($tt:tt)+ ]) => { $crate::macros::array($crate::json!(@array [] () $($tt)+)) };
+
+    ({}) => { $crate::macros::object(::std::vec::Vec::new()) };
+    ({ $($tt:tt)+ }) => { $crate::macros::object($crate::json!(@object [] ($($tt)+))) };
+
+    // Array elements are gathered one token at a time, until a comma.
+    (@array [$($elements:expr,)*] ($($current:tt)+) , $($rest:tt)*) => {
+        $crate::json!(@array [$($elements,)* $crate::json!($($current)+),] () $($rest)*)
+    };
+    (@array [$($elements:expr,)*] ($($current:tt)*) $next:tt $($rest:tt)*) => {
+        $crate::json!(@array [$($elements,)*] ($($current)* $next) $($rest)*)
+    };
+    (@array [$($elements:expr,)*] ($($current:tt)+)) => {
+        ::std::vec![$($elements,)* $crate::json!($($current)+)]
+    };
+    (@array [$($elements:expr,)*] ()) => {
+        ::std::vec![$($elements,)*]
+    };
+
+    // Object members, first the key and then the value tokens.
+    (@object [$($members:expr,)*] ()) => {
+        ::std::vec![$($members,)*]
+    };
+    (@object [$($members:expr,)*] ($key:literal : $($rest:tt)*)) => {
+        $crate::json!(@member [$($members,)*] ($key) () ($($rest)*))
+    };
+    (@object [$($members:expr,)*] (($key:expr) : $($rest:tt)*)) => {
+        $crate::json!(@member [$($members,)*] ($key) () ($($rest)*))
+    };
+    (@member [$($members:expr,)*] ($key:expr) ($($current:tt)+) (, $($rest:tt)*)) => {
+        $crate::json!(@object [$($members,)* (::std::string::String::from($key), $crate::json!($($current)+)),] ($($rest)*))
+    };
+    (@member [$($members:expr,)*] ($key:expr) ($($current:tt)+) ()) => {
+        $crate::json!(@object [$($members,)* (::std::string::String::from($key), $crate::json!($($current)+)),] ())
+    };
+    (@member [$($members:expr,)*] ($key:expr) ($($current:tt)*) ($next:tt $($rest:tt)*)) => {
+        $crate::json!(@member [$($members,)*] ($key) ($($current)* $next) ($($rest)*))
+    };
+
+    ($other:expr) => { $crate::macros::value($other) };
+}
+
+/// Converts an interpolated value. Non-negative integers are unsigned, the
+/// same as when parsing.
+#[doc(hidden)]
+pub fn value<T: Into<Json>>(value: T) -> Json
+{
+    match value.into() {
+        Json::Number(Number::Integer(value), _) if value >= 0 => Json::from(value as u64),
+        json => json,
+    }
+}
+
+#[doc(hidden)]
+pub fn array(elements: Vec<Json>) -> Json
+{
+    Json::from(elements)
+}
+
+#[doc(hidden)]
+pub fn object(members: Vec<(String, Json)>) -> Json
+{
+    ordered_object(members)
+}
@@ -0,0 +1,317 @@
+//! Deep merging of layered documents, like defaults overridden by the
+//! environment and then by the user.
+//!
+//! Objects are merged member by member. How arrays are combined, and what
+//! happens when two layers give different values, is set by `MergeOptions`
+//! for the whole document and by `MergeRule`s for parts of it. Unlike
+//! `Json::merge_patch`, a `null` is a value like any other.
+//!
+//! Every node of the result comes with the layer it's from, see `Origin`.
+
+use std::collections::{ HashMap, HashSet };
+
+use crate::error::Error;
+use crate::format::Orders;
+use crate::hash::Semantic;
+use crate::json::{ object, values_equal, Json };
+use crate::pointer::JsonPointer;
+
+/// How two arrays are combined.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArrayMerge
+{
+    /// The later array replaces the earlier one.
+    Replace,
+    /// Elements of the later array go after the earlier ones.
+    Append,
+    /// Like `Append`, leaving out elements equal to one already there.
+    Union,
+    /// Objects with the same value for this member are merged, other
+    /// elements are appended.
+    ByKey(String),
+}
+
+/// What to do when two layers give different values that can't be merged:
+/// scalars, or an object and an array.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Conflict
+{
+    LeftWins,
+    RightWins,
+    Error,
+}
+
+/// Strategies for the node at `path` and below, unless a rule for a longer
+/// path says otherwise. Strategies left to `None` are inherited.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MergeRule
+{
+    pub path:      JsonPointer,
+    pub arrays:    Option<ArrayMerge>,
+    pub conflicts: Option<Conflict>,
+}
+
+/// Settings for `Json::merge_with`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MergeOptions
+{
+    pub arrays:    ArrayMerge,
+    pub conflicts: Conflict,
+    pub rules:     Vec<MergeRule>,
+}
+
+impl Default for MergeOptions
+{
+    fn default() -> MergeOptions
+    {
+        MergeOptions { arrays: ArrayMerge::Replace, conflicts: Conflict::RightWins, rules: vec![] }
+    }
+}
+
+/// Where a node of the result comes from: its layer, and its pointer in
+/// that layer. Arrays and objects are from the last layer that had them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Origin
+{
+    pub layer:   usize,
+    pub pointer: JsonPointer,
+}
+
+/// Result of `Json::merge`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Merged
+{
+    pub value:   Json,
+    pub origins: HashMap<JsonPointer, Origin>,
+}
+
+impl Merged
+{
+    /// Origin of the node at `pointer` in the result.
+    pub fn origin(&self, pointer: &JsonPointer) -> Option<&Origin>
+    {
+        self.origins.get(pointer)
+    }
+}
+
+impl Json
+{
+    /// Merges layers, each one overriding the ones before it. No layers
+    /// give an empty object.
+    pub fn merge(layers: &[Json]) -> Result<Merged, Error>
+    {
+        Json::merge_with(layers, &MergeOptions::default())
+    }
+
+    pub fn merge_with(layers: &[Json], options: &MergeOptions) -> Result<Merged, Error>
+    {
+        let mut trees = layers.iter().enumerate().map(|(layer, json)| Tree::new(json, layer));
+
+        let mut merged = match trees.next() {
+            Some(tree) => tree,
+            None       => Tree::Object(vec![], Origin { layer: 0, pointer: JsonPointer::root() }),
+        };
+
+        for tree in trees {
+            merged = merge(merged, tree, &JsonPointer::root(), options)?;
+        }
+
+        let mut origins = HashMap::new();
+        let value = merged.build(&JsonPointer::root(), &mut origins);
+
+        Ok(Merged { value, origins })
+    }
+}
+
+// A layer, or the merge of several, with the origin of every node.
+enum Tree
+{
+    Leaf(Json, Origin),
+    Array(Vec<Tree>, Origin),
+    Object(Vec<(String, Tree)>, Origin),
+}
+
+// An empty array, left in place of a node while it's merged.
+impl Default for Tree
+{
+    fn default() -> Tree
+    {
+        Tree::Array(vec![], Origin { layer: 0, pointer: JsonPointer::root() })
+    }
+}
+
+impl Tree
+{
+    fn new(json: &Json, layer: usize) -> Tree
+    {
+        Tree::node(json, layer, &JsonPointer::root(), &Orders::new(json))
+    }
+
+    fn node(json: &Json, layer: usize, pointer: &JsonPointer, orders: &Orders) -> Tree
+    {
+        let origin = Origin { layer, pointer: pointer.clone() };
+
+        match json {
+            Json::Array(elements, _) => Tree::Array(
+                elements.iter().enumerate().map(|(index, element)| Tree::node(element, layer, &pointer.child(index.to_string()), orders)).collect(),
+                origin,
+            ),
+            Json::Object(members, source) => Tree::Object(
+                orders.ordered(pointer, members, source).into_iter()
+                    .map(|(key, value)| (key.clone(), Tree::node(value, layer, &pointer.child(key.as_str()), orders)))
+                    .collect(),
+                origin,
+            ),
+            _ => Tree::Leaf(json.clone(), origin),
+        }
+    }
+
+    // The value, without origins.
+    fn json(&self) -> Json
+    {
+        match self {
+            Tree::Leaf(json, _)      => json.clone(),
+            Tree::Array(elements, _) => Json::from(elements.iter().map(Tree::json).collect::<Vec<_>>()),
+            Tree::Object(members, _) => object(members.iter().map(|(key, value)| (key.clone(), value.json())).collect()),
+        }
+    }
+
+    fn build(self, pointer: &JsonPointer, origins: &mut HashMap<JsonPointer, Origin>) -> Json
+    {
+        let (json, origin) = match self {
+            Tree::Leaf(json, origin) => (json, origin),
+            Tree::Array(elements, origin) => {
+                let elements = elements.into_iter()
+                    .enumerate()
+                    .map(|(index, element)| element.build(&pointer.child(index.to_string()), origins))
+                    .collect::<Vec<_>>();
+                (Json::from(elements), origin)
+            },
+            Tree::Object(members, origin) => {
+                let members = members.into_iter()
+                    .map(|(key, value)| {
+                        let value = value.build(&pointer.child(key.as_str()), origins);
+                        (key, value)
+                    })
+                    .collect();
+                (object(members), origin)
+            },
+        };
+
+        origins.insert(pointer.clone(), origin);
+        json
+    }
+}
+
+// Strategies that apply at `path`, from the longest matching rules.
+fn strategies<'a>(path: &JsonPointer, options: &'a MergeOptions) -> (&'a ArrayMerge, Conflict)
+{
+    let mut arrays    = (0, &options.arrays);
+    let mut conflicts = (0, options.conflicts);
+
+    for rule in options.rules.iter().filter(|rule| rule.path.is_prefix_of(path)) {
+        let length = rule.path.len() + 1;
+
+        match &rule.arrays {
+            Some(strategy) if length >= arrays.0 => arrays = (length, strategy),
+            _ => {},
+        }
+        match rule.conflicts {
+            Some(strategy) if length >= conflicts.0 => conflicts = (length, strategy),
+            _ => {},
+        }
+    }
+
+    (arrays.1, conflicts.1)
+}
+
+fn merge(left: Tree, right: Tree, path: &JsonPointer, options: &MergeOptions) -> Result<Tree, Error>
+{
+    let (arrays, conflicts) = strategies(path, options);
+
+    match (left, right) {
+        (Tree::Object(mut members, _), Tree::Object(others, origin)) => {
+            let mut positions: HashMap<String, usize> = members.iter().enumerate().map(|(index, (key, _))| (key.clone(), index)).collect();
+
+            for (key, value) in others {
+                match positions.get(&key) {
+                    Some(&index) => {
+                        let current = std::mem::take(&mut members[index].1);
+                        members[index].1 = merge(current, value, &path.child(key.as_str()), options)?;
+                    },
+                    None => {
+                        positions.insert(key.clone(), members.len());
+                        members.push((key, value));
+                    },
+                }
+            }
+
+            Ok(Tree::Object(members, origin))
+        },
+        (Tree::Array(mut elements, _), Tree::Array(others, origin)) => {
+            match arrays {
+                ArrayMerge::Replace => return Ok(Tree::Array(others, origin)),
+                ArrayMerge::Append  => elements.extend(others),
+                ArrayMerge::Union => {
+                    let mut values: HashSet<Semantic> = elements.iter().map(|element| Semantic(element.json())).collect();
+
+                    for other in others {
+                        if values.insert(Semantic(other.json())) {
+                            elements.push(other);
+                        }
+                    }
+                },
+                ArrayMerge::ByKey(name) => {
+                    // Index of the first element with each key.
+                    let mut indices: HashMap<Semantic, usize> = HashMap::new();
+                    for (index, element) in elements.iter().enumerate() {
+                        if let Some(key) = key_of(element, name) {
+                            indices.entry(Semantic(key)).or_insert(index);
+                        }
+                    }
+
+                    for other in others {
+                        let key = key_of(&other, name).map(Semantic);
+
+                        match key.as_ref().and_then(|key| indices.get(key)) {
+                            Some(&index) => {
+                                let current = std::mem::take(&mut elements[index]);
+                                elements[index] = merge(current, other, &path.child(index.to_string()), options)?;
+                            },
+                            None => {
+                                if let Some(key) = key {
+                                    indices.insert(key, elements.len());
+                                }
+                                elements.push(other);
+                            },
+                        }
+                    }
+                },
+            }
+
+            Ok(Tree::Array(elements, origin))
+        },
+        (left, right) => {
+            if let (Tree::Leaf(left, _), Tree::Leaf(value, _)) = (&left, &right) {
+                if values_equal(left, value) {
+                    return Ok(right);
+                }
+            }
+
+            match conflicts {
+                Conflict::LeftWins  => Ok(left),
+                Conflict::RightWins => Ok(right),
+                Conflict::Error     => Err(Error::MergeConflict(path.to_string())),
+            }
+        },
+    }
+}
+
+// Value of the member `name` of an object element.
+fn key_of(tree: &Tree, name: &str) -> Option<Json>
+{
+    match tree {
+        Tree::Object(members, _) => members.iter().find(|(key, _)| key == name).map(|(_, value)| value.json()),
+        _ => None,
+    }
+}
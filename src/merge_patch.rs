@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use crate::json::{ values_equal, Json };
+
+impl Json
+{
+    /// Applies a JSON Merge Patch, as described by RFC 7396.
+    ///
+    /// Members of an object patch are merged recursively into the target,
+    /// `null` members delete the corresponding key. Any other kind of patch
+    /// replaces the target entirely.
+    pub fn merge_patch(&mut self, patch: &Json)
+    {
+        let members = match patch {
+            Json::Object(members, _) => members,
+            _ => {
+                *self = patch.clone();
+                return;
+            },
+        };
+
+        if !matches!(self, Json::Object(_, _)) {
+            *self = Json::from(HashMap::new());
+        }
+
+        if let Json::Object(target, _) = self {
+            for (key, value) in members {
+                if let Json::Null(_) = value {
+                    target.remove(key);
+                    continue;
+                }
+
+                target.entry(key.clone())
+                    .or_insert_with(|| Json::from(()))
+                    .merge_patch(value);
+            }
+        }
+
+        self.refresh_source();
+    }
+
+    /// Computes the merge patch that turns `from` into `to`.
+    ///
+    /// Merge patches can't set a member to `null`, so `null` members of `to`
+    /// that differ from `from` end up being removed instead.
+    pub fn create_merge_patch(from: &Json, to: &Json) -> Json
+    {
+        let (from, to) = match (from, to) {
+            (Json::Object(from, _), Json::Object(to, _)) => (from, to),
+            _ => { return to.clone(); },
+        };
+
+        let mut patch = HashMap::new();
+
+        for key in from.keys() {
+            if !to.contains_key(key) {
+                patch.insert(key.clone(), Json::from(()));
+            }
+        }
+
+        for (key, value) in to {
+            match from.get(key) {
+                Some(previous) if values_equal(previous, value) => {},
+                Some(previous) => {
+                    patch.insert(key.clone(), Json::create_merge_patch(previous, value));
+                },
+                None => {
+                    patch.insert(key.clone(), Json::create_merge_patch(&Json::from(()), value));
+                },
+            }
+        }
+
+        Json::from(patch)
+    }
+}
@@ -1,3 +1,6 @@
+use std::cmp::Ordering;
+use std::fmt;
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Number
 {
@@ -6,14 +9,79 @@ pub enum Number
     Float(f64),
 }
 
-impl Number
+impl fmt::Display for Number
 {
-    pub fn to_string(&self) -> String
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
     {
         match self {
-            Number::Unsigned(value) => value.to_string(),
-            Number::Integer(value)  => value.to_string(),
-            Number::Float(value)    => value.to_string(),
+            Number::Unsigned(value) => write!(f, "{}", value),
+            Number::Integer(value)  => write!(f, "{}", value),
+            // Negative zero is still written as `0`.
+            Number::Float(value) if *value == 0. => f.write_str("0"),
+            Number::Float(value)    => write!(f, "{}", value),
+        }
+    }
+}
+
+impl Number
+{
+    /// Formats the number like ECMAScript's `Number.prototype.toString`, the
+    /// form RFC 8785 requires. Integers are converted to `f64` first, so the
+    /// ones beyond 2^53 lose precision as they would in JavaScript.
+    pub fn to_ecmascript_string(&self) -> String
+    {
+        let value = f64::from(self.clone());
+
+        if value == 0. {
+            return "0".to_string();
+        }
+
+        if !value.is_finite() {
+            return match value {
+                value if value.is_nan() => "NaN".to_string(),
+                value if value < 0.     => "-Infinity".to_string(),
+                _                       => "Infinity".to_string(),
+            };
+        }
+
+        if value < 0. {
+            return format!("-{}", Number::Float(-value).to_ecmascript_string());
+        }
+
+        // Rust gives the shortest digits that round-trip, as ECMAScript does,
+        // but when several are as short ECMAScript wants the closest to the
+        // exact value, ties going to the even digit. Formatting again with
+        // that many digits rounds the exact value that way.
+        let shortest = format!("{:e}", value);
+        let closest  = format!("{:.*e}", shortest.find('e').unwrap().saturating_sub(2), value);
+        let scientific = match closest.parse::<f64>() {
+            Ok(parsed) if parsed == value => closest,
+            _ => shortest,
+        };
+
+        // The value is `0.digits` times 10 to the `exponent`.
+        let (mantissa, exponent) = scientific.split_once('e').unwrap();
+        let digits: String = mantissa.chars().filter(|chr| *chr != '.').collect();
+        let length   = digits.len() as i32;
+        let exponent = exponent.parse::<i32>().unwrap() + 1;
+
+        match exponent {
+            exponent if length <= exponent && exponent <= 21 => digits + &"0".repeat((exponent - length) as usize),
+            exponent if 0 < exponent && exponent <= 21 => {
+                format!("{}.{}", &digits[..exponent as usize], &digits[exponent as usize..])
+            },
+            exponent if -6 < exponent && exponent <= 0 => format!("0.{}{}", "0".repeat(-exponent as usize), digits),
+            exponent => {
+                let sign = match exponent > 0 {
+                    true  => '+',
+                    false => '-',
+                };
+
+                match length {
+                    1 => format!("{}e{}{}", digits, sign, (exponent - 1).abs()),
+                    _ => format!("{}.{}e{}{}", &digits[..1], &digits[1..], sign, (exponent - 1).abs()),
+                }
+            },
         }
     }
 }
@@ -78,3 +146,48 @@ impl From<Number> for f64
     }
 }
 
+// Compares the exact values, `None` when a NaN is involved.
+pub(crate) fn compare_numbers(left: &Number, right: &Number) -> Option<Ordering>
+{
+    match (left, right) {
+        (Number::Float(left), Number::Float(right)) => left.partial_cmp(right),
+        (Number::Float(left), _) => compare_float(*left, as_i128(right)),
+        (_, Number::Float(right)) => compare_float(*right, as_i128(left)).map(Ordering::reverse),
+        _ => Some(as_i128(left).cmp(&as_i128(right))),
+    }
+}
+
+// Compares a float to an integer without rounding either of them.
+fn compare_float(float: f64, integer: i128) -> Option<Ordering>
+{
+    // 2^127, past the range of `i128`.
+    const LIMIT: f64 = 170141183460469231731687303715884105728.;
+
+    if float.is_nan() {
+        return None;
+    }
+
+    if float >= LIMIT {
+        return Some(Ordering::Greater);
+    }
+
+    if float < -LIMIT {
+        return Some(Ordering::Less);
+    }
+
+    let truncated = float.trunc();
+
+    match (truncated as i128).cmp(&integer) {
+        Ordering::Equal => float.partial_cmp(&truncated),
+        ordering        => Some(ordering),
+    }
+}
+
+pub(crate) fn as_i128(number: &Number) -> i128
+{
+    match number {
+        Number::Unsigned(value) => i128::from(*value),
+        Number::Integer(value)  => i128::from(*value),
+        Number::Float(value)    => *value as i128,
+    }
+}
@@ -24,7 +24,7 @@ pub fn array(slice: &mut Peekable<&mut Chars>) -> Result<Json, Error>
     'tokenizer: loop {
         let current = match slice.peek() {
             Some(chr) => *chr,
-            None      => { break 'tokenizer },
+            None      => { return Err(Error::UnexpectedEof); },
         };
 
         // absorb all whitespace into the array source
@@ -56,10 +56,7 @@ pub fn array(slice: &mut Peekable<&mut Chars>) -> Result<Json, Error>
                 _   => {
                     stage = Stages::Comma;
 
-                    let node = match node(slice) {
-                        Ok(node) => node,
-                        Err(e)   => { return Err(e) },
-                    };
+                    let node = node(slice)?;
 
                     source.push_str(&node.to_source());
 
@@ -82,10 +79,7 @@ pub fn array(slice: &mut Peekable<&mut Chars>) -> Result<Json, Error>
             Stages::Value => {
                 stage = Stages::Comma;
 
-                let node = match node(slice) {
-                    Ok(node) => node,
-                    Err(e)   => { return Err(e) },
-                };
+                let node = node(slice)?;
                 source.push_str(&node.to_source());
 
                 array.push(node);
@@ -5,40 +5,22 @@ use crate::error::Error;
 use crate::json::Json;
 use crate::*;
 
+/// Parses a single value, leaving whatever follows it untouched.
 pub fn node(slice: &mut Peekable<&mut Chars>) -> Result<Json, Error>
 {
-    let mut content = Err(Error::UnexpectedEof);
+    let current = match slice.peek() {
+        Some(chr) => *chr,
+        None      => { return Err(Error::UnexpectedEof); },
+    };
 
-    'tokenizer: loop {
-        let current = match slice.peek() {
-            Some(chr) => *chr,
-            None      => { break 'tokenizer },
-        };
+    match current {
+        'n'             => null(slice),
+        'f' | 't'       => boolean(slice),
+        '0'..='9' | '-' => number(slice),
+        '"'             => string(slice),
+        '['             => array(slice),
+        '{'             => object(slice),
 
-        match current {
-            'n' => {
-                content = null(slice);
-            },
-            'f' | 't' => {
-                content = boolean(slice);
-            },
-            '0'..='9' | '-' => {
-                content = number(slice);
-            },
-            '"' => {
-                content = string(slice);
-            },
-            '[' => {
-                content = array(slice);
-            },
-            '{' => {
-                content = object(slice);
-            },
-
-            _ => { break 'tokenizer }
-        }
+        _ => Err(Error::InvalidCharacter(current.to_string())),
     }
-
-    content
 }
-
@@ -20,7 +20,6 @@ pub fn number(slice: &mut Peekable<&mut Chars>) -> Result<Json, Error>
         AfterExp,
         AfterExpSign,
         Exponent,
-        End,
     }
 
     let mut stage       = Stages::Sign;
@@ -48,8 +47,6 @@ pub fn number(slice: &mut Peekable<&mut Chars>) -> Result<Json, Error>
             Stages::AfterSign => match current {
                 '0'       => { stage = Stages::AfterZero; token.push(current); slice.next(); },
                 '1'..='9' => { stage = Stages::Integer;   token.push(current); slice.next(); },
-                '.'       => { stage = Stages::AfterDot;  token.push(current); slice.next(); },
-                'e' | 'E' => { stage = Stages::AfterExp;  token.push(current); slice.next(); },
 
                 // Waiting for a number.
                 _ => {
@@ -69,9 +66,8 @@ pub fn number(slice: &mut Peekable<&mut Chars>) -> Result<Json, Error>
             },
             Stages::AfterDot => match current {
                 '0'..='9' => { stage = Stages::Fraction; token.push(current); slice.next(); },
-                'e' | 'E' => { stage = Stages::AfterExp; token.push(current); slice.next(); },
 
-                // Waiting for a number.
+                // Waiting for a digit.
                 _ => {
                     return Err(Error::InvalidCharacter(current.to_string()));
                 },
@@ -83,8 +79,7 @@ pub fn number(slice: &mut Peekable<&mut Chars>) -> Result<Json, Error>
             },
             Stages::AfterExp => match current {
                 '+' | '-' => { stage = Stages::AfterExpSign; token.push(current); slice.next(); },
-                '0'       => { stage = Stages::End;          token.push(current); slice.next(); },
-                '1'..='9' => { stage = Stages::Exponent;     token.push(current); slice.next(); },
+                '0'..='9' => { stage = Stages::Exponent;     token.push(current); slice.next(); },
 
                 // Waiting for a number.
                 _ => {
@@ -92,7 +87,7 @@ pub fn number(slice: &mut Peekable<&mut Chars>) -> Result<Json, Error>
                 },
             },
             Stages::AfterExpSign => match current {
-                '1'..='9' => { stage = Stages::Exponent; token.push(current); slice.next(); },
+                '0'..='9' => { stage = Stages::Exponent; token.push(current); slice.next(); },
 
                 // Waiting for a number.
                 _ => {
@@ -103,25 +98,30 @@ pub fn number(slice: &mut Peekable<&mut Chars>) -> Result<Json, Error>
                 '0'..='9' => { token.push(current); slice.next(); },
                 _         => { break 'tokenizer; },
             },
-            Stages::End => match current {
-                _         => { break 'tokenizer; },
-            },
         }
 
         source.push(current);
     }
 
+    match stage {
+        Stages::Sign | Stages::AfterSign | Stages::AfterDot | Stages::AfterExp | Stages::AfterExpSign => {
+            return Err(Error::UnexpectedEof);
+        },
+        _ => {},
+    }
+
     Ok(Json::Number(
         match stage {
-            Stages::Integer | Stages::AfterZero => {
-                if is_unsigned {
-                    Number::Unsigned(token.parse::<u64>().unwrap())
-                } else {
-                    Number::Integer(token.parse::<i64>().unwrap())
-                }
+            // Integers too big for 64 bits are kept as floats.
+            Stages::Integer | Stages::AfterZero if is_unsigned && token.parse::<u64>().is_ok() => {
+                Number::Unsigned(token.parse::<u64>().unwrap())
+            },
+            Stages::Integer | Stages::AfterZero if !is_unsigned && token.parse::<i64>().is_ok() => {
+                Number::Integer(token.parse::<i64>().unwrap())
             },
-            _ => {
-                Number::Float(token.parse::<f64>().unwrap())
+            _ => match token.parse::<f64>() {
+                Ok(value) => Number::Float(value),
+                Err(_)    => { return Err(Error::InvalidCharacter(token)); },
             },
         },
         source
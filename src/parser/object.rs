@@ -27,7 +27,7 @@ pub fn object(slice: &mut Peekable<&mut Chars>) -> Result<Json, Error>
     'tokenizer: loop {
         let current = match slice.peek() {
             Some(chr) => *chr,
-            None      => { break 'tokenizer },
+            None      => { return Err(Error::UnexpectedEof); },
         };
 
         match stage {
@@ -68,18 +68,17 @@ pub fn object(slice: &mut Peekable<&mut Chars>) -> Result<Json, Error>
                     return Err(Error::InvalidCharacter(current.to_string()));
                 },
             },
-            Stages::Value => {
-                stage = Stages::Comma;
-
-                let node = match node(slice) {
-                    Ok(node) => node,
-                    Err(e)   => { return Err(e) },
-                };
+            Stages::Value => match current {
+                ' ' | '\r' | '\n' | '\t' => { slice.next(); },
+                _ => {
+                    stage = Stages::Comma;
 
+                    let node = node(slice)?;
 
-                source.push_str(&node.to_source());
-                object.insert(index.clone(), node);
-                continue;
+                    source.push_str(&node.to_source());
+                    object.insert(index.clone(), node);
+                    continue;
+                },
             },
             Stages::Comma => match current {
                 ' ' | '\r' | '\n' | '\t' => { slice.next(); },
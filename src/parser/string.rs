@@ -9,6 +9,8 @@ pub fn string(slice: &mut Peekable<&mut Chars>) -> Result<Json, Error>
 {
     let mut token   = String::new();
     let mut unicode = String::new();
+    // High surrogate waiting for the low one that must follow it.
+    let mut high    = None;
 
     #[derive(Debug, PartialEq)]
     enum Stages {
@@ -38,6 +40,12 @@ pub fn string(slice: &mut Peekable<&mut Chars>) -> Result<Json, Error>
                     return Err(Error::InvalidCharacter(current.to_string()));
                 },
             },
+            Stages::Unescaped if high.is_some() && current != '\\' => {
+                return Err(Error::InvalidCharacter(current.to_string()));
+            },
+            Stages::Escaped if high.is_some() && current != 'u' => {
+                return Err(Error::InvalidCharacter(current.to_string()));
+            },
             Stages::Unescaped => match current {
                 '\\' => { stage = Stages::Escaped; slice.next(); },
                 '"'  => { stage = Stages::End;     slice.next(); },
@@ -60,9 +68,7 @@ pub fn string(slice: &mut Peekable<&mut Chars>) -> Result<Json, Error>
                 },
             },
             Stages::EscapedUnicode => match current {
-                '0'..='9' => { unicode.push(current); slice.next(); },
-                'A'..='F' => { unicode.push(current); slice.next(); },
-                'a'..='f' => { unicode.push(current); slice.next(); },
+                '0'..='9' | 'A'..='F' | 'a'..='f' if unicode.len() < 4 => { unicode.push(current); slice.next(); },
                 _ => {
                     stage = Stages::AfterUnicode;
                     // skip so that current character does not get to source,
@@ -76,21 +82,34 @@ pub fn string(slice: &mut Peekable<&mut Chars>) -> Result<Json, Error>
                 }
 
                 let code = u32::from_str_radix(unicode.as_str(), 16).unwrap();
-                token.push(char::from_u32(code).unwrap());
+                match (high.take(), code) {
+                    (None, 0xD800..=0xDBFF) => { high = Some(code); },
+                    (Some(high), 0xDC00..=0xDFFF) => {
+                        token.push(char::from_u32(0x10000 + ((high - 0xD800) << 10) + (code - 0xDC00)).unwrap());
+                    },
+                    (None, _) if !(0xDC00..=0xDFFF).contains(&code) => {
+                        token.push(char::from_u32(code).unwrap());
+                    },
+                    _ => {
+                        return Err(Error::InvalidCharacter(format!("U+{}", unicode)));
+                    },
+                }
 
                 stage = Stages::Unescaped;
                 // skip so that current character does not get to source,
                 // since slice is not moved
                 continue;
             },
-            Stages::End => match current {
-                _ => { break 'tokenizer; },
-            },
+            Stages::End => { break 'tokenizer; },
         }
 
         source.push(current);
     }
 
+    if stage != Stages::End {
+        return Err(Error::UnexpectedEof);
+    }
+
     Ok(Json::String(token, source))
 }
 
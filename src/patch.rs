@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::json::{ values_equal, Json };
+use crate::pointer::JsonPointer;
+
+/// A single JSON Patch operation, as described by RFC 6902.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation {
+    Add { path: JsonPointer, value: Json },
+    Remove { path: JsonPointer },
+    Replace { path: JsonPointer, value: Json },
+    Move { from: JsonPointer, path: JsonPointer },
+    Copy { from: JsonPointer, path: JsonPointer },
+    Test { path: JsonPointer, value: Json },
+}
+
+/// A JSON Patch document, a list of operations applied in order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Patch(pub Vec<Operation>);
+
+impl Patch
+{
+    /// Reads a patch from its JSON representation, an array of operation
+    /// objects.
+    pub fn from_json(json: &Json) -> Result<Patch, Error>
+    {
+        let operations = match json {
+            Json::Array(operations, _) => operations,
+            _ => { return Err(Error::InvalidPatch("patch must be an array".to_string())); },
+        };
+
+        let mut result = vec![];
+
+        for operation in operations {
+            let members = match operation {
+                Json::Object(members, _) => members,
+                _ => { return Err(Error::InvalidPatch("operation must be an object".to_string())); },
+            };
+
+            let pointer = |name: &str| -> Result<JsonPointer, Error> {
+                match members.get(name) {
+                    Some(Json::String(pointer, _)) => JsonPointer::parse(pointer),
+                    _ => Err(Error::InvalidPatch(format!("missing '{}'", name))),
+                }
+            };
+
+            let value = || -> Result<Json, Error> {
+                match members.get("value") {
+                    Some(value) => Ok(value.clone()),
+                    None        => Err(Error::InvalidPatch("missing 'value'".to_string())),
+                }
+            };
+
+            result.push(match members.get("op") {
+                Some(Json::String(op, _)) => match op.as_str() {
+                    "add"     => Operation::Add { path: pointer("path")?, value: value()? },
+                    "remove"  => Operation::Remove { path: pointer("path")? },
+                    "replace" => Operation::Replace { path: pointer("path")?, value: value()? },
+                    "move"    => Operation::Move { from: pointer("from")?, path: pointer("path")? },
+                    "copy"    => Operation::Copy { from: pointer("from")?, path: pointer("path")? },
+                    "test"    => Operation::Test { path: pointer("path")?, value: value()? },
+                    _ => { return Err(Error::InvalidPatch(format!("unknown operation '{}'", op))); },
+                },
+                _ => { return Err(Error::InvalidPatch("missing 'op'".to_string())); },
+            });
+        }
+
+        Ok(Patch(result))
+    }
+
+    pub fn to_json(&self) -> Json
+    {
+        Json::from(self.0.iter().map(Operation::to_json).collect::<Vec<Json>>())
+    }
+
+    /// Applies every operation in order. If any of them fails, `json` is left
+    /// untouched.
+    pub fn apply(&self, json: &mut Json) -> Result<(), Error>
+    {
+        let mut result = json.clone();
+
+        for operation in &self.0 {
+            operation.apply(&mut result)?;
+        }
+
+        *json = result;
+        Ok(())
+    }
+
+    /// Generates a patch that turns `from` into `to`.
+    ///
+    /// Object members are compared by key and arrays are compared element by
+    /// element using their longest common subsequence, so unchanged elements
+    /// don't show up in the patch.
+    pub fn diff(from: &Json, to: &Json) -> Patch
+    {
+        let mut operations = vec![];
+        diff(&JsonPointer::root(), from, to, &mut operations);
+        Patch(operations)
+    }
+}
+
+impl Operation
+{
+    pub fn apply(&self, json: &mut Json) -> Result<(), Error>
+    {
+        match self {
+            Operation::Add { path, value } => {
+                path.insert(json, value.clone())?;
+            },
+            Operation::Remove { path } => {
+                path.remove(json)?;
+            },
+            Operation::Replace { path, value } => {
+                path.resolve(json)?;
+                path.set(json, value.clone())?;
+            },
+            Operation::Move { from, path } => {
+                if from == path {
+                    from.resolve(json)?;
+                    return Ok(());
+                }
+
+                if from.is_prefix_of(path) {
+                    return Err(Error::InvalidPatch(format!("can't move {} into one of its children", from)));
+                }
+
+                let value = from.remove(json)?;
+                path.insert(json, value)?;
+            },
+            Operation::Copy { from, path } => {
+                let value = from.resolve(json)?.clone();
+                path.insert(json, value)?;
+            },
+            Operation::Test { path, value } => {
+                if !values_equal(path.resolve(json)?, value) {
+                    return Err(Error::TestFailed(path.to_string()));
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> Json
+    {
+        let mut members = HashMap::new();
+
+        let (op, path) = match self {
+            Operation::Add { path, .. }     => ("add", path),
+            Operation::Remove { path }      => ("remove", path),
+            Operation::Replace { path, .. } => ("replace", path),
+            Operation::Move { path, .. }    => ("move", path),
+            Operation::Copy { path, .. }    => ("copy", path),
+            Operation::Test { path, .. }    => ("test", path),
+        };
+
+        members.insert("op".to_string(), Json::from(op));
+        members.insert("path".to_string(), Json::from(path.to_string()));
+
+        match self {
+            Operation::Add { value, .. } | Operation::Replace { value, .. } | Operation::Test { value, .. } => {
+                members.insert("value".to_string(), value.clone());
+            },
+            Operation::Move { from, .. } | Operation::Copy { from, .. } => {
+                members.insert("from".to_string(), Json::from(from.to_string()));
+            },
+            Operation::Remove { .. } => {},
+        }
+
+        Json::from(members)
+    }
+}
+
+fn diff(path: &JsonPointer, from: &Json, to: &Json, operations: &mut Vec<Operation>)
+{
+    if values_equal(from, to) {
+        return;
+    }
+
+    match (from, to) {
+        (Json::Object(from, _), Json::Object(to, _)) => {
+            let mut keys: Vec<&String> = from.keys().chain(to.keys().filter(|key| !from.contains_key(*key))).collect();
+            keys.sort();
+
+            for key in keys {
+                let child = path.child(key.as_str());
+
+                match (from.get(key), to.get(key)) {
+                    (Some(from), Some(to)) => diff(&child, from, to, operations),
+                    (Some(_), None) => operations.push(Operation::Remove { path: child }),
+                    (None, Some(to)) => operations.push(Operation::Add { path: child, value: to.clone() }),
+                    (None, None) => {},
+                }
+            }
+        },
+        (Json::Array(from, _), Json::Array(to, _)) => diff_arrays(path, from, to, operations),
+        _ => operations.push(Operation::Replace { path: path.clone(), value: to.clone() }),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Edit {
+    Keep,
+    Delete(usize),
+    Insert(usize),
+}
+
+// Elements deleted and inserted between two kept ones, `kept` counting the
+// elements kept since the previous run.
+pub(crate) struct Run
+{
+    pub kept:     usize,
+    pub deleted:  Vec<usize>,
+    pub inserted: Vec<usize>,
+}
+
+fn diff_arrays(path: &JsonPointer, from: &[Json], to: &[Json], operations: &mut Vec<Operation>)
+{
+    // Position in the array as it is being patched.
+    let mut index = 0;
+
+    for run in runs(from, to) {
+        index += run.kept;
+
+        // Elements removed and added at the same place are replacements,
+        // which can be refined when both are containers.
+        let replaced = run.deleted.len().min(run.inserted.len());
+
+        for (old, new) in run.deleted.iter().zip(&run.inserted) {
+            diff(&path.child(index.to_string()), &from[*old], &to[*new], operations);
+            index += 1;
+        }
+
+        for _ in replaced..run.deleted.len() {
+            operations.push(Operation::Remove { path: path.child(index.to_string()) });
+        }
+
+        for new in &run.inserted[replaced..] {
+            operations.push(Operation::Add { path: path.child(index.to_string()), value: to[*new].clone() });
+            index += 1;
+        }
+    }
+}
+
+// Runs of changes turning `from` into `to`.
+pub(crate) fn runs(from: &[Json], to: &[Json]) -> Vec<Run>
+{
+    let mut runs = vec![];
+    let mut kept = 0;
+    let mut edits = edit_script(from, to).into_iter().peekable();
+
+    while let Some(edit) = edits.next() {
+        if edit == Edit::Keep {
+            kept += 1;
+            continue;
+        }
+
+        let mut run     = Run { kept, deleted: vec![], inserted: vec![] };
+        let mut current = Some(edit);
+
+        while let Some(edit) = current {
+            match edit {
+                Edit::Delete(old) => run.deleted.push(old),
+                Edit::Insert(new) => run.inserted.push(new),
+                Edit::Keep => unreachable!(),
+            }
+
+            current = match edits.peek() {
+                Some(Edit::Keep) | None => None,
+                Some(_) => edits.next(),
+            };
+        }
+
+        runs.push(run);
+        kept = 0;
+    }
+
+    runs
+}
+
+// Largest table of common subsequence lengths `edit_script` builds, past it
+// the differing middle of the arrays is replaced as a whole.
+const MAX_TABLE: usize = 1 << 20;
+
+// Edit script turning `from` into `to`, built from their longest common
+// subsequence once the elements they start and end with are set aside.
+fn edit_script(from: &[Json], to: &[Json]) -> Vec<Edit>
+{
+    let prefix = from.iter().zip(to).take_while(|(from, to)| values_equal(from, to)).count();
+    let suffix = from[prefix..].iter().rev().zip(to[prefix..].iter().rev()).take_while(|(from, to)| values_equal(from, to)).count();
+
+    let (rows, columns) = (from.len() - prefix - suffix, to.len() - prefix - suffix);
+    let mut edits: Vec<Edit> = (0..prefix).map(|_| Edit::Keep).collect();
+
+    match (rows + 1).saturating_mul(columns + 1) > MAX_TABLE {
+        true => {
+            edits.extend((prefix..prefix + rows).map(Edit::Delete));
+            edits.extend((prefix..prefix + columns).map(Edit::Insert));
+        },
+        false => middle_script(&from[prefix..prefix + rows], &to[prefix..prefix + columns], prefix, &mut edits),
+    }
+
+    edits.extend((0..suffix).map(|_| Edit::Keep));
+    edits
+}
+
+// Appends the edits between `from` and `to`, whose elements start at `offset`
+// in the whole arrays.
+fn middle_script(from: &[Json], to: &[Json], offset: usize, edits: &mut Vec<Edit>)
+{
+    let (rows, columns) = (from.len(), to.len());
+    let mut lengths = vec![vec![0usize; columns + 1]; rows + 1];
+
+    for row in (0..rows).rev() {
+        for column in (0..columns).rev() {
+            lengths[row][column] = if values_equal(&from[row], &to[column]) {
+                lengths[row + 1][column + 1] + 1
+            } else {
+                lengths[row + 1][column].max(lengths[row][column + 1])
+            };
+        }
+    }
+
+    let (mut row, mut column) = (0, 0);
+
+    while row < rows || column < columns {
+        if row < rows && column < columns && values_equal(&from[row], &to[column]) {
+            edits.push(Edit::Keep);
+            row += 1;
+            column += 1;
+        } else if row < rows && (column == columns || lengths[row + 1][column] >= lengths[row][column + 1]) {
+            edits.push(Edit::Delete(offset + row));
+            row += 1;
+        } else {
+            edits.push(Edit::Insert(offset + column));
+            column += 1;
+        }
+    }
+}
+
+impl Json
+{
+    /// Applies a JSON Patch document, see `Patch::apply`.
+    pub fn apply_patch(&mut self, patch: &Json) -> Result<(), Error>
+    {
+        Patch::from_json(patch)?.apply(self)
+    }
+}
@@ -0,0 +1,484 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::Error;
+use crate::json::{ member_offsets, quote, skip_whitespace, Json };
+
+/// A parsed JSON Pointer, as described by RFC 6901.
+///
+/// The pointer is stored as its list of reference tokens, already unescaped,
+/// so `"/a~1b/0"` is kept as `["a/b", "0"]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct JsonPointer
+{
+    tokens: Vec<String>,
+}
+
+impl JsonPointer
+{
+    /// The empty pointer, which refers to the whole document.
+    pub fn root() -> JsonPointer
+    {
+        JsonPointer { tokens: vec![] }
+    }
+
+    pub fn parse(text: &str) -> Result<JsonPointer, Error>
+    {
+        if text.is_empty() {
+            return Ok(JsonPointer::root());
+        }
+
+        if !text.starts_with('/') {
+            return Err(Error::InvalidPointer(text.to_string()));
+        }
+
+        let mut tokens = vec![];
+
+        for token in text[1..].split('/') {
+            match unescape(token) {
+                Some(token) => tokens.push(token),
+                None        => { return Err(Error::InvalidPointer(text.to_string())); },
+            }
+        }
+
+        Ok(JsonPointer { tokens })
+    }
+
+    pub fn from_tokens<I, S>(tokens: I) -> JsonPointer
+        where I: IntoIterator<Item = S>, S: Into<String>
+    {
+        JsonPointer { tokens: tokens.into_iter().map(Into::into).collect() }
+    }
+
+    pub fn tokens(&self) -> &[String]
+    {
+        &self.tokens
+    }
+
+    pub fn is_root(&self) -> bool
+    {
+        self.tokens.is_empty()
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.tokens.is_empty()
+    }
+
+    pub fn push<S: Into<String>>(&mut self, token: S)
+    {
+        self.tokens.push(token.into());
+    }
+
+    pub fn pop(&mut self) -> Option<String>
+    {
+        self.tokens.pop()
+    }
+
+    /// Returns a new pointer with `token` appended.
+    pub fn child<S: Into<String>>(&self, token: S) -> JsonPointer
+    {
+        let mut pointer = self.clone();
+        pointer.push(token);
+        pointer
+    }
+
+    /// Returns the pointer to the parent node, or `None` for the root.
+    pub fn parent(&self) -> Option<JsonPointer>
+    {
+        if self.is_root() {
+            return None;
+        }
+
+        Some(JsonPointer { tokens: self.tokens[..self.tokens.len() - 1].to_vec() })
+    }
+
+    pub fn last(&self) -> Option<&str>
+    {
+        self.tokens.last().map(String::as_str)
+    }
+
+    /// Whether `self` is `other` or one of its ancestors.
+    pub fn is_prefix_of(&self, other: &JsonPointer) -> bool
+    {
+        other.tokens.starts_with(&self.tokens)
+    }
+
+    pub fn resolve<'a>(&self, json: &'a Json) -> Result<&'a Json, Error>
+    {
+        let mut current = json;
+
+        for (depth, token) in self.tokens.iter().enumerate() {
+            current = match current {
+                Json::Object(map, _) => match map.get(token) {
+                    Some(value) => value,
+                    None        => { return Err(Error::MissingKey(self.prefix(depth + 1))); },
+                },
+                Json::Array(vector, _) => {
+                    let index = self.index(token, depth, vector.len())?;
+                    match vector.get(index) {
+                        Some(value) => value,
+                        None        => { return Err(Error::IndexOutOfRange(self.prefix(depth + 1))); },
+                    }
+                },
+                _ => { return Err(Error::NotAContainer(self.prefix(depth))); },
+            };
+        }
+
+        Ok(current)
+    }
+
+    /// Byte offset of the node the pointer refers to within the source of
+    /// `json`, if it exists.
+    pub fn offset(&self, json: &Json) -> Option<usize>
+    {
+        let mut current = json;
+        let mut offset  = 0;
+
+        for (depth, token) in self.tokens.iter().enumerate() {
+            let source = current.to_source();
+
+            let (child, position) = match current {
+                Json::Array(vector, _) => {
+                    let index = self.index(token, depth, vector.len()).ok()?;
+                    let child = vector.get(index)?;
+
+                    // Past the `[` and every element before this one, along
+                    // with its comma.
+                    let mut position = skip_whitespace(&source, 1);
+                    for element in &vector[..index] {
+                        position = skip_whitespace(&source, position + element.to_source().len());
+                        position = skip_whitespace(&source, position + 1);
+                    }
+
+                    (child, position)
+                },
+                Json::Object(map, _) => {
+                    let child = map.get(token)?;
+
+                    // Keys may repeat, the last occurrence is the one kept.
+                    let member = member_offsets(&source)?.into_iter().rev().find(|member| member.key == *token)?;
+
+                    (child, member.value_start)
+                },
+                _ => return None,
+            };
+
+            current = child;
+            offset += position;
+        }
+
+        Some(offset)
+    }
+
+    /// Mutable counterpart of `resolve`.
+    ///
+    /// Sources of the ancestors are not refreshed when the returned node is
+    /// modified, use `set` and `remove` or call `Json::refresh_source` on the
+    /// way back up to keep them in sync.
+    pub fn resolve_mut<'a>(&self, json: &'a mut Json) -> Result<&'a mut Json, Error>
+    {
+        let mut current = json;
+
+        for (depth, token) in self.tokens.iter().enumerate() {
+            current = match current {
+                Json::Object(map, _) => match map.get_mut(token) {
+                    Some(value) => value,
+                    None        => { return Err(Error::MissingKey(self.prefix(depth + 1))); },
+                },
+                Json::Array(vector, _) => {
+                    let index = self.index(token, depth, vector.len())?;
+                    match vector.get_mut(index) {
+                        Some(value) => value,
+                        None        => { return Err(Error::IndexOutOfRange(self.prefix(depth + 1))); },
+                    }
+                },
+                _ => { return Err(Error::NotAContainer(self.prefix(depth))); },
+            };
+        }
+
+        Ok(current)
+    }
+
+    /// Stores `value` at the location of the pointer and returns the value it
+    /// replaced, if any.
+    ///
+    /// Object members are inserted or replaced. Array elements are replaced,
+    /// except for the index right past the end (or `-`), which appends. The
+    /// parent of the location must exist.
+    pub fn set(&self, json: &mut Json, value: Json) -> Result<Option<Json>, Error>
+    {
+        if self.is_root() {
+            return Ok(Some(std::mem::replace(json, value)));
+        }
+
+        self.modify(json, 0, |parent, token, depth| match parent {
+            Json::Object(map, _) => Ok(map.insert(token.to_string(), value)),
+            Json::Array(vector, _) => {
+                let index = self.index(token, depth, vector.len())?;
+                if index < vector.len() {
+                    Ok(Some(std::mem::replace(&mut vector[index], value)))
+                } else if index == vector.len() {
+                    vector.push(value);
+                    Ok(None)
+                } else {
+                    Err(Error::IndexOutOfRange(self.prefix(depth + 1)))
+                }
+            },
+            _ => Err(Error::NotAContainer(self.prefix(depth))),
+        })
+    }
+
+    /// Like `set`, but array elements are shifted to the right to make room
+    /// for `value` instead of being replaced.
+    pub fn insert(&self, json: &mut Json, value: Json) -> Result<Option<Json>, Error>
+    {
+        if self.is_root() {
+            return Ok(Some(std::mem::replace(json, value)));
+        }
+
+        self.modify(json, 0, |parent, token, depth| match parent {
+            Json::Object(map, _) => Ok(map.insert(token.to_string(), value)),
+            Json::Array(vector, _) => {
+                let index = self.index(token, depth, vector.len())?;
+                if index > vector.len() {
+                    return Err(Error::IndexOutOfRange(self.prefix(depth + 1)));
+                }
+                vector.insert(index, value);
+                Ok(None)
+            },
+            _ => Err(Error::NotAContainer(self.prefix(depth))),
+        })
+    }
+
+    /// Removes the node at the location of the pointer and returns it. The
+    /// root can't be removed.
+    pub fn remove(&self, json: &mut Json) -> Result<Json, Error>
+    {
+        if self.is_root() {
+            return Err(Error::InvalidPointer(String::new()));
+        }
+
+        self.modify(json, 0, |parent, token, depth| match parent {
+            Json::Object(map, _) => match map.remove(token) {
+                Some(value) => Ok(value),
+                None        => Err(Error::MissingKey(self.prefix(depth + 1))),
+            },
+            Json::Array(vector, _) => {
+                let index = self.index(token, depth, vector.len())?;
+                if index >= vector.len() {
+                    return Err(Error::IndexOutOfRange(self.prefix(depth + 1)));
+                }
+                Ok(vector.remove(index))
+            },
+            _ => Err(Error::NotAContainer(self.prefix(depth))),
+        })
+    }
+
+    /// Same as `set`, but works on JSON text and only rewrites the text of
+    /// the target, so the rest of the document keeps its formatting.
+    ///
+    /// New members and elements go after the last ones, separated from them
+    /// like the first one is from the opening bracket.
+    pub fn set_in_source(&self, text: &str, value: &Json) -> Result<String, Error>
+    {
+        let json  = Json::parse(text)?;
+        let start = skip_whitespace(text, 0);
+        let new   = value.to_source();
+
+        if let (Ok(old), Some(offset)) = (self.resolve(&json), self.offset(&json)) {
+            let at = start + offset;
+            return Ok(format!("{}{}{}", &text[..at], new, &text[at + old.to_source().len()..]));
+        }
+
+        // The root always resolves, so there is a parent from here on.
+        let depth   = self.tokens.len() - 1;
+        let token   = &self.tokens[depth];
+        let pointer = JsonPointer { tokens: self.tokens[..depth].to_vec() };
+        let parent  = pointer.resolve(&json)?;
+        let source  = parent.to_source();
+        let at      = start + pointer.offset(&json).unwrap_or(0);
+
+        // Whitespace after the comma following the first child, or after the
+        // opening bracket when there is a single one.
+        let separator = |first_end: usize| {
+            let comma = skip_whitespace(&source, first_end);
+            match source[comma..].starts_with(',') {
+                true  => &source[comma + 1..skip_whitespace(&source, comma + 1)],
+                false => &source[1..skip_whitespace(&source, 1)],
+            }
+        };
+
+        let (end, inserted) = match parent {
+            Json::Object(_, _) => {
+                let members = member_offsets(&source).unwrap_or_default();
+
+                match (members.first(), members.last()) {
+                    (Some(first), Some(last)) => {
+                        let colon = &source[source[..last.value_start].rfind(':').unwrap_or(last.value_start)..last.value_start];
+                        let comma = separator(first.value_end);
+                        (last.value_end, format!(",{}{}{}{}", comma, quote(token), colon, new))
+                    },
+                    _ => (1, format!("{}: {}", quote(token), new)),
+                }
+            },
+            Json::Array(vector, _) => {
+                let index = self.index(token, depth, vector.len())?;
+                if index > vector.len() {
+                    return Err(Error::IndexOutOfRange(self.prefix(depth + 1)));
+                }
+
+                match (vector.first(), vector.last()) {
+                    (Some(first), Some(last)) => {
+                        let comma    = separator(skip_whitespace(&source, 1) + first.to_source().len());
+                        let position = JsonPointer::root().child((vector.len() - 1).to_string()).offset(parent).unwrap_or(1);
+                        (position + last.to_source().len(), format!(",{}{}", comma, new))
+                    },
+                    _ => (1, new),
+                }
+            },
+            _ => return Err(Error::NotAContainer(self.prefix(depth))),
+        };
+
+        Ok(format!("{}{}{}", &text[..at + end], inserted, &text[at + end..]))
+    }
+
+    // Walks down to the parent of the target, applies `apply` to it and then
+    // refreshes the sources of every container on the way back up.
+    fn modify<F, T>(&self, json: &mut Json, depth: usize, apply: F) -> Result<T, Error>
+        where F: FnOnce(&mut Json, &str, usize) -> Result<T, Error>
+    {
+        let token = &self.tokens[depth];
+
+        let result = if depth + 1 == self.tokens.len() {
+            apply(json, token, depth)?
+        } else {
+            let child = match json {
+                Json::Object(map, _) => match map.get_mut(token) {
+                    Some(value) => value,
+                    None        => { return Err(Error::MissingKey(self.prefix(depth + 1))); },
+                },
+                Json::Array(vector, _) => {
+                    let index = self.index(token, depth, vector.len())?;
+                    match vector.get_mut(index) {
+                        Some(value) => value,
+                        None        => { return Err(Error::IndexOutOfRange(self.prefix(depth + 1))); },
+                    }
+                },
+                _ => { return Err(Error::NotAContainer(self.prefix(depth))); },
+            };
+
+            self.modify(child, depth + 1, apply)?
+        };
+
+        json.refresh_source();
+        Ok(result)
+    }
+
+    // Parses an array index token. `-` refers to the element right past the
+    // end of the array.
+    fn index(&self, token: &str, depth: usize, len: usize) -> Result<usize, Error>
+    {
+        if token == "-" {
+            return Ok(len);
+        }
+
+        let valid = !token.is_empty()
+            && token.chars().all(|c| c.is_ascii_digit())
+            && (token == "0" || !token.starts_with('0'));
+
+        match token.parse::<usize>() {
+            Ok(index) if valid => Ok(index),
+            _ => Err(Error::InvalidIndex(self.prefix(depth + 1))),
+        }
+    }
+
+    // String form of the first `depth` tokens of the pointer.
+    fn prefix(&self, depth: usize) -> String
+    {
+        JsonPointer { tokens: self.tokens[..depth].to_vec() }.to_string()
+    }
+}
+
+impl fmt::Display for JsonPointer
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        for token in &self.tokens {
+            write!(f, "/{}", escape(token))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for JsonPointer
+{
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<JsonPointer, Error>
+    {
+        JsonPointer::parse(text)
+    }
+}
+
+/// Escapes a reference token, `~` becomes `~0` and `/` becomes `~1`.
+pub fn escape(token: &str) -> String
+{
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Reverses `escape`. Returns `None` if the token has a `~` that isn't
+/// followed by `0` or `1`.
+pub fn unescape(token: &str) -> Option<String>
+{
+    let mut result = String::new();
+    let mut chars  = token.chars();
+
+    while let Some(current) = chars.next() {
+        if current != '~' {
+            result.push(current);
+            continue;
+        }
+
+        match chars.next() {
+            Some('0') => result.push('~'),
+            Some('1') => result.push('/'),
+            _         => { return None; },
+        }
+    }
+
+    Some(result)
+}
+
+impl Json
+{
+    /// Looks up a value by its JSON Pointer, like `"/a/b/0"`.
+    pub fn pointer(&self, pointer: &str) -> Result<&Json, Error>
+    {
+        JsonPointer::parse(pointer)?.resolve(self)
+    }
+
+    /// See `JsonPointer::resolve_mut`.
+    pub fn pointer_mut(&mut self, pointer: &str) -> Result<&mut Json, Error>
+    {
+        JsonPointer::parse(pointer)?.resolve_mut(self)
+    }
+
+    /// See `JsonPointer::set`.
+    pub fn set_pointer(&mut self, pointer: &str, value: Json) -> Result<Option<Json>, Error>
+    {
+        JsonPointer::parse(pointer)?.set(self, value)
+    }
+
+    /// See `JsonPointer::remove`.
+    pub fn remove_pointer(&mut self, pointer: &str) -> Result<Json, Error>
+    {
+        JsonPointer::parse(pointer)?.remove(self)
+    }
+}
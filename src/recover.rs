@@ -0,0 +1,375 @@
+//! Parsing that recovers from errors, for editors and other tools that need
+//! a tree of text still being written.
+//!
+//! Each error is reported as a `Diagnostic` and parsing resumes at the next
+//! `,`, `}` or `]`. Values that are missing or can't be parsed are replaced
+//! by `null` in the tree, and listed as `Placeholder`s so they can be told
+//! apart from a real `null`. Containers keep the text they span as their
+//! source, errors included.
+
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::ops::Range;
+use std::str::Chars;
+
+use crate::error::Error;
+use crate::json::{ skip_whitespace, Json };
+use crate::parser::{ boolean, null, number, string };
+use crate::pointer::JsonPointer;
+use crate::span::{ Cursor, Kind, Recorder, Span, Spans };
+
+/// An error found while parsing, `pointer` is the node it concerns.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic
+{
+    pub message: String,
+    pub span:    Span,
+    pub pointer: JsonPointer,
+}
+
+/// A value that is missing, with an empty span, or that couldn't be parsed,
+/// with the span of the text skipped. It is `null` in the tree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placeholder
+{
+    pub pointer: JsonPointer,
+    pub span:    Span,
+}
+
+/// What `Json::parse_tolerant` makes out of a text.
+///
+/// `spans` has every node of `value` but the placeholders, which have their
+/// own spans.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recovered
+{
+    pub value:        Json,
+    pub spans:        Spans,
+    pub placeholders: Vec<Placeholder>,
+    pub diagnostics:  Vec<Diagnostic>,
+}
+
+impl Json
+{
+    /// Parses `text` whatever errors it holds, returning the tree that could
+    /// be made out of it along with every error found. Valid JSON gives the
+    /// same tree and spans as `Json::parse_with_spans`, and no diagnostics.
+    pub fn parse_tolerant(text: &str) -> Recovered
+    {
+        let mut parser = Parser {
+            text,
+            position:    0,
+            cursor:      Cursor::new(text),
+            recorder:    Recorder::default(),
+            diagnostics: vec![],
+        };
+        let root  = JsonPointer::root();
+        let value = parser.value(&root);
+
+        if parser.peek().is_some() {
+            let start = parser.position;
+            parser.position = text.len();
+            parser.report(start, text.len(), &root, "unexpected text after the document");
+        }
+
+        let mut spans = Spans::default();
+        let placeholders = parser.recorder.finish(&mut spans).into_iter()
+            .map(|(pointer, span)| Placeholder { pointer, span })
+            .collect();
+
+        Recovered { value, spans, placeholders, diagnostics: parser.diagnostics }
+    }
+}
+
+type Lexer = fn(&mut Peekable<&mut Chars>) -> Result<Json, Error>;
+
+struct Parser<'a>
+{
+    text:        &'a str,
+    position:    usize,
+    cursor:      Cursor<'a>,
+    recorder:    Recorder,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Parser<'a>
+{
+    // Skips whitespace and returns the next character without consuming it.
+    fn peek(&mut self) -> Option<char>
+    {
+        self.position = skip_whitespace(self.text, self.position);
+        self.text[self.position..].chars().next()
+    }
+
+    fn report<S: Into<String>>(&mut self, start: usize, end: usize, pointer: &JsonPointer, message: S)
+    {
+        self.diagnostics.push(Diagnostic {
+            message: message.into(),
+            span:    self.cursor.span(start, end),
+            pointer: pointer.clone(),
+        });
+    }
+
+    // Moves to the next `,`, `}` or `]`.
+    fn skip(&mut self)
+    {
+        let rest = &self.text[self.position..];
+        self.position += rest.find([',', '}', ']']).unwrap_or(rest.len());
+    }
+
+    // Offset of the end of the text from `start` to the current position,
+    // whitespace left out.
+    fn end(&self, start: usize) -> usize
+    {
+        start + self.text[start..self.position].trim_end().len()
+    }
+
+    // Parses a value and records its span, `null` stands for values missing
+    // or invalid.
+    fn value(&mut self, pointer: &JsonPointer) -> Json
+    {
+        let next  = self.peek();
+        let start = self.position;
+        let from  = self.cursor.position(start);
+
+        let json = match next {
+            Some('{') => Some(self.object(pointer)),
+            Some('[') => Some(self.array(pointer)),
+            Some('"') => self.string(pointer),
+            Some('n') => self.scalar(pointer, null),
+            Some('t') | Some('f') => self.scalar(pointer, boolean),
+            Some('-') | Some('0'..='9') => self.scalar(pointer, number),
+            None | Some(',') | Some('}') | Some(']') => {
+                self.report(start, start, pointer, "expected a value");
+                None
+            },
+            Some(chr) => {
+                self.skip();
+                let end = self.end(start);
+                self.report(start, end, pointer, format!("unexpected character `{}`", chr));
+                None
+            },
+        };
+
+        let end  = self.end(start);
+        let span = Span { start: from, end: self.cursor.position(end) };
+
+        match json {
+            Some(json) => {
+                self.recorder.push(pointer.clone(), span, Kind::Value);
+                json
+            },
+            None => {
+                self.recorder.push(pointer.clone(), span, Kind::Placeholder);
+                Json::from(())
+            },
+        }
+    }
+
+    // Runs one of the parser functions at the current position, on error
+    // returns the offset it stopped at.
+    fn lex(&mut self, lexer: Lexer) -> Result<Json, (Error, usize)>
+    {
+        let mut slice    = self.text[self.position..].chars();
+        let mut peekable = (&mut slice).peekable();
+
+        let result  = lexer(&mut peekable);
+        let pending = peekable.peek().map_or(0, |chr| chr.len_utf8());
+        let offset  = self.text.len() - slice.as_str().len() - pending;
+
+        match result {
+            Ok(json) => {
+                self.position += json.to_source().len();
+                Ok(json)
+            },
+            Err(error) => Err((error, offset)),
+        }
+    }
+
+    fn scalar(&mut self, pointer: &JsonPointer, lexer: Lexer) -> Option<Json>
+    {
+        let start = self.position;
+
+        match self.lex(lexer) {
+            Ok(json) => Some(json),
+            Err((error, _)) => {
+                self.skip();
+                let end = self.end(start);
+                self.report(start, end, pointer, format!("invalid value: {}", error));
+                None
+            },
+        }
+    }
+
+    // Strings can't span lines, so one left open ends with its line.
+    fn string(&mut self, pointer: &JsonPointer) -> Option<Json>
+    {
+        let start    = self.position;
+        let line_end = self.text[start..].find('\n').map_or(self.text.len(), |index| start + index);
+
+        let (error, offset) = match self.lex(string) {
+            Ok(json) if self.position <= line_end => return Some(json),
+            Err((error, offset)) if offset < line_end && error != Error::UnexpectedEof => (error, offset),
+            _ => (Error::UnexpectedEof, line_end),
+        };
+
+        // Up to the closing quote, if the line has one.
+        let mut escaped = false;
+        let closing = self.text[offset..line_end].find(|chr| {
+            let found = chr == '"' && !escaped;
+            escaped = chr == '\\' && !escaped;
+            found
+        });
+
+        match closing {
+            Some(index) => {
+                self.position = offset + index + 1;
+                self.report(start, self.position, pointer, format!("invalid string: {}", error));
+            },
+            None => {
+                self.position = line_end;
+                let end = self.end(start);
+                self.report(start, end, pointer, "unterminated string");
+            },
+        }
+
+        None
+    }
+
+    // Keys that can't be parsed are taken as written.
+    fn key(&mut self, pointer: &JsonPointer) -> String
+    {
+        let start = self.position;
+
+        match self.string(pointer) {
+            Some(Json::String(key, _)) => key,
+            _ => {
+                let source = &self.text[start + 1..self.end(start)];
+                source.strip_suffix('"').unwrap_or(source).to_string()
+            },
+        }
+    }
+
+    fn object(&mut self, pointer: &JsonPointer) -> Json
+    {
+        let start       = self.position;
+        let mut members = HashMap::new();
+        // Where the spans of each member start and end in the recorder, to
+        // drop those of a key given again.
+        let mut ranges: HashMap<String, Range<usize>> = HashMap::new();
+        self.position += 1;
+
+        loop {
+            match self.peek() {
+                Some('}') => {
+                    self.position += 1;
+                    break;
+                },
+                // A `]` closes an enclosing array.
+                None | Some(']') => {
+                    self.report(self.position, self.position, pointer, "expected `}`");
+                    break;
+                },
+                Some(',') => {
+                    self.report(self.position, self.position + 1, pointer, "expected a member");
+                    self.position += 1;
+                },
+                Some('"') => {
+                    let first     = self.recorder.len();
+                    let key_start = self.position;
+                    let key       = self.key(pointer);
+                    let child     = pointer.child(key.as_str());
+
+                    let key_end = self.end(key_start);
+                    let span    = self.cursor.span(key_start, key_end);
+                    self.recorder.push(child.clone(), span, Kind::Key);
+
+                    match self.peek() {
+                        Some(':') => self.position += 1,
+                        _ => self.report(self.position, self.position, &child, "expected `:`"),
+                    }
+
+                    let value = self.value(&child);
+                    members.insert(key.clone(), value);
+
+                    if let Some(previous) = ranges.insert(key, first..self.recorder.len()) {
+                        self.recorder.forget(previous);
+                    }
+
+                    self.separator(pointer, '}');
+                },
+                Some(_) => {
+                    let start = self.position;
+                    self.skip();
+                    self.report(start, self.position, pointer, "expected a key");
+
+                    if self.peek() == Some(',') {
+                        self.position += 1;
+                    }
+                },
+            }
+        }
+
+        Json::Object(members, self.text[start..self.position].to_string())
+    }
+
+    fn array(&mut self, pointer: &JsonPointer) -> Json
+    {
+        let start        = self.position;
+        let mut elements = vec![];
+        self.position += 1;
+
+        loop {
+            match self.peek() {
+                Some(']') => {
+                    self.position += 1;
+                    break;
+                },
+                // A `}` closes an enclosing object.
+                None | Some('}') => {
+                    self.report(self.position, self.position, pointer, "expected `]`");
+                    break;
+                },
+                Some(_) => {
+                    let value = self.value(&pointer.child(elements.len().to_string()));
+                    elements.push(value);
+
+                    self.separator(pointer, ']');
+                },
+            }
+        }
+
+        Json::Array(elements, self.text[start..self.position].to_string())
+    }
+
+    // Consumes the comma after a member or element, the closing bracket is
+    // left to the caller.
+    fn separator(&mut self, pointer: &JsonPointer, close: char)
+    {
+        match self.peek() {
+            Some(',') => {
+                self.position += 1;
+
+                if self.peek() == Some(close) {
+                    let comma = self.text[..self.position].rfind(',').unwrap_or(self.position);
+                    self.report(comma, comma + 1, pointer, "trailing comma");
+                }
+            },
+            Some(chr) if chr == close => {},
+            None | Some('}') | Some(']') => {},
+            // Most likely a forgotten comma, the next value is parsed as usual.
+            Some('"') | Some('{') | Some('[') | Some('-') | Some('0'..='9') | Some('t') | Some('f') | Some('n') => {
+                self.report(self.position, self.position, pointer, "expected `,`");
+            },
+            Some(_) => {
+                let start = self.position;
+                self.skip();
+                self.report(start, self.position, pointer, format!("expected `,` or `{}`", close));
+
+                if self.peek() == Some(',') {
+                    self.position += 1;
+                }
+            },
+        }
+    }
+}
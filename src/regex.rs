@@ -0,0 +1,557 @@
+// A small regular expression engine.
+//
+// It understands the I-Regexp subset from RFC 9485 used by JSONPath, plus the
+// few ECMA-262 extras that show up in JSON Schema patterns: `^` and `$`
+// anchors, `\d`, `\w`, `\s` and their negations, non-capturing groups and lazy
+// quantifiers. Captures and back-references are not supported.
+//
+// Patterns are compiled to a program run by a Pike VM, which follows every
+// possible match at once, so matching takes time linear in the length of the
+// text and never recurses over it.
+
+// Programs bigger than this are refused, as counted repetitions copy their
+// body and could grow without bounds.
+const MAX_PROGRAM: usize = 100_000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Regex
+{
+    program: Vec<Instruction>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    Start,
+    End,
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+    // Whether it is lazy doesn't change what matches, so it isn't kept.
+    Repeat(Box<Node>, usize, Option<usize>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Instruction {
+    Char(char),
+    Any,
+    Class(Class),
+    Start,
+    End,
+    // Goes on at both targets.
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Class
+{
+    negated: bool,
+    items:   Vec<ClassItem>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+    Category(String, bool),
+}
+
+impl Regex
+{
+    pub fn new(pattern: &str) -> Result<Regex, String>
+    {
+        let mut parser = Parser { chars: pattern.chars().collect(), pos: 0 };
+        let node = parser.alternation()?;
+
+        if parser.pos != parser.chars.len() {
+            return Err(format!("unexpected '{}' at {}", parser.chars[parser.pos], parser.pos));
+        }
+
+        let mut program = vec![];
+        compile(&node, &mut program)?;
+        program.push(Instruction::Match);
+
+        Ok(Regex { program })
+    }
+
+    /// Whether the whole of `text` matches the expression.
+    pub fn is_full_match(&self, text: &str) -> bool
+    {
+        self.run(text, true)
+    }
+
+    /// Whether any substring of `text` matches the expression.
+    pub fn is_match(&self, text: &str) -> bool
+    {
+        self.run(text, false)
+    }
+
+    // Runs every thread in lockstep over the text, threads at the same
+    // instruction being merged. A full match starts at 0 and must end at the
+    // end, a search starts a thread at every position and stops at the first
+    // match.
+    fn run(&self, text: &str, full: bool) -> bool
+    {
+        let chars: Vec<char> = text.chars().collect();
+
+        let mut current = Threads::new(self.program.len());
+        let mut next    = Threads::new(self.program.len());
+
+        for pos in 0..=chars.len() {
+            if pos == 0 || !full {
+                current.add(&self.program, 0, pos, chars.len());
+            }
+
+            if full && current.list.is_empty() {
+                return false;
+            }
+
+            let chr = chars.get(pos).copied();
+
+            for &pc in &current.list {
+                let step = match (&self.program[pc], chr) {
+                    (Instruction::Match, _) if !full || pos == chars.len() => { return true; },
+                    (Instruction::Char(expected), Some(chr)) => *expected == chr,
+                    (Instruction::Any, Some(chr))            => chr != '\n' && chr != '\r',
+                    (Instruction::Class(class), Some(chr))   => class.contains(chr),
+                    _ => false,
+                };
+
+                if step {
+                    next.add(&self.program, pc + 1, pos + 1, chars.len());
+                }
+            }
+
+            std::mem::swap(&mut current, &mut next);
+            next.clear();
+        }
+
+        false
+    }
+}
+
+// Threads waiting on an instruction that reads a character, or on the match.
+struct Threads
+{
+    list:   Vec<usize>,
+    seen:   Vec<bool>,
+    // Every instruction marked as seen, to unmark them in `clear`.
+    marked: Vec<usize>,
+    // Instructions still to follow while adding a thread.
+    stack:  Vec<usize>,
+}
+
+impl Threads
+{
+    fn new(size: usize) -> Threads
+    {
+        Threads { list: vec![], seen: vec![false; size], marked: vec![], stack: vec![] }
+    }
+
+    fn clear(&mut self)
+    {
+        for &pc in &self.marked {
+            self.seen[pc] = false;
+        }
+        self.marked.clear();
+        self.list.clear();
+    }
+
+    // Adds a thread at `pc`, following jumps, splits and anchors right away.
+    fn add(&mut self, program: &[Instruction], pc: usize, pos: usize, length: usize)
+    {
+        self.stack.push(pc);
+
+        while let Some(pc) = self.stack.pop() {
+            if self.seen[pc] {
+                continue;
+            }
+            self.seen[pc] = true;
+            self.marked.push(pc);
+
+            match &program[pc] {
+                Instruction::Jump(target) => self.stack.push(*target),
+                Instruction::Split(first, second) => {
+                    self.stack.push(*second);
+                    self.stack.push(*first);
+                },
+                Instruction::Start if pos == 0      => self.stack.push(pc + 1),
+                Instruction::End if pos == length   => self.stack.push(pc + 1),
+                Instruction::Start | Instruction::End => {},
+                _ => self.list.push(pc),
+            }
+        }
+    }
+}
+
+struct Parser
+{
+    chars: Vec<char>,
+    pos:   usize,
+}
+
+impl Parser
+{
+    fn peek(&self) -> Option<char>
+    {
+        self.chars.get(self.pos).cloned()
+    }
+
+    fn next(&mut self) -> Result<char, String>
+    {
+        match self.chars.get(self.pos) {
+            Some(chr) => { self.pos += 1; Ok(*chr) },
+            None      => Err("unexpected end of pattern".to_string()),
+        }
+    }
+
+    fn alternation(&mut self) -> Result<Node, String>
+    {
+        let mut branches = vec![self.concatenation()?];
+
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            branches.push(self.concatenation()?);
+        }
+
+        if branches.len() == 1 {
+            return Ok(branches.pop().unwrap());
+        }
+
+        Ok(Node::Alternate(branches))
+    }
+
+    fn concatenation(&mut self) -> Result<Node, String>
+    {
+        let mut nodes = vec![];
+
+        while let Some(current) = self.peek() {
+            if current == '|' || current == ')' {
+                break;
+            }
+
+            let atom = self.atom()?;
+            nodes.push(self.quantifier(atom)?);
+        }
+
+        match nodes.len() {
+            0 => Ok(Node::Empty),
+            1 => Ok(nodes.pop().unwrap()),
+            _ => Ok(Node::Concat(nodes)),
+        }
+    }
+
+    fn atom(&mut self) -> Result<Node, String>
+    {
+        let current = self.next()?;
+
+        match current {
+            '.' => Ok(Node::Any),
+            '^' => Ok(Node::Start),
+            '$' => Ok(Node::End),
+            '[' => Ok(Node::Class(self.class()?)),
+            '(' => {
+                if self.chars[self.pos..].starts_with(&['?', ':']) {
+                    self.pos += 2;
+                }
+
+                let node = self.alternation()?;
+                if self.next()? != ')' {
+                    return Err("unbalanced parenthesis".to_string());
+                }
+
+                Ok(node)
+            },
+            '\\' => match self.escape()? {
+                ClassItem::Range(chr, _) => Ok(Node::Char(chr)),
+                item => Ok(Node::Class(Class { negated: false, items: vec![item] })),
+            },
+            '*' | '+' | '?' | '{' | ')' | ']' | '}' => {
+                Err(format!("unexpected '{}' at {}", current, self.pos - 1))
+            },
+            _ => Ok(Node::Char(current)),
+        }
+    }
+
+    fn quantifier(&mut self, atom: Node) -> Result<Node, String>
+    {
+        let (min, max) = match self.peek() {
+            Some('*') => { self.pos += 1; (0, None) },
+            Some('+') => { self.pos += 1; (1, None) },
+            Some('?') => { self.pos += 1; (0, Some(1)) },
+            Some('{') => {
+                self.pos += 1;
+                let min = self.integer()?;
+                let max = match self.next()? {
+                    '}' => Some(min),
+                    ',' => {
+                        if self.peek() == Some('}') {
+                            self.pos += 1;
+                            None
+                        } else {
+                            let max = self.integer()?;
+                            if self.next()? != '}' {
+                                return Err("unterminated quantifier".to_string());
+                            }
+                            Some(max)
+                        }
+                    },
+                    _ => { return Err("invalid quantifier".to_string()); },
+                };
+
+                (min, max)
+            },
+            _ => { return Ok(atom); },
+        };
+
+        if let Some(max) = max {
+            if max < min {
+                return Err("invalid quantifier range".to_string());
+            }
+        }
+
+        // Lazy quantifiers match the same texts.
+        if self.peek() == Some('?') {
+            self.pos += 1;
+        }
+
+        Ok(Node::Repeat(Box::new(atom), min, max))
+    }
+
+    fn integer(&mut self) -> Result<usize, String>
+    {
+        let start = self.pos;
+
+        while let Some('0'..='9') = self.peek() {
+            self.pos += 1;
+        }
+
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        digits.parse().map_err(|_| "invalid quantifier".to_string())
+    }
+
+    fn class(&mut self) -> Result<Class, String>
+    {
+        let mut class = Class { negated: false, items: vec![] };
+
+        if self.peek() == Some('^') {
+            self.pos += 1;
+            class.negated = true;
+        }
+
+        let mut first = true;
+
+        loop {
+            let current = self.next()?;
+
+            let item = match current {
+                ']' if !first => { break; },
+                '\\' => self.escape()?,
+                _    => ClassItem::Range(current, current),
+            };
+            first = false;
+
+            // A plain character followed by `-` and another character is a
+            // range, unless the `-` closes the class.
+            if let ClassItem::Range(low, _) = item {
+                if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|c| *c != ']') {
+                    self.pos += 1;
+                    let high = match self.next()? {
+                        '\\' => match self.escape()? {
+                            ClassItem::Range(high, _) => high,
+                            _ => { return Err("invalid class range".to_string()); },
+                        },
+                        high => high,
+                    };
+
+                    if high < low {
+                        return Err("invalid class range".to_string());
+                    }
+
+                    class.items.push(ClassItem::Range(low, high));
+                    continue;
+                }
+            }
+
+            class.items.push(item);
+        }
+
+        Ok(class)
+    }
+
+    fn escape(&mut self) -> Result<ClassItem, String>
+    {
+        let current = self.next()?;
+
+        let chr = match current {
+            'd' => { return Ok(ClassItem::Digit(false)); },
+            'D' => { return Ok(ClassItem::Digit(true)); },
+            'w' => { return Ok(ClassItem::Word(false)); },
+            'W' => { return Ok(ClassItem::Word(true)); },
+            's' => { return Ok(ClassItem::Space(false)); },
+            'S' => { return Ok(ClassItem::Space(true)); },
+            'p' | 'P' => {
+                if self.next()? != '{' {
+                    return Err("invalid category escape".to_string());
+                }
+
+                let mut name = String::new();
+                loop {
+                    match self.next()? {
+                        '}' => { break; },
+                        chr => name.push(chr),
+                    }
+                }
+
+                if !is_known_category(&name) {
+                    return Err(format!("unknown category {}", name));
+                }
+
+                return Ok(ClassItem::Category(name, current == 'P'));
+            },
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'f' => '\u{000C}',
+            'v' => '\u{000B}',
+            'u' => {
+                let mut code = String::new();
+                for _ in 0..4 {
+                    code.push(self.next()?);
+                }
+
+                match u32::from_str_radix(&code, 16).ok().and_then(std::char::from_u32) {
+                    Some(chr) => chr,
+                    None      => { return Err(format!("invalid escape \\u{}", code)); },
+                }
+            },
+            chr if chr.is_ascii_alphanumeric() => {
+                return Err(format!("unknown escape \\{}", chr));
+            },
+            chr => chr,
+        };
+
+        Ok(ClassItem::Range(chr, chr))
+    }
+}
+
+fn is_known_category(name: &str) -> bool
+{
+    matches!(name, "L" | "Lu" | "Ll" | "Lt" | "Lm" | "Lo" | "N" | "Nd" | "P" | "Z" | "Zs" | "C" | "Cc")
+}
+
+fn category_contains(name: &str, chr: char) -> bool
+{
+    match name {
+        "L" | "Lm" | "Lo" | "Lt" => chr.is_alphabetic(),
+        "Lu" => chr.is_uppercase(),
+        "Ll" => chr.is_lowercase(),
+        "N" | "Nd" => chr.is_numeric(),
+        "P"  => chr.is_ascii_punctuation(),
+        "Z" | "Zs" => chr.is_whitespace() && !chr.is_control(),
+        "C" | "Cc" => chr.is_control(),
+        _ => false,
+    }
+}
+
+impl ClassItem
+{
+    fn contains(&self, chr: char) -> bool
+    {
+        match self {
+            ClassItem::Range(low, high) => *low <= chr && chr <= *high,
+            ClassItem::Digit(negated)   => chr.is_ascii_digit() != *negated,
+            ClassItem::Word(negated)    => (chr.is_ascii_alphanumeric() || chr == '_') != *negated,
+            ClassItem::Space(negated)   => chr.is_whitespace() != *negated,
+            ClassItem::Category(name, negated) => category_contains(name, chr) != *negated,
+        }
+    }
+}
+
+impl Class
+{
+    fn contains(&self, chr: char) -> bool
+    {
+        self.items.iter().any(|item| item.contains(chr)) != self.negated
+    }
+}
+
+// Appends the instructions matching `node`.
+fn compile(node: &Node, program: &mut Vec<Instruction>) -> Result<(), String>
+{
+    if program.len() > MAX_PROGRAM {
+        return Err("pattern too large".to_string());
+    }
+
+    match node {
+        Node::Empty => {},
+        Node::Char(chr) => program.push(Instruction::Char(*chr)),
+        Node::Any => program.push(Instruction::Any),
+        Node::Class(class) => program.push(Instruction::Class(class.clone())),
+        Node::Start => program.push(Instruction::Start),
+        Node::End => program.push(Instruction::End),
+        Node::Concat(nodes) => {
+            for node in nodes {
+                compile(node, program)?;
+            }
+        },
+        Node::Alternate(branches) => {
+            let mut jumps = vec![];
+
+            for (index, branch) in branches.iter().enumerate() {
+                let split = program.len();
+                if index + 1 < branches.len() {
+                    program.push(Instruction::Split(split + 1, 0));
+                }
+
+                compile(branch, program)?;
+
+                if index + 1 < branches.len() {
+                    jumps.push(program.len());
+                    program.push(Instruction::Jump(0));
+                    program[split] = Instruction::Split(split + 1, program.len());
+                }
+            }
+
+            for jump in jumps {
+                program[jump] = Instruction::Jump(program.len());
+            }
+        },
+        Node::Repeat(node, min, max) => {
+            for _ in 0..*min {
+                compile(node, program)?;
+            }
+
+            match max {
+                None => {
+                    let split = program.len();
+                    program.push(Instruction::Split(split + 1, 0));
+                    compile(node, program)?;
+                    program.push(Instruction::Jump(split));
+                    program[split] = Instruction::Split(split + 1, program.len());
+                },
+                Some(max) => {
+                    let mut splits = vec![];
+
+                    for _ in *min..*max {
+                        splits.push(program.len());
+                        program.push(Instruction::Split(0, 0));
+                        compile(node, program)?;
+                    }
+
+                    for split in splits {
+                        program[split] = Instruction::Split(split + 1, program.len());
+                    }
+                },
+            }
+        },
+    }
+
+    Ok(())
+}
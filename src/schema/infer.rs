@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+
+use crate::json::{ object, Json };
+use crate::number::Number;
+
+/// Settings for `infer_with`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InferOptions
+{
+    /// Strings become an `enum` when they take at most this many distinct
+    /// values, 0 disables enums.
+    pub max_enum_values: usize,
+    /// How many times, on average, each distinct string must have been seen
+    /// for an `enum`, so one-off identifiers aren't taken for one.
+    pub min_enum_repeats: usize,
+}
+
+impl Default for InferOptions
+{
+    fn default() -> InferOptions
+    {
+        InferOptions { max_enum_values: 8, min_enum_repeats: 2 }
+    }
+}
+
+// Everything seen at one location of the samples.
+#[derive(Default)]
+struct Shape
+{
+    null:    bool,
+    boolean: bool,
+    integer: bool,
+    float:   bool,
+    // Distinct strings in the order they were seen, no more than one past
+    // what an enum can hold.
+    strings: Vec<String>,
+    seen:    usize,
+    arrays:  usize,
+    items:   Option<Box<Shape>>,
+    objects: usize,
+    members: BTreeMap<String, (usize, Shape)>,
+}
+
+impl Shape
+{
+    fn add(&mut self, json: &Json, options: &InferOptions)
+    {
+        match json {
+            Json::Null(_)       => self.null = true,
+            Json::Boolean(_, _) => self.boolean = true,
+            Json::Number(Number::Float(_), _) => self.float = true,
+            Json::Number(_, _)  => self.integer = true,
+            Json::String(value, _) => {
+                self.seen += 1;
+                if self.strings.len() <= options.max_enum_values && !self.strings.contains(value) {
+                    self.strings.push(value.clone());
+                }
+            },
+            Json::Array(elements, _) => {
+                self.arrays += 1;
+                for element in elements {
+                    self.items.get_or_insert_with(Box::default).add(element, options);
+                }
+            },
+            Json::Object(members, _) => {
+                self.objects += 1;
+                for (key, value) in members {
+                    let (count, shape) = self.members.entry(key.clone()).or_default();
+                    *count += 1;
+                    shape.add(value, options);
+                }
+            },
+        }
+    }
+
+    fn schema(&self, options: &InferOptions) -> Vec<(String, Json)>
+    {
+        let mut types = vec![];
+
+        if self.null    { types.push("null"); }
+        if self.boolean { types.push("boolean"); }
+        if self.float   { types.push("number"); } else if self.integer { types.push("integer"); }
+        if self.seen > 0    { types.push("string"); }
+        if self.arrays > 0  { types.push("array"); }
+        if self.objects > 0 { types.push("object"); }
+
+        let mut schema = vec![];
+
+        match types.len() {
+            0 => return schema,
+            1 => schema.push(("type".to_string(), Json::from(types[0]))),
+            _ => schema.push(("type".to_string(), Json::from(types.iter().map(|name| Json::from(*name)).collect::<Vec<Json>>()))),
+        }
+
+        // Only plain (or nullable) strings can be listed as an enum.
+        let only_strings = types.iter().all(|name| *name == "string" || *name == "null");
+        let repeated     = self.seen >= self.strings.len() * options.min_enum_repeats;
+
+        if self.seen > 0 && only_strings && self.strings.len() <= options.max_enum_values && repeated {
+            let mut values: Vec<Json> = self.strings.iter().map(|value| Json::from(value.clone())).collect();
+            if self.null {
+                values.push(Json::from(()));
+            }
+
+            schema.push(("enum".to_string(), Json::from(values)));
+        }
+
+        if let Some(items) = &self.items {
+            schema.push(("items".to_string(), object(items.schema(options))));
+        }
+
+        if self.objects > 0 {
+            let properties = self.members.iter()
+                .map(|(key, (_, shape))| (key.clone(), object(shape.schema(options))))
+                .collect();
+
+            let required: Vec<Json> = self.members.iter()
+                .filter(|(_, (count, _))| *count == self.objects)
+                .map(|(key, _)| Json::from(key.clone()))
+                .collect();
+
+            schema.push(("properties".to_string(), object(properties)));
+            schema.push(("required".to_string(), Json::from(required)));
+        }
+
+        schema
+    }
+}
+
+/// Infers a schema every sample is valid against.
+///
+/// Types seen at the same location are merged, object members missing from
+/// some samples are left out of `required`, integers only become `number`
+/// once a float shows up, and small sets of repeated strings become an
+/// `enum`.
+pub fn infer(samples: &[Json]) -> Json
+{
+    infer_with(samples, &InferOptions::default())
+}
+
+pub fn infer_with(samples: &[Json], options: &InferOptions) -> Json
+{
+    let mut shape = Shape::default();
+
+    for sample in samples {
+        shape.add(sample, options);
+    }
+
+    let mut schema = vec![(
+        "$schema".to_string(),
+        Json::from("https://json-schema.org/draft/2020-12/schema"),
+    )];
+    schema.extend(shape.schema(options));
+
+    object(schema)
+}
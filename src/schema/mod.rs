@@ -0,0 +1,231 @@
+//! JSON Schema validation, following draft 2020-12.
+//!
+//! Supported keywords are `type`, `enum`, `const`, the numeric, string,
+//! array and object bounds, `pattern`, `properties`, `patternProperties`,
+//! `additionalProperties`, `required`, `prefixItems`, `items`, `allOf`,
+//! `anyOf`, `oneOf`, `not` and `$ref` to locations in the same document.
+//! Other keywords are ignored. A `$ref` that comes back to the same schema
+//! for the same value would never end, and fails as a reference cycle.
+//!
+//! Schemas can also be inferred from sample documents with `infer`.
+
+mod infer;
+mod validate;
+
+use std::collections::{ HashMap, HashSet };
+use std::fmt;
+
+use crate::json::Json;
+use crate::pointer::JsonPointer;
+use crate::regex::Regex;
+use crate::span::Spans;
+
+pub use self::infer::{ infer, infer_with, InferOptions };
+
+/// A schema ready to validate documents.
+#[derive(Debug)]
+pub struct Schema
+{
+    root:     Json,
+    patterns: HashMap<String, Regex>,
+}
+
+/// Error found while compiling a schema, `path` points into the schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaError
+{
+    pub path:    JsonPointer,
+    pub message: String,
+}
+
+/// A failed validation.
+///
+/// `instance_path` points at the offending value and `schema_path` at the
+/// keyword that rejected it, going through every `$ref` on the way. The
+/// position of the value is given by `line` and `column`, both starting at
+/// 1, in the text the document was parsed from when validated with
+/// `validate_with_spans`, in its source otherwise.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationError
+{
+    pub instance_path: JsonPointer,
+    pub schema_path:   JsonPointer,
+    pub message:       String,
+    pub line:          usize,
+    pub column:        usize,
+}
+
+impl fmt::Display for SchemaError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "{} at {}", self.message, self.path)
+    }
+}
+
+impl fmt::Display for ValidationError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "{}:{}: {} ({})", self.line, self.column, self.message, self.schema_path)
+    }
+}
+
+impl Schema
+{
+    /// Checks that every `pattern` compiles and every `$ref` resolves.
+    pub fn new(root: Json) -> Result<Schema, SchemaError>
+    {
+        let mut compiler = Compiler { root: &root, patterns: HashMap::new(), references: HashSet::new() };
+        compiler.compile(&root, &JsonPointer::root())?;
+
+        let patterns = compiler.patterns;
+        Ok(Schema { root, patterns })
+    }
+
+    pub fn is_valid(&self, instance: &Json) -> bool
+    {
+        self.validate(instance).is_ok()
+    }
+
+    /// Validates `instance`, returning every error found.
+    pub fn validate(&self, instance: &Json) -> Result<(), Vec<ValidationError>>
+    {
+        let errors = validate::validate(self, instance, None);
+
+        match errors.is_empty() {
+            true  => Ok(()),
+            false => Err(errors),
+        }
+    }
+
+    /// Validates a document parsed with `Json::parse_with_spans`, the
+    /// positions of the errors being those in the parsed text.
+    pub fn validate_with_spans(&self, instance: &Json, spans: &Spans) -> Result<(), Vec<ValidationError>>
+    {
+        let errors = validate::validate(self, instance, Some(spans));
+
+        match errors.is_empty() {
+            true  => Ok(()),
+            false => Err(errors),
+        }
+    }
+}
+
+impl Json
+{
+    /// Validates this document against `schema`.
+    pub fn validate(&self, schema: &Schema) -> Result<(), Vec<ValidationError>>
+    {
+        schema.validate(self)
+    }
+}
+
+// Resolves a `$ref` to a location of the same document, `#` being the root.
+fn resolve<'a>(root: &'a Json, reference: &str) -> Option<(&'a Json, JsonPointer)>
+{
+    let fragment = reference.strip_prefix('#')?;
+    let pointer  = JsonPointer::parse(&percent_decode(fragment)?).ok()?;
+
+    pointer.resolve(root).ok().map(|schema| (schema, pointer))
+}
+
+fn percent_decode(text: &str) -> Option<String>
+{
+    let mut bytes = vec![];
+    let mut iter  = text.bytes();
+
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let high = (iter.next()? as char).to_digit(16)?;
+            let low  = (iter.next()? as char).to_digit(16)?;
+            bytes.push((high * 16 + low) as u8);
+        } else {
+            bytes.push(byte);
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+struct Compiler<'a>
+{
+    root:     &'a Json,
+    patterns: HashMap<String, Regex>,
+    // Targets of the references compiled so far, so that cycles end.
+    references: HashSet<JsonPointer>,
+}
+
+impl<'a> Compiler<'a>
+{
+    fn compile(&mut self, schema: &'a Json, path: &JsonPointer) -> Result<(), SchemaError>
+    {
+        let members = match schema {
+            Json::Object(members, _) => members,
+            Json::Boolean(_, _) => return Ok(()),
+            _ => return Err(SchemaError { path: path.clone(), message: "schema must be an object or a boolean".to_string() }),
+        };
+
+        if let Some(Json::String(pattern, _)) = members.get("pattern") {
+            self.add_pattern(pattern, &path.child("pattern"))?;
+        }
+
+        // The target may be outside of the places searched below, it is
+        // compiled from where it sits in the document.
+        if let Some(Json::String(reference, _)) = members.get("$ref") {
+            match resolve(self.root, reference) {
+                Some((target, pointer)) => {
+                    if self.references.insert(pointer.clone()) {
+                        self.compile(target, &pointer)?;
+                    }
+                },
+                None => {
+                    return Err(SchemaError { path: path.child("$ref"), message: format!("can't resolve {}", reference) });
+                },
+            }
+        }
+
+        for keyword in &["properties", "patternProperties", "$defs", "definitions"] {
+            if let Some(Json::Object(subschemas, _)) = members.get(*keyword) {
+                for (key, subschema) in subschemas {
+                    if *keyword == "patternProperties" {
+                        self.add_pattern(key, &path.child(*keyword).child(key.as_str()))?;
+                    }
+
+                    self.compile(subschema, &path.child(*keyword).child(key.as_str()))?;
+                }
+            }
+        }
+
+        for keyword in &["allOf", "anyOf", "oneOf", "prefixItems"] {
+            if let Some(Json::Array(subschemas, _)) = members.get(*keyword) {
+                for (index, subschema) in subschemas.iter().enumerate() {
+                    self.compile(subschema, &path.child(*keyword).child(index.to_string()))?;
+                }
+            }
+        }
+
+        for keyword in &["not", "items", "additionalProperties"] {
+            if let Some(subschema) = members.get(*keyword) {
+                self.compile(subschema, &path.child(*keyword))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_pattern(&mut self, pattern: &str, path: &JsonPointer) -> Result<(), SchemaError>
+    {
+        if self.patterns.contains_key(pattern) {
+            return Ok(());
+        }
+
+        match Regex::new(pattern) {
+            Ok(regex) => {
+                self.patterns.insert(pattern.to_string(), regex);
+                Ok(())
+            },
+            Err(message) => Err(SchemaError { path: path.clone(), message: format!("invalid pattern: {}", message) }),
+        }
+    }
+}
@@ -0,0 +1,465 @@
+use std::cmp::Ordering;
+use std::collections::{ HashMap, HashSet };
+
+use crate::json::{ values_equal, Json };
+use crate::number::{ as_i128, compare_numbers, Number };
+use crate::pointer::JsonPointer;
+use crate::schema::{ resolve, Schema, ValidationError };
+use crate::span::Spans;
+
+// A schema and a value of the instance, by address.
+type Pair = (usize, usize);
+
+struct Validator<'a>
+{
+    schema: &'a Schema,
+    errors: Vec<ValidationError>,
+    // Pairs being checked, meeting one again means `$ref` went round in a
+    // circle without moving into the instance.
+    active: HashSet<Pair>,
+    // Whether a subschema of `anyOf`, `oneOf` or `not` passed, so that
+    // branching references don't evaluate the same pair over and over.
+    passed: HashMap<Pair, bool>,
+}
+
+pub fn validate(schema: &Schema, instance: &Json, spans: Option<&Spans>) -> Vec<ValidationError>
+{
+    let mut validator = Validator { schema, errors: vec![], active: HashSet::new(), passed: HashMap::new() };
+    validator.check(&schema.root, &JsonPointer::root(), instance, &JsonPointer::root());
+
+    // Positions are only worked out for the values that failed.
+    let source = instance.to_source();
+
+    validator.errors.into_iter().map(|mut error| {
+        match spans.and_then(|spans| spans.value(&error.instance_path)) {
+            Some(span) => {
+                error.line   = span.start.line;
+                error.column = span.start.column;
+            },
+            None => {
+                let offset = error.instance_path.offset(instance).unwrap_or(0);
+                let before = &source[..offset];
+
+                error.line   = before.matches('\n').count() + 1;
+                error.column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+            },
+        }
+        error
+    }).collect()
+}
+
+fn type_name(instance: &Json) -> &'static str
+{
+    match instance {
+        Json::Object(_, _)  => "object",
+        Json::Array(_, _)   => "array",
+        Json::String(_, _)  => "string",
+        Json::Number(_, _)  => "number",
+        Json::Boolean(_, _) => "boolean",
+        Json::Null(_)       => "null",
+    }
+}
+
+fn is_integer(number: &Number) -> bool
+{
+    match number {
+        Number::Float(value) => value.fract() == 0.,
+        _ => true,
+    }
+}
+
+fn has_type(instance: &Json, name: &str) -> bool
+{
+    match (name, instance) {
+        ("integer", Json::Number(number, _)) => is_integer(number),
+        (name, instance) => type_name(instance) == name,
+    }
+}
+
+// Whether `value` is an exact multiple of `divisor`, floats being taken as
+// the shortest decimal that reads back as them, so 0.3 is a multiple of 0.1.
+fn is_multiple(value: &Number, divisor: &Number) -> bool
+{
+    let ((value, value_exponent), (divisor, divisor_exponent)) = match (decimal(value), decimal(divisor)) {
+        (Some(value), Some(divisor)) if divisor.0 != 0 => (value, divisor),
+        _ => { return false; },
+    };
+
+    match value_exponent >= divisor_exponent {
+        // value × 10^k modulo divisor.
+        true => {
+            let divisor = divisor.unsigned_abs();
+            let scale   = power_modulo(10, (value_exponent - divisor_exponent) as u32, divisor);
+            let rest    = (value.unsigned_abs() % divisor) * scale % divisor;
+
+            rest == 0
+        },
+        false => {
+            let scale = 10i128.checked_pow((divisor_exponent - value_exponent) as u32);
+
+            match scale.and_then(|scale| divisor.checked_mul(scale)) {
+                Some(divisor) => value % divisor == 0,
+                None          => value == 0,
+            }
+        },
+    }
+}
+
+// Digits and exponent of a finite number, as in `digits × 10^exponent`.
+fn decimal(number: &Number) -> Option<(i128, i32)>
+{
+    match number {
+        Number::Float(value) if value.is_finite() => {
+            let text = format!("{:e}", value);
+            let (mantissa, exponent) = text.split_once('e')?;
+            let fraction = mantissa.split_once('.').map_or(0, |(_, fraction)| fraction.len() as i32);
+
+            Some((mantissa.replace('.', "").parse().ok()?, exponent.parse::<i32>().ok()? - fraction))
+        },
+        Number::Float(_) => None,
+        number => Some((as_i128(number), 0)),
+    }
+}
+
+fn power_modulo(base: u128, mut exponent: u32, modulus: u128) -> u128
+{
+    let mut result = 1 % modulus;
+    let mut base   = base % modulus;
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus;
+        }
+
+        base = base * base % modulus;
+        exponent >>= 1;
+    }
+
+    result
+}
+
+impl<'a> Validator<'a>
+{
+    fn fail(&mut self, instance_path: &JsonPointer, schema_path: &JsonPointer, message: String)
+    {
+        self.errors.push(ValidationError {
+            instance_path: instance_path.clone(),
+            schema_path:   schema_path.clone(),
+            message,
+            line:   0,
+            column: 0,
+        });
+    }
+
+    // Every pattern the validator can reach is compiled by `Schema::new`, one
+    // missing is a bug, which fails the value rather than letting it through.
+    fn uncompiled(&mut self, instance_path: &JsonPointer, schema_path: &JsonPointer, pattern: &str)
+    {
+        self.fail(instance_path, schema_path, format!("internal error: pattern {} wasn't compiled", pattern));
+    }
+
+    // Runs `schema` on its own, returning whether it passed.
+    fn passes(&mut self, schema: &Json, schema_path: &JsonPointer, instance: &Json, instance_path: &JsonPointer) -> bool
+    {
+        let pair = (schema as *const Json as usize, instance as *const Json as usize);
+
+        if let Some(passed) = self.passed.get(&pair) {
+            return *passed;
+        }
+
+        let errors = std::mem::take(&mut self.errors);
+        self.check(schema, schema_path, instance, instance_path);
+
+        let passed = std::mem::replace(&mut self.errors, errors).is_empty();
+        self.passed.insert(pair, passed);
+        passed
+    }
+
+    fn check(&mut self, schema: &Json, schema_path: &JsonPointer, instance: &Json, instance_path: &JsonPointer)
+    {
+        let members = match schema {
+            Json::Boolean(true, _) => return,
+            Json::Boolean(false, _) => {
+                return self.fail(instance_path, schema_path, "no value is allowed here".to_string());
+            },
+            Json::Object(members, _) => members,
+            _ => return,
+        };
+
+        let pair = (schema as *const Json as usize, instance as *const Json as usize);
+
+        if !self.active.insert(pair) {
+            return self.fail(instance_path, schema_path, "reference cycle".to_string());
+        }
+
+        if let Some(Json::String(reference, _)) = members.get("$ref") {
+            if let Some((target, _)) = resolve(&self.schema.root, reference) {
+                self.check(target, &schema_path.child("$ref"), instance, instance_path);
+            }
+        }
+
+        self.check_generic(members, schema_path, instance, instance_path);
+        self.check_combinators(members, schema_path, instance, instance_path);
+
+        match instance {
+            Json::Number(number, _)   => self.check_number(members, schema_path, number, instance_path),
+            Json::String(text, _)     => self.check_string(members, schema_path, text, instance_path),
+            Json::Array(elements, _)  => self.check_array(members, schema_path, elements, instance_path),
+            Json::Object(properties, _) => self.check_object(members, schema_path, properties, instance_path),
+            _ => {},
+        }
+
+        self.active.remove(&pair);
+    }
+
+    fn check_generic(&mut self, members: &HashMap<String, Json>, schema_path: &JsonPointer, instance: &Json, instance_path: &JsonPointer)
+    {
+        match members.get("type") {
+            Some(Json::String(name, _)) if !has_type(instance, name) => {
+                let message = format!("expected {}, found {}", name, type_name(instance));
+                self.fail(instance_path, &schema_path.child("type"), message);
+            },
+            Some(Json::Array(names, _)) => {
+                let names: Vec<&str> = names.iter().filter_map(Json::as_str).collect();
+
+                if !names.iter().any(|name| has_type(instance, name)) {
+                    let message = format!("expected one of {}, found {}", names.join(", "), type_name(instance));
+                    self.fail(instance_path, &schema_path.child("type"), message);
+                }
+            },
+            _ => {},
+        }
+
+        if let Some(Json::Array(values, _)) = members.get("enum") {
+            if !values.iter().any(|value| values_equal(value, instance)) {
+                self.fail(instance_path, &schema_path.child("enum"), "value is not one of the allowed values".to_string());
+            }
+        }
+
+        if let Some(value) = members.get("const") {
+            if !values_equal(value, instance) {
+                self.fail(instance_path, &schema_path.child("const"), format!("expected {}", value));
+            }
+        }
+    }
+
+    fn check_combinators(
+        &mut self, members: &HashMap<String, Json>, schema_path: &JsonPointer,
+        instance: &Json, instance_path: &JsonPointer
+    )
+    {
+        if let Some(Json::Array(subschemas, _)) = members.get("allOf") {
+            for (index, subschema) in subschemas.iter().enumerate() {
+                self.check(subschema, &schema_path.child("allOf").child(index.to_string()), instance, instance_path);
+            }
+        }
+
+        if let Some(Json::Array(subschemas, _)) = members.get("anyOf") {
+            let path = schema_path.child("anyOf");
+
+            let matched = subschemas.iter().enumerate().any(|(index, subschema)| {
+                self.passes(subschema, &path.child(index.to_string()), instance, instance_path)
+            });
+
+            if !matched {
+                self.fail(instance_path, &path, "value doesn't match any of the schemas".to_string());
+            }
+        }
+
+        if let Some(Json::Array(subschemas, _)) = members.get("oneOf") {
+            let path = schema_path.child("oneOf");
+
+            let matched = subschemas.iter().enumerate()
+                .filter(|(index, subschema)| self.passes(subschema, &path.child(index.to_string()), instance, instance_path))
+                .count();
+
+            if matched != 1 {
+                self.fail(instance_path, &path, format!("value matches {} of the schemas instead of exactly one", matched));
+            }
+        }
+
+        if let Some(subschema) = members.get("not") {
+            let path = schema_path.child("not");
+
+            if self.passes(subschema, &path, instance, instance_path) {
+                self.fail(instance_path, &path, "value must not match the schema".to_string());
+            }
+        }
+    }
+
+    fn check_number(&mut self, members: &HashMap<String, Json>, schema_path: &JsonPointer, number: &Number, instance_path: &JsonPointer)
+    {
+        let bounds: [(&str, &[Ordering], &str); 4] = [
+            ("minimum",          &[Ordering::Greater, Ordering::Equal], "greater than or equal to"),
+            ("maximum",          &[Ordering::Less, Ordering::Equal],    "less than or equal to"),
+            ("exclusiveMinimum", &[Ordering::Greater],                  "greater than"),
+            ("exclusiveMaximum", &[Ordering::Less],                     "less than"),
+        ];
+
+        for (keyword, allowed, description) in bounds.iter() {
+            if let Some(Json::Number(limit, _)) = members.get(*keyword) {
+                let valid = compare_numbers(number, limit).is_some_and(|ordering| allowed.contains(&ordering));
+
+                if !valid {
+                    let message = format!("{} must be {} {}", number, description, limit);
+                    self.fail(instance_path, &schema_path.child(*keyword), message);
+                }
+            }
+        }
+
+        if let Some(Json::Number(divisor, _)) = members.get("multipleOf") {
+            if !is_multiple(number, divisor) {
+                self.fail(instance_path, &schema_path.child("multipleOf"), format!("{} is not a multiple of {}", number, divisor));
+            }
+        }
+    }
+
+    fn check_string(&mut self, members: &HashMap<String, Json>, schema_path: &JsonPointer, text: &str, instance_path: &JsonPointer)
+    {
+        let length = text.chars().count();
+
+        if let Some(Json::Number(limit, _)) = members.get("minLength") {
+            if (length as f64) < f64::from(limit.clone()) {
+                self.fail(instance_path, &schema_path.child("minLength"), format!("string is shorter than {} characters", limit));
+            }
+        }
+
+        if let Some(Json::Number(limit, _)) = members.get("maxLength") {
+            if (length as f64) > f64::from(limit.clone()) {
+                self.fail(instance_path, &schema_path.child("maxLength"), format!("string is longer than {} characters", limit));
+            }
+        }
+
+        if let Some(Json::String(pattern, _)) = members.get("pattern") {
+            match self.schema.patterns.get(pattern) {
+                Some(regex) if regex.is_match(text) => {},
+                Some(_) => self.fail(instance_path, &schema_path.child("pattern"), format!("string doesn't match {}", pattern)),
+                None    => self.uncompiled(instance_path, &schema_path.child("pattern"), pattern),
+            }
+        }
+    }
+
+    fn check_array(&mut self, members: &HashMap<String, Json>, schema_path: &JsonPointer, elements: &[Json], instance_path: &JsonPointer)
+    {
+        let mut first = 0;
+
+        if let Some(Json::Array(subschemas, _)) = members.get("prefixItems") {
+            for (index, (subschema, element)) in subschemas.iter().zip(elements).enumerate() {
+                let path = schema_path.child("prefixItems").child(index.to_string());
+                self.check(subschema, &path, element, &instance_path.child(index.to_string()));
+            }
+
+            first = subschemas.len();
+        }
+
+        if let Some(subschema) = members.get("items") {
+            for (index, element) in elements.iter().enumerate().skip(first) {
+                self.check(subschema, &schema_path.child("items"), element, &instance_path.child(index.to_string()));
+            }
+        }
+
+        if let Some(Json::Number(limit, _)) = members.get("minItems") {
+            if (elements.len() as f64) < f64::from(limit.clone()) {
+                self.fail(instance_path, &schema_path.child("minItems"), format!("array has fewer than {} items", limit));
+            }
+        }
+
+        if let Some(Json::Number(limit, _)) = members.get("maxItems") {
+            if (elements.len() as f64) > f64::from(limit.clone()) {
+                self.fail(instance_path, &schema_path.child("maxItems"), format!("array has more than {} items", limit));
+            }
+        }
+
+        if let Some(Json::Boolean(true, _)) = members.get("uniqueItems") {
+            let repeated = (0..elements.len()).any(|left| {
+                (left + 1..elements.len()).any(|right| values_equal(&elements[left], &elements[right]))
+            });
+
+            if repeated {
+                self.fail(instance_path, &schema_path.child("uniqueItems"), "array items are not unique".to_string());
+            }
+        }
+    }
+
+    fn check_object(
+        &mut self, members: &HashMap<String, Json>, schema_path: &JsonPointer,
+        properties: &HashMap<String, Json>, instance_path: &JsonPointer
+    )
+    {
+        // Properties are visited in key order, so errors come out in a
+        // stable order.
+        let mut keys: Vec<&String> = properties.keys().collect();
+        keys.sort();
+
+        let declared = match members.get("properties") {
+            Some(Json::Object(declared, _)) => Some(declared),
+            _ => None,
+        };
+
+        let patterns = match members.get("patternProperties") {
+            Some(Json::Object(patterns, _)) => {
+                let mut patterns: Vec<(&String, &Json)> = patterns.iter().collect();
+                patterns.sort_by(|left, right| left.0.cmp(right.0));
+                patterns
+            },
+            _ => vec![],
+        };
+
+        for key in keys {
+            let value = &properties[key];
+            let path  = instance_path.child(key.as_str());
+            let mut evaluated = false;
+
+            if let Some(subschema) = declared.and_then(|declared| declared.get(key)) {
+                self.check(subschema, &schema_path.child("properties").child(key.as_str()), value, &path);
+                evaluated = true;
+            }
+
+            for (pattern, subschema) in &patterns {
+                let schema_path = schema_path.child("patternProperties").child(pattern.as_str());
+
+                match self.schema.patterns.get(pattern.as_str()) {
+                    Some(regex) if regex.is_match(key) => {
+                        self.check(subschema, &schema_path, value, &path);
+                        evaluated = true;
+                    },
+                    Some(_) => {},
+                    None    => self.uncompiled(&path, &schema_path, pattern),
+                }
+            }
+
+            if !evaluated {
+                if let Some(subschema) = members.get("additionalProperties") {
+                    let schema_path = schema_path.child("additionalProperties");
+
+                    match subschema {
+                        Json::Boolean(false, _) => {
+                            self.fail(&path, &schema_path, format!("property {} is not allowed", key));
+                        },
+                        subschema => self.check(subschema, &schema_path, value, &path),
+                    }
+                }
+            }
+        }
+
+        if let Some(Json::Array(required, _)) = members.get("required") {
+            for name in required.iter().filter_map(Json::as_str) {
+                if !properties.contains_key(name) {
+                    self.fail(instance_path, &schema_path.child("required"), format!("missing required property {}", name));
+                }
+            }
+        }
+
+        if let Some(Json::Number(limit, _)) = members.get("minProperties") {
+            if (properties.len() as f64) < f64::from(limit.clone()) {
+                self.fail(instance_path, &schema_path.child("minProperties"), format!("object has fewer than {} properties", limit));
+            }
+        }
+
+        if let Some(Json::Number(limit, _)) = members.get("maxProperties") {
+            if (properties.len() as f64) > f64::from(limit.clone()) {
+                self.fail(instance_path, &schema_path.child("maxProperties"), format!("object has more than {} properties", limit));
+            }
+        }
+    }
+}
@@ -0,0 +1,516 @@
+use std::fmt;
+
+use ::serde::de::{ self, Deserialize, DeserializeSeed, IntoDeserializer, Visitor };
+
+use crate::error;
+use crate::json::{ object, Json };
+use crate::number::Number;
+use crate::parser::{ boolean, null, number, string };
+use crate::serde::Error;
+
+/// Deserializes an instance of `T` from JSON text.
+pub fn from_str<'de, T: Deserialize<'de>>(text: &'de str) -> Result<T, Error>
+{
+    let mut deserializer = Deserializer::from_str(text);
+
+    match T::deserialize(&mut deserializer) {
+        Ok(value) => {
+            deserializer.end()?;
+            Ok(value)
+        },
+        Err(error) => Err(deserializer.locate(error)),
+    }
+}
+
+/// A deserializer reading JSON text, it keeps track of its position so
+/// errors can point at the offending character.
+pub struct Deserializer<'de>
+{
+    input:  &'de str,
+    offset: usize,
+}
+
+impl<'de> Deserializer<'de>
+{
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(input: &'de str) -> Deserializer<'de>
+    {
+        Deserializer { input, offset: 0 }
+    }
+
+    /// Checks that only whitespace is left after the value.
+    pub fn end(&mut self) -> Result<(), Error>
+    {
+        match self.peek() {
+            Some(_) => Err(self.error_at(self.offset, "trailing characters")),
+            None    => Ok(()),
+        }
+    }
+
+    // Skips whitespace and returns the next character without consuming it.
+    fn peek(&mut self) -> Option<char>
+    {
+        let rest = &self.input[self.offset..];
+        let trimmed = rest.trim_start_matches([' ', '\t', '\r', '\n']);
+        self.offset += rest.len() - trimmed.len();
+
+        trimmed.chars().next()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Error>
+    {
+        match self.peek() {
+            Some(chr) if chr == expected => {
+                self.offset += chr.len_utf8();
+                Ok(())
+            },
+            Some(chr) => Err(self.error_at(self.offset, format!("expected `{}`, found `{}`", expected, chr))),
+            None      => Err(self.error_at(self.offset, "unexpected end of input")),
+        }
+    }
+
+    fn error_at<T: fmt::Display>(&self, offset: usize, message: T) -> Error
+    {
+        let before = &self.input[..offset];
+        let line   = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+
+        Error { message: message.to_string(), line, column }
+    }
+
+    // Gives errors without a position the current one.
+    fn locate(&self, error: Error) -> Error
+    {
+        match error.line {
+            0 => self.error_at(self.offset, error.message),
+            _ => error,
+        }
+    }
+
+    // Reads a scalar through the regular parser, the source of the parsed
+    // value tells how much of the input it used.
+    fn scalar(&mut self) -> Result<Json, Error>
+    {
+        let first = match self.peek() {
+            Some(chr) => chr,
+            None      => return Err(self.error_at(self.offset, "unexpected end of input")),
+        };
+
+        let mut chars = self.input[self.offset..].chars();
+
+        let result = {
+            let mut slice = (&mut chars).peekable();
+
+            match first {
+                'n'             => null(&mut slice),
+                't' | 'f'       => boolean(&mut slice),
+                '0'..='9' | '-' => number(&mut slice),
+                '"'             => string(&mut slice),
+                _ => return Err(self.error_at(self.offset, format!("expected value, found `{}`", first))),
+            }
+        };
+
+        match result {
+            // The parser takes numbers too large for a float as infinite.
+            Ok(Json::Number(Number::Float(value), _)) if !value.is_finite() => {
+                Err(self.error_at(self.offset, "number out of range"))
+            },
+            Ok(json) => {
+                self.offset += json.to_source().len();
+                Ok(json)
+            },
+            Err(error::Error::InvalidCharacter(found)) => {
+                // The offending character was the last one taken out.
+                let end  = self.input.len() - chars.as_str().len();
+                let last = self.input[..end].chars().next_back().map_or(0, char::len_utf8);
+
+                Err(self.error_at(end - last, format!("invalid character `{}`", found)))
+            },
+            Err(_) => Err(self.error_at(self.input.len(), "unexpected end of input")),
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de>
+{
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error>
+    {
+        match self.peek() {
+            Some('[') => {
+                self.offset += 1;
+                let value = visitor.visit_seq(Elements { de: &mut *self, first: true })?;
+                self.expect(']')?;
+                Ok(value)
+            },
+            Some('{') => {
+                self.offset += 1;
+                let value = visitor.visit_map(Members { de: &mut *self, first: true })?;
+                self.expect('}')?;
+                Ok(value)
+            },
+            _ => match self.scalar()? {
+                Json::Null(_)                          => visitor.visit_unit(),
+                Json::Boolean(value, _)                => visitor.visit_bool(value),
+                Json::Number(Number::Unsigned(value), _) => visitor.visit_u64(value),
+                Json::Number(Number::Integer(value), _)  => visitor.visit_i64(value),
+                Json::Number(Number::Float(value), _)    => visitor.visit_f64(value),
+                Json::String(value, _)                 => visitor.visit_string(value),
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error>
+    {
+        match self.peek() {
+            Some('n') => {
+                self.scalar()?;
+                visitor.visit_none()
+            },
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error>
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // Unit variants are plain strings, the others are objects with a single
+    // member named after the variant.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self, _name: &'static str, _variants: &'static [&'static str], visitor: V
+    ) -> Result<V::Value, Error>
+    {
+        match self.peek() {
+            Some('{') => {
+                self.offset += 1;
+                let value = visitor.visit_enum(Variant { de: &mut *self })?;
+                self.expect('}')?;
+                Ok(value)
+            },
+            Some('"') => match self.scalar()? {
+                Json::String(variant, _) => visitor.visit_enum(variant.into_deserializer()),
+                _ => unreachable!(),
+            },
+            _ => Err(self.error_at(self.offset, "expected string or object for enum")),
+        }
+    }
+
+    ::serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+struct Elements<'a, 'de: 'a>
+{
+    de:    &'a mut Deserializer<'de>,
+    first: bool,
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for Elements<'a, 'de>
+{
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
+    {
+        if self.de.peek() == Some(']') {
+            return Ok(None);
+        }
+
+        if !self.first {
+            self.de.expect(',')?;
+        }
+
+        self.first = false;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+struct Members<'a, 'de: 'a>
+{
+    de:    &'a mut Deserializer<'de>,
+    first: bool,
+}
+
+impl<'de, 'a> de::MapAccess<'de> for Members<'a, 'de>
+{
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    {
+        if self.de.peek() == Some('}') {
+            return Ok(None);
+        }
+
+        if !self.first {
+            self.de.expect(',')?;
+        }
+
+        self.first = false;
+
+        match self.de.peek() {
+            Some('"') => seed.deserialize(MapKey { de: &mut *self.de }).map(Some),
+            _ => Err(self.de.error_at(self.de.offset, "expected string key")),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error>
+    {
+        self.de.expect(':')?;
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+// Reads an object key, which is always a string, but is parsed when a
+// number or a boolean is asked for, the way those keys are serialized.
+struct MapKey<'a, 'de: 'a>
+{
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'a, 'de> MapKey<'a, 'de>
+{
+    fn parse<T: std::str::FromStr>(self) -> Result<T, Error>
+    {
+        let start = self.de.offset;
+
+        match self.de.scalar()? {
+            Json::String(key, _) => key.parse().map_err(|_| self.de.error_at(start, format!("invalid key {:?}", key))),
+            _ => unreachable!(),
+        }
+    }
+}
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident $visit:ident $type:ty,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error>
+            {
+                visitor.$visit(self.parse::<$type>()?)
+            }
+        )*
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for MapKey<'a, 'de>
+{
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error>
+    {
+        self.de.deserialize_any(visitor)
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool visit_bool bool,
+        deserialize_i8 visit_i8 i8,
+        deserialize_i16 visit_i16 i16,
+        deserialize_i32 visit_i32 i32,
+        deserialize_i64 visit_i64 i64,
+        deserialize_i128 visit_i128 i128,
+        deserialize_u8 visit_u8 u8,
+        deserialize_u16 visit_u16 u16,
+        deserialize_u32 visit_u32 u32,
+        deserialize_u64 visit_u64 u64,
+        deserialize_u128 visit_u128 u128,
+        deserialize_f32 visit_f32 f32,
+        deserialize_f64 visit_f64 f64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error>
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error>
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self, name: &'static str, variants: &'static [&'static str], visitor: V
+    ) -> Result<V::Value, Error>
+    {
+        self.de.deserialize_enum(name, variants, visitor)
+    }
+
+    ::serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+struct Variant<'a, 'de: 'a>
+{
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'de, 'a> de::EnumAccess<'de> for Variant<'a, 'de>
+{
+    type Error   = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error>
+    {
+        let variant = seed.deserialize(&mut *self.de)?;
+        self.de.expect(':')?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, 'a> de::VariantAccess<'de> for Variant<'a, 'de>
+{
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error>
+    {
+        Deserialize::deserialize(self.de)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error>
+    {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _length: usize, visitor: V) -> Result<V::Value, Error>
+    {
+        de::Deserializer::deserialize_seq(self.de, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error>
+    {
+        de::Deserializer::deserialize_map(self.de, visitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Json
+{
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Json, D::Error>
+    {
+        deserializer.deserialize_any(JsonVisitor)
+    }
+}
+
+struct JsonVisitor;
+
+impl<'de> Visitor<'de> for JsonVisitor
+{
+    type Value = Json;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Json, E>
+    {
+        Ok(Json::from(()))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Json, E>
+    {
+        Ok(Json::from(()))
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Json, D::Error>
+    {
+        Json::deserialize(deserializer)
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Json, E>
+    {
+        Ok(Json::from(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Json, E>
+    {
+        Ok(Json::from(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Json, E>
+    {
+        match value {
+            value if value >= 0 => Ok(Json::from(value as u64)),
+            value => Ok(Json::from(value)),
+        }
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Json, E>
+    {
+        Ok(Json::from(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Json, E>
+    {
+        Ok(Json::from(value))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<Json, E>
+    {
+        Ok(Json::from(value))
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Json, A::Error>
+    {
+        let mut elements = vec![];
+
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+
+        Ok(Json::from(elements))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Json, A::Error>
+    {
+        let mut members: Vec<(String, Json)> = vec![];
+
+        while let Some((key, value)) = map.next_entry::<String, Json>()? {
+            members.retain(|(existing, _)| *existing != key);
+            members.push((key, value));
+        }
+
+        Ok(object(members))
+    }
+}
+
+impl<'de> Deserialize<'de> for Number
+{
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Number, D::Error>
+    {
+        struct NumberVisitor;
+
+        impl<'de> Visitor<'de> for NumberVisitor
+        {
+            type Value = Number;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result
+            {
+                f.write_str("a number")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Number, E>
+            {
+                Ok(Number::Unsigned(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Number, E>
+            {
+                Ok(Number::Integer(value))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Number, E>
+            {
+                Ok(Number::Float(value))
+            }
+        }
+
+        deserializer.deserialize_any(NumberVisitor)
+    }
+}
+
@@ -0,0 +1,63 @@
+//! Integration with serde, enabled by the `serde` feature.
+//!
+//! `Json` and `Number` implement `Serialize` and `Deserialize`, and any serde
+//! type can be read from text with `from_str` or written with `to_string`.
+
+mod de;
+mod ser;
+
+use std::error;
+use std::fmt;
+
+pub use self::de::{ from_str, Deserializer };
+pub use self::ser::{ to_json, to_string, Serializer };
+
+/// Error raised while serializing or deserializing.
+///
+/// Errors found while reading text carry the line and column where they
+/// happened, both starting at 1. They are 0 when there is no position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Error
+{
+    pub message: String,
+    pub line:    usize,
+    pub column:  usize,
+}
+
+impl Error
+{
+    fn new<T: fmt::Display>(message: T) -> Error
+    {
+        Error { message: message.to_string(), line: 0, column: 0 }
+    }
+}
+
+impl fmt::Display for Error
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        if self.line == 0 {
+            return f.write_str(&self.message);
+        }
+
+        write!(f, "{} at line {} column {}", self.message, self.line, self.column)
+    }
+}
+
+impl error::Error for Error {}
+
+impl ::serde::ser::Error for Error
+{
+    fn custom<T: fmt::Display>(message: T) -> Error
+    {
+        Error::new(message)
+    }
+}
+
+impl ::serde::de::Error for Error
+{
+    fn custom<T: fmt::Display>(message: T) -> Error
+    {
+        Error::new(message)
+    }
+}
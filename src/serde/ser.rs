@@ -0,0 +1,422 @@
+use ::serde::ser::{ self, Serialize };
+
+use crate::json::{ object, Json };
+use crate::number::Number;
+use crate::serde::Error;
+
+/// Serializes `value` into a `Json` tree.
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<Json, Error>
+{
+    value.serialize(Serializer)
+}
+
+/// Serializes `value` as compact JSON text. Struct fields keep their
+/// declaration order.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, Error>
+{
+    Ok(to_json(value)?.to_source())
+}
+
+impl Serialize for Json
+{
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        use ::serde::ser::{ SerializeMap, SerializeSeq };
+
+        match self {
+            Json::Null(_)              => serializer.serialize_unit(),
+            Json::Boolean(value, _)    => serializer.serialize_bool(*value),
+            Json::Number(value, _)     => value.serialize(serializer),
+            Json::String(value, _)     => serializer.serialize_str(value),
+            Json::Array(elements, _)   => {
+                let mut seq = serializer.serialize_seq(Some(elements.len()))?;
+                for element in elements {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            },
+            Json::Object(members, _) => {
+                let mut keys: Vec<&String> = members.keys().collect();
+                keys.sort();
+
+                let mut map = serializer.serialize_map(Some(members.len()))?;
+                for key in keys {
+                    map.serialize_entry(key, &members[key])?;
+                }
+                map.end()
+            },
+        }
+    }
+}
+
+impl Serialize for Number
+{
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        match self {
+            Number::Unsigned(value) => serializer.serialize_u64(*value),
+            Number::Integer(value)  => serializer.serialize_i64(*value),
+            Number::Float(value)    => serializer.serialize_f64(*value),
+        }
+    }
+}
+
+/// A serializer producing `Json` values.
+pub struct Serializer;
+
+// Wraps `value` as `{"variant":value}`, the external enum representation.
+fn tagged(variant: &'static str, value: Json) -> Json
+{
+    object(vec![(variant.to_string(), value)])
+}
+
+impl ser::Serializer for Serializer
+{
+    type Ok    = Json;
+    type Error = Error;
+
+    type SerializeSeq           = SerializeArray;
+    type SerializeTuple         = SerializeArray;
+    type SerializeTupleStruct   = SerializeArray;
+    type SerializeTupleVariant  = SerializeArray;
+    type SerializeMap           = SerializeObject;
+    type SerializeStruct        = SerializeObject;
+    type SerializeStructVariant = SerializeObject;
+
+    fn serialize_bool(self, value: bool) -> Result<Json, Error>
+    {
+        Ok(Json::from(value))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Json, Error>
+    {
+        self.serialize_i64(i64::from(value))
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<Json, Error>
+    {
+        self.serialize_i64(i64::from(value))
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<Json, Error>
+    {
+        self.serialize_i64(i64::from(value))
+    }
+
+    // Like the parser, non-negative integers are kept as unsigned.
+    fn serialize_i64(self, value: i64) -> Result<Json, Error>
+    {
+        match value {
+            value if value >= 0 => Ok(Json::from(value as u64)),
+            value => Ok(Json::from(value)),
+        }
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Json, Error>
+    {
+        self.serialize_u64(u64::from(value))
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Json, Error>
+    {
+        self.serialize_u64(u64::from(value))
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Json, Error>
+    {
+        self.serialize_u64(u64::from(value))
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Json, Error>
+    {
+        Ok(Json::from(value))
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Json, Error>
+    {
+        self.serialize_f64(f64::from(value))
+    }
+
+    // NaN and infinities have no JSON representation, they become null.
+    fn serialize_f64(self, value: f64) -> Result<Json, Error>
+    {
+        if !value.is_finite() {
+            return Ok(Json::from(()));
+        }
+
+        Ok(Json::from(value))
+    }
+
+    fn serialize_char(self, value: char) -> Result<Json, Error>
+    {
+        Ok(Json::from(value.to_string()))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Json, Error>
+    {
+        Ok(Json::from(value))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Json, Error>
+    {
+        Ok(Json::from(value.iter().map(|byte| Json::from(u64::from(*byte))).collect::<Vec<Json>>()))
+    }
+
+    fn serialize_none(self) -> Result<Json, Error>
+    {
+        Ok(Json::from(()))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Json, Error>
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Json, Error>
+    {
+        Ok(Json::from(()))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Json, Error>
+    {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<Json, Error>
+    {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<Json, Error>
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self, _name: &'static str, _index: u32, variant: &'static str, value: &T
+    ) -> Result<Json, Error>
+    {
+        Ok(tagged(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, length: Option<usize>) -> Result<SerializeArray, Error>
+    {
+        Ok(SerializeArray { elements: Vec::with_capacity(length.unwrap_or(0)), variant: None })
+    }
+
+    fn serialize_tuple(self, length: usize) -> Result<SerializeArray, Error>
+    {
+        self.serialize_seq(Some(length))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, length: usize) -> Result<SerializeArray, Error>
+    {
+        self.serialize_seq(Some(length))
+    }
+
+    fn serialize_tuple_variant(
+        self, _name: &'static str, _index: u32, variant: &'static str, length: usize
+    ) -> Result<SerializeArray, Error>
+    {
+        Ok(SerializeArray { elements: Vec::with_capacity(length), variant: Some(variant) })
+    }
+
+    fn serialize_map(self, length: Option<usize>) -> Result<SerializeObject, Error>
+    {
+        Ok(SerializeObject { members: Vec::with_capacity(length.unwrap_or(0)), key: None, variant: None })
+    }
+
+    fn serialize_struct(self, _name: &'static str, length: usize) -> Result<SerializeObject, Error>
+    {
+        self.serialize_map(Some(length))
+    }
+
+    fn serialize_struct_variant(
+        self, _name: &'static str, _index: u32, variant: &'static str, length: usize
+    ) -> Result<SerializeObject, Error>
+    {
+        Ok(SerializeObject { members: Vec::with_capacity(length), key: None, variant: Some(variant) })
+    }
+}
+
+#[doc(hidden)]
+pub struct SerializeArray
+{
+    elements: Vec<Json>,
+    variant:  Option<&'static str>,
+}
+
+impl SerializeArray
+{
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error>
+    {
+        self.elements.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Json, Error>
+    {
+        let array = Json::from(self.elements);
+
+        match self.variant {
+            Some(variant) => Ok(tagged(variant, array)),
+            None          => Ok(array),
+        }
+    }
+}
+
+impl ser::SerializeSeq for SerializeArray
+{
+    type Ok    = Json;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error>
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Json, Error>
+    {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray
+{
+    type Ok    = Json;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error>
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Json, Error>
+    {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray
+{
+    type Ok    = Json;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error>
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Json, Error>
+    {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeArray
+{
+    type Ok    = Json;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error>
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Json, Error>
+    {
+        self.finish()
+    }
+}
+
+#[doc(hidden)]
+pub struct SerializeObject
+{
+    members: Vec<(String, Json)>,
+    key:     Option<String>,
+    variant: Option<&'static str>,
+}
+
+impl SerializeObject
+{
+    fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), Error>
+    {
+        self.members.push((key.to_string(), value.serialize(Serializer)?));
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Json, Error>
+    {
+        let members = object(self.members);
+
+        match self.variant {
+            Some(variant) => Ok(tagged(variant, members)),
+            None          => Ok(members),
+        }
+    }
+}
+
+impl ser::SerializeMap for SerializeObject
+{
+    type Ok    = Json;
+    type Error = Error;
+
+    // Keys must serialize to strings, numbers or booleans, the latter two
+    // are written as their text.
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error>
+    {
+        self.key = Some(match key.serialize(Serializer)? {
+            Json::String(key, _)  => key,
+            Json::Number(key, _)  => key.to_string(),
+            Json::Boolean(key, _) => key.to_string(),
+            _ => return Err(Error::new("map keys must be strings")),
+        });
+
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error>
+    {
+        match self.key.take() {
+            Some(key) => self.field(&key, value),
+            None      => Err(Error::new("map value serialized before its key")),
+        }
+    }
+
+    fn end(self) -> Result<Json, Error>
+    {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject
+{
+    type Ok    = Json;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<Json, Error>
+    {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeObject
+{
+    type Ok    = Json;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<Json, Error>
+    {
+        self.finish()
+    }
+}
@@ -0,0 +1,324 @@
+//! Locations of parsed values in the text they come from.
+//!
+//! `Json::parse_with_spans` returns, along with the value, a table giving
+//! the span of every node and of every object key, keyed by JSON Pointer.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::error::ParseError;
+use crate::json::{ skip_whitespace, Json };
+use crate::parser::string;
+use crate::pointer::JsonPointer;
+
+/// A location in a text. `offset` counts bytes from 0, `line` and `column`
+/// start at 1, and columns count characters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Position
+{
+    pub offset: usize,
+    pub line:   usize,
+    pub column: usize,
+}
+
+/// The text between `start` and `end`, `end` excluded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Span
+{
+    pub start: Position,
+    pub end:   Position,
+}
+
+impl Span
+{
+    pub fn contains(&self, offset: usize) -> bool
+    {
+        self.start.offset <= offset && offset < self.end.offset
+    }
+}
+
+/// Spans of every node of a document.
+///
+/// When an object repeats a key, the spans are those of the last occurrence,
+/// the one kept in the tree.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Spans
+{
+    pub(crate) values: HashMap<JsonPointer, Span>,
+    pub(crate) keys:   HashMap<JsonPointer, Span>,
+}
+
+impl Spans
+{
+    /// Span of the node at `pointer`.
+    pub fn value(&self, pointer: &JsonPointer) -> Option<Span>
+    {
+        self.values.get(pointer).copied()
+    }
+
+    /// Span of the key, quotes included, of the object member at `pointer`.
+    pub fn key(&self, pointer: &JsonPointer) -> Option<Span>
+    {
+        self.keys.get(pointer).copied()
+    }
+
+    /// The innermost node whose value or key covers `offset`.
+    pub fn at(&self, offset: usize) -> Option<JsonPointer>
+    {
+        self.values.iter()
+            .chain(self.keys.iter())
+            .filter(|(_, span)| span.contains(offset))
+            .max_by_key(|(pointer, _)| pointer.len())
+            .map(|(pointer, _)| pointer.clone())
+    }
+
+    /// Every node along with its span, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&JsonPointer, &Span)>
+    {
+        self.values.iter()
+    }
+}
+
+impl Json
+{
+    /// Same as `parse_with_position`, also returning the span of every node.
+    pub fn parse_with_spans(text: &str) -> Result<(Json, Spans), ParseError>
+    {
+        let json = Json::parse_with_position(text)?;
+
+        let mut spans = Spans::default();
+        record(text, Position { offset: 0, line: 1, column: 1 }, &JsonPointer::root(), &mut spans);
+
+        Ok((json, spans))
+    }
+}
+
+// Turns offsets into positions by moving a cursor through the text, which
+// is cheap as long as offsets come roughly in the order of the text.
+pub(crate) struct Cursor<'a>
+{
+    text:   &'a str,
+    origin: Position,
+    // Position of `text[at.offset]`, the offset being within `text`.
+    at:     Position,
+}
+
+impl<'a> Cursor<'a>
+{
+    pub(crate) fn new(text: &'a str) -> Cursor<'a>
+    {
+        Cursor::within(text, Position { offset: 0, line: 1, column: 1 })
+    }
+
+    // For a part of a larger text, starting at `origin`.
+    pub(crate) fn within(text: &'a str, origin: Position) -> Cursor<'a>
+    {
+        Cursor { text, origin, at: Position { offset: 0, ..origin } }
+    }
+
+    pub(crate) fn position(&mut self, offset: usize) -> Position
+    {
+        if offset < self.at.offset {
+            let back  = &self.text[offset..self.at.offset];
+            let lines = back.matches('\n').count();
+
+            // Going back past a line break, the column is counted from the
+            // start of the line.
+            let column = match (lines, self.text[..offset].rfind('\n')) {
+                (0, _)           => self.at.column - back.chars().count(),
+                (_, Some(index)) => 1 + self.text[index + 1..offset].chars().count(),
+                (_, None)        => self.origin.column + self.text[..offset].chars().count(),
+            };
+            self.at = Position { offset, line: self.at.line - lines, column };
+        }
+
+        for chr in self.text[self.at.offset..offset].chars() {
+            match chr {
+                '\n' => {
+                    self.at.line  += 1;
+                    self.at.column = 1;
+                },
+                _ => self.at.column += 1,
+            }
+        }
+        self.at.offset = offset;
+
+        Position { offset: self.origin.offset + offset, ..self.at }
+    }
+
+    pub(crate) fn span(&mut self, start: usize, end: usize) -> Span
+    {
+        Span { start: self.position(start), end: self.position(end) }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Kind {
+    Value,
+    Key,
+    // A value that is missing or couldn't be parsed.
+    Placeholder,
+}
+
+// Spans met while parsing, in the order they end. Those of an object member
+// whose key comes again are taken out, only the last one is in the tree.
+#[derive(Default)]
+pub(crate) struct Recorder
+{
+    entries: Vec<Option<(JsonPointer, Span, Kind)>>,
+}
+
+impl Recorder
+{
+    pub(crate) fn len(&self) -> usize
+    {
+        self.entries.len()
+    }
+
+    pub(crate) fn push(&mut self, pointer: JsonPointer, span: Span, kind: Kind)
+    {
+        self.entries.push(Some((pointer, span, kind)));
+    }
+
+    pub(crate) fn forget(&mut self, range: Range<usize>)
+    {
+        self.entries[range].iter_mut().for_each(|entry| *entry = None);
+    }
+
+    // Adds the spans to `spans`, and returns the placeholders.
+    pub(crate) fn finish(self, spans: &mut Spans) -> Vec<(JsonPointer, Span)>
+    {
+        let mut placeholders = vec![];
+
+        for (pointer, span, kind) in self.entries.into_iter().flatten() {
+            match kind {
+                Kind::Value       => { spans.values.insert(pointer, span); },
+                Kind::Key         => { spans.keys.insert(pointer, span); },
+                Kind::Placeholder => placeholders.push((pointer, span)),
+            }
+        }
+
+        placeholders
+    }
+}
+
+// Adds to `spans` those of the valid value in `text`, the text starting at
+// `origin` and the value sitting at `pointer`.
+pub(crate) fn record(text: &str, origin: Position, pointer: &JsonPointer, spans: &mut Spans)
+{
+    let mut scanner = Scanner {
+        text,
+        position: skip_whitespace(text, 0),
+        cursor:   Cursor::within(text, origin),
+        recorder: Recorder::default(),
+    };
+    scanner.value(pointer);
+    scanner.recorder.finish(spans);
+}
+
+// Walks a valid value once, noting spans as it goes.
+struct Scanner<'a>
+{
+    text:     &'a str,
+    position: usize,
+    cursor:   Cursor<'a>,
+    recorder: Recorder,
+}
+
+impl<'a> Scanner<'a>
+{
+    fn here(&mut self) -> Position
+    {
+        self.cursor.position(self.position)
+    }
+
+    fn next_byte(&mut self) -> u8
+    {
+        self.position = skip_whitespace(self.text, self.position);
+        self.text.as_bytes()[self.position]
+    }
+
+    fn value(&mut self, pointer: &JsonPointer)
+    {
+        let start = self.here();
+
+        match self.next_byte() {
+            b'[' => {
+                self.position += 1;
+                let mut index = 0;
+
+                loop {
+                    match self.next_byte() {
+                        b']' => { self.position += 1; break; },
+                        b',' => { self.position += 1; },
+                        _ => {
+                            self.value(&pointer.child(index.to_string()));
+                            index += 1;
+                        },
+                    }
+                }
+            },
+            b'{' => {
+                self.position += 1;
+                let mut members: HashMap<String, Range<usize>> = HashMap::new();
+
+                loop {
+                    match self.next_byte() {
+                        b'}' => { self.position += 1; break; },
+                        b',' => { self.position += 1; },
+                        _ => {
+                            let first     = self.recorder.len();
+                            let key_start = self.here();
+                            let key       = self.string();
+                            let child     = pointer.child(key.as_str());
+
+                            let span = Span { start: key_start, end: self.here() };
+                            self.recorder.push(child.clone(), span, Kind::Key);
+
+                            // Past the colon.
+                            self.next_byte();
+                            self.position += 1;
+                            self.position = skip_whitespace(self.text, self.position);
+                            self.value(&child);
+
+                            if let Some(previous) = members.insert(key, first..self.recorder.len()) {
+                                self.recorder.forget(previous);
+                            }
+                        },
+                    }
+                }
+            },
+            b'"' => { self.string(); },
+            _ => {
+                let rest = &self.text[self.position..];
+                self.position += rest.find([',', ']', '}', ' ', '\t', '\r', '\n']).unwrap_or(rest.len());
+            },
+        }
+
+        let span = Span { start, end: self.here() };
+        self.recorder.push(pointer.clone(), span, Kind::Value);
+    }
+
+    // Moves past the string at the current position, returning its value.
+    fn string(&mut self) -> String
+    {
+        let start = self.position;
+        let bytes = self.text.as_bytes();
+        self.position += 1;
+
+        while bytes[self.position] != b'"' {
+            self.position += match bytes[self.position] {
+                b'\\' => 2,
+                _     => 1,
+            };
+        }
+        self.position += 1;
+
+        let mut chars = self.text[start..self.position].chars();
+
+        match string(&mut (&mut chars).peekable()) {
+            Ok(Json::String(value, _)) => value,
+            _ => String::new(),
+        }
+    }
+}
@@ -0,0 +1,306 @@
+//! Traversal of a tree with visitors.
+//!
+//! `Visit` and `VisitMut` have a method per kind of node, each defaulting to
+//! the matching `walk_*` function, which visits the children. Override the
+//! methods of interest and call `walk_*` from them to keep going down.
+//! `Fold` takes the tree by value and builds a new one.
+//!
+//! Every method gets the path of the node it's called for. Object members
+//! are visited in the order of the source, then sorted for the members the
+//! source doesn't mention.
+//!
+//! ```
+//! use simple_json::visit::Visit;
+//! use simple_json::{ Json, JsonPointer };
+//!
+//! // Finds the nulls of a document.
+//! struct Nulls(Vec<String>);
+//!
+//! impl Visit for Nulls
+//! {
+//!     fn visit_scalar(&mut self, path: &JsonPointer, json: &Json)
+//!     {
+//!         if json.is_null() {
+//!             self.0.push(path.to_string());
+//!         }
+//!     }
+//! }
+//!
+//! let mut nulls = Nulls(vec![]);
+//! Json::parse("{\"a\": [1, null], \"b\": null}").unwrap().visit(&mut nulls);
+//!
+//! assert_eq!(nulls.0, vec!["/a/1", "/b"]);
+//! ```
+
+use crate::format::ordered;
+use crate::json::{ object, quote, Json };
+use crate::pointer::JsonPointer;
+
+/// Visits a tree by reference.
+pub trait Visit
+{
+    fn visit_value(&mut self, path: &JsonPointer, json: &Json)
+    {
+        walk_value(self, path, json);
+    }
+
+    fn visit_object(&mut self, path: &JsonPointer, json: &Json)
+    {
+        walk_object(self, path, json);
+    }
+
+    /// `path` is the member's, ending with `key`.
+    fn visit_member(&mut self, path: &JsonPointer, key: &str, value: &Json)
+    {
+        walk_member(self, path, key, value);
+    }
+
+    fn visit_array(&mut self, path: &JsonPointer, json: &Json)
+    {
+        walk_array(self, path, json);
+    }
+
+    /// `path` is the element's, ending with `index`.
+    fn visit_element(&mut self, path: &JsonPointer, index: usize, value: &Json)
+    {
+        walk_element(self, path, index, value);
+    }
+
+    /// Strings, numbers, booleans and nulls.
+    fn visit_scalar(&mut self, _path: &JsonPointer, _json: &Json)
+    {
+    }
+}
+
+pub fn walk_value<V: Visit + ?Sized>(visitor: &mut V, path: &JsonPointer, json: &Json)
+{
+    match json {
+        Json::Object(_, _) => visitor.visit_object(path, json),
+        Json::Array(_, _)  => visitor.visit_array(path, json),
+        _                  => visitor.visit_scalar(path, json),
+    }
+}
+
+pub fn walk_object<V: Visit + ?Sized>(visitor: &mut V, path: &JsonPointer, json: &Json)
+{
+    if let Json::Object(members, source) = json {
+        for (key, value) in ordered(members, source, false) {
+            visitor.visit_member(&path.child(key.as_str()), key, value);
+        }
+    }
+}
+
+pub fn walk_member<V: Visit + ?Sized>(visitor: &mut V, path: &JsonPointer, _key: &str, value: &Json)
+{
+    visitor.visit_value(path, value);
+}
+
+pub fn walk_array<V: Visit + ?Sized>(visitor: &mut V, path: &JsonPointer, json: &Json)
+{
+    if let Json::Array(elements, _) = json {
+        for (index, element) in elements.iter().enumerate() {
+            visitor.visit_element(&path.child(index.to_string()), index, element);
+        }
+    }
+}
+
+pub fn walk_element<V: Visit + ?Sized>(visitor: &mut V, path: &JsonPointer, _index: usize, value: &Json)
+{
+    visitor.visit_value(path, value);
+}
+
+/// Visits a tree by mutable reference.
+///
+/// Once their children are visited, the sources of arrays and objects are
+/// rebuilt, without whitespace, as `JsonPointer::set` does.
+pub trait VisitMut
+{
+    fn visit_value_mut(&mut self, path: &JsonPointer, json: &mut Json)
+    {
+        walk_value_mut(self, path, json);
+    }
+
+    fn visit_object_mut(&mut self, path: &JsonPointer, json: &mut Json)
+    {
+        walk_object_mut(self, path, json);
+    }
+
+    fn visit_member_mut(&mut self, path: &JsonPointer, key: &str, value: &mut Json)
+    {
+        walk_member_mut(self, path, key, value);
+    }
+
+    fn visit_array_mut(&mut self, path: &JsonPointer, json: &mut Json)
+    {
+        walk_array_mut(self, path, json);
+    }
+
+    fn visit_element_mut(&mut self, path: &JsonPointer, index: usize, value: &mut Json)
+    {
+        walk_element_mut(self, path, index, value);
+    }
+
+    fn visit_scalar_mut(&mut self, _path: &JsonPointer, _json: &mut Json)
+    {
+    }
+}
+
+pub fn walk_value_mut<V: VisitMut + ?Sized>(visitor: &mut V, path: &JsonPointer, json: &mut Json)
+{
+    match json {
+        Json::Object(_, _) => visitor.visit_object_mut(path, json),
+        Json::Array(_, _)  => visitor.visit_array_mut(path, json),
+        _                  => visitor.visit_scalar_mut(path, json),
+    }
+}
+
+pub fn walk_object_mut<V: VisitMut + ?Sized>(visitor: &mut V, path: &JsonPointer, json: &mut Json)
+{
+    if let Json::Object(members, source) = json {
+        let keys: Vec<String> = ordered(members, source, false).into_iter().map(|(key, _)| key.clone()).collect();
+
+        for key in &keys {
+            if let Some(value) = members.get_mut(key) {
+                visitor.visit_member_mut(&path.child(key.as_str()), key, value);
+            }
+        }
+
+        let members = keys.iter()
+            .filter_map(|key| members.get(key).map(|value| format!("{}:{}", quote(key), value.to_source())))
+            .collect::<Vec<_>>();
+        *source = format!("{{{}}}", members.join(","));
+    }
+}
+
+pub fn walk_member_mut<V: VisitMut + ?Sized>(visitor: &mut V, path: &JsonPointer, _key: &str, value: &mut Json)
+{
+    visitor.visit_value_mut(path, value);
+}
+
+pub fn walk_array_mut<V: VisitMut + ?Sized>(visitor: &mut V, path: &JsonPointer, json: &mut Json)
+{
+    if let Json::Array(elements, _) = json {
+        for (index, element) in elements.iter_mut().enumerate() {
+            visitor.visit_element_mut(&path.child(index.to_string()), index, element);
+        }
+    }
+
+    json.refresh_source();
+}
+
+pub fn walk_element_mut<V: VisitMut + ?Sized>(visitor: &mut V, path: &JsonPointer, _index: usize, value: &mut Json)
+{
+    visitor.visit_value_mut(path, value);
+}
+
+/// Rebuilds a tree, taking it by value.
+///
+/// Members and elements can be renamed or dropped on the way, the rebuilt
+/// arrays and objects get a source without whitespace.
+pub trait Fold
+{
+    fn fold_value(&mut self, path: &JsonPointer, json: Json) -> Json
+    {
+        fold_value(self, path, json)
+    }
+
+    fn fold_object(&mut self, path: &JsonPointer, json: Json) -> Json
+    {
+        fold_object(self, path, json)
+    }
+
+    /// Returns the member to put in the new object, if any.
+    fn fold_member(&mut self, path: &JsonPointer, key: String, value: Json) -> Option<(String, Json)>
+    {
+        fold_member(self, path, key, value)
+    }
+
+    fn fold_array(&mut self, path: &JsonPointer, json: Json) -> Json
+    {
+        fold_array(self, path, json)
+    }
+
+    /// Returns the element to put in the new array, if any.
+    fn fold_element(&mut self, path: &JsonPointer, index: usize, value: Json) -> Option<Json>
+    {
+        fold_element(self, path, index, value)
+    }
+
+    fn fold_scalar(&mut self, _path: &JsonPointer, json: Json) -> Json
+    {
+        json
+    }
+}
+
+pub fn fold_value<F: Fold + ?Sized>(folder: &mut F, path: &JsonPointer, json: Json) -> Json
+{
+    match json {
+        Json::Object(_, _) => folder.fold_object(path, json),
+        Json::Array(_, _)  => folder.fold_array(path, json),
+        _                  => folder.fold_scalar(path, json),
+    }
+}
+
+pub fn fold_object<F: Fold + ?Sized>(folder: &mut F, path: &JsonPointer, json: Json) -> Json
+{
+    let (mut members, source) = match json {
+        Json::Object(members, source) => (members, source),
+        json => return json,
+    };
+
+    let keys: Vec<String> = ordered(&members, &source, false).into_iter().map(|(key, _)| key.clone()).collect();
+
+    let folded = keys.into_iter()
+        .filter_map(|key| {
+            let value = members.remove(&key)?;
+            folder.fold_member(&path.child(key.as_str()), key, value)
+        })
+        .collect();
+
+    object(folded)
+}
+
+pub fn fold_member<F: Fold + ?Sized>(folder: &mut F, path: &JsonPointer, key: String, value: Json) -> Option<(String, Json)>
+{
+    Some((key, folder.fold_value(path, value)))
+}
+
+pub fn fold_array<F: Fold + ?Sized>(folder: &mut F, path: &JsonPointer, json: Json) -> Json
+{
+    let elements = match json {
+        Json::Array(elements, _) => elements,
+        json => return json,
+    };
+
+    let folded: Vec<Json> = elements.into_iter()
+        .enumerate()
+        .filter_map(|(index, element)| folder.fold_element(&path.child(index.to_string()), index, element))
+        .collect();
+
+    Json::from(folded)
+}
+
+pub fn fold_element<F: Fold + ?Sized>(folder: &mut F, path: &JsonPointer, _index: usize, value: Json) -> Option<Json>
+{
+    Some(folder.fold_value(path, value))
+}
+
+impl Json
+{
+    /// Runs a visitor over the tree, starting at the root.
+    pub fn visit<V: Visit + ?Sized>(&self, visitor: &mut V)
+    {
+        visitor.visit_value(&JsonPointer::root(), self);
+    }
+
+    pub fn visit_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V)
+    {
+        visitor.visit_value_mut(&JsonPointer::root(), self);
+    }
+
+    /// Rebuilds the tree with a folder.
+    pub fn fold<F: Fold + ?Sized>(self, folder: &mut F) -> Json
+    {
+        folder.fold_value(&JsonPointer::root(), self)
+    }
+}
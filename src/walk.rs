@@ -0,0 +1,227 @@
+//! Depth-first iteration over every node of a tree, along with its path.
+//!
+//! The traversals keep their own stack instead of recursing, so the depth of
+//! a tree doesn't matter. Object members come in the order of the source,
+//! then sorted for the members the source doesn't mention. The order is read
+//! from the source of the root, once per traversal.
+
+use std::collections::HashMap;
+use std::vec::IntoIter;
+
+use crate::format::Orders;
+use crate::json::{ object, Json };
+use crate::pointer::JsonPointer;
+
+/// When a node comes relative to its children.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order
+{
+    /// Parents before their children.
+    Pre,
+    /// Children before their parents.
+    Post,
+}
+
+/// Settings for `Json::walk_with`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WalkOptions
+{
+    pub order: Order,
+}
+
+impl Default for WalkOptions
+{
+    fn default() -> WalkOptions
+    {
+        WalkOptions { order: Order::Pre }
+    }
+}
+
+/// Iterator returned by `Json::walk`.
+pub struct Walk<'a>
+{
+    order:  Order,
+    // Nodes still to go, last first, and whether their children were pushed.
+    stack:  Vec<(JsonPointer, &'a Json, bool)>,
+    orders: Orders,
+}
+
+impl<'a> Iterator for Walk<'a>
+{
+    type Item = (JsonPointer, &'a Json);
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        loop {
+            let (pointer, json, expanded) = self.stack.pop()?;
+
+            let children = match expanded {
+                true  => vec![],
+                false => children(json, &pointer, &self.orders),
+            };
+
+            if children.is_empty() {
+                return Some((pointer, json));
+            }
+
+            match self.order {
+                Order::Pre => {
+                    self.stack.extend(children.into_iter().rev().map(|(token, child)| (pointer.child(token), child, false)));
+                    return Some((pointer, json));
+                },
+                Order::Post => {
+                    self.stack.push((pointer.clone(), json, true));
+                    self.stack.extend(children.into_iter().rev().map(|(token, child)| (pointer.child(token), child, false)));
+                },
+            }
+        }
+    }
+}
+
+fn children<'a>(json: &'a Json, pointer: &JsonPointer, orders: &Orders) -> Vec<(String, &'a Json)>
+{
+    match json {
+        Json::Array(elements, _) => elements.iter().enumerate().map(|(index, element)| (index.to_string(), element)).collect(),
+        Json::Object(members, source) => orders.ordered(pointer, members, source).into_iter().map(|(key, value)| (key.clone(), value)).collect(),
+        _ => vec![],
+    }
+}
+
+// A container taken apart by `walk_mut`, rebuilt once its children are done.
+enum Frame
+{
+    Array { pointer: JsonPointer, done: Vec<Json>, pending: IntoIter<Json> },
+    Object { pointer: JsonPointer, done: Vec<(String, Json)>, pending: IntoIter<(String, Json)>, key: String },
+}
+
+impl Frame
+{
+    // Takes a container apart, scalars are given back.
+    fn new(pointer: JsonPointer, json: Json, orders: &Orders) -> Result<Frame, Json>
+    {
+        match json {
+            Json::Array(elements, _) => Ok(Frame::Array { pointer, done: vec![], pending: elements.into_iter() }),
+            Json::Object(members, source) => {
+                let pending = take_ordered(members, &source, &pointer, orders).into_iter();
+                Ok(Frame::Object { pointer, done: vec![], pending, key: String::new() })
+            },
+            json => Err(json),
+        }
+    }
+
+    fn next_child(&mut self) -> Option<(JsonPointer, Json)>
+    {
+        match self {
+            Frame::Array { pointer, done, pending } => pending.next().map(|element| (pointer.child(done.len().to_string()), element)),
+            Frame::Object { pointer, pending, key, .. } => pending.next().map(|(name, value)| {
+                *key = name;
+                (pointer.child(key.as_str()), value)
+            }),
+        }
+    }
+
+    fn finish_child(&mut self, json: Json)
+    {
+        match self {
+            Frame::Array { done, .. }       => done.push(json),
+            Frame::Object { done, key, .. } => done.push((std::mem::take(key), json)),
+        }
+    }
+
+    fn build(self) -> Json
+    {
+        match self {
+            Frame::Array { done, .. }  => Json::from(done),
+            Frame::Object { done, .. } => object(done),
+        }
+    }
+}
+
+impl Json
+{
+    /// Every node of the tree, parents first.
+    pub fn walk(&self) -> Walk<'_>
+    {
+        self.walk_with(&WalkOptions::default())
+    }
+
+    pub fn walk_with(&self, options: &WalkOptions) -> Walk<'_>
+    {
+        Walk { order: options.order, stack: vec![(JsonPointer::root(), self, false)], orders: Orders::new(self) }
+    }
+
+    /// The nodes without children: scalars, and empty arrays and objects.
+    pub fn leaves(&self) -> impl Iterator<Item = (JsonPointer, &Json)>
+    {
+        self.walk().filter(|(_, json)| match json {
+            Json::Array(elements, _) => elements.is_empty(),
+            Json::Object(members, _) => members.is_empty(),
+            _ => true,
+        })
+    }
+
+    /// Calls `f` on every node, parents first. A node can be changed or
+    /// replaced, the walk goes on with its new children.
+    ///
+    /// Every array and object is rebuilt, whether `f` changed it or not, so
+    /// their sources lose the whitespace and repeated keys of the original
+    /// text. Objects already in the tree keep the order of their members,
+    /// members `f` adds to them come last, sorted. The order is read from the
+    /// tree before the walk, so the members of an object that `f` puts in
+    /// place of another, or inside a node it replaced, are in an unspecified
+    /// order.
+    pub fn walk_mut<F>(&mut self, mut f: F)
+        where F: FnMut(&JsonPointer, &mut Json)
+    {
+        let mut stack: Vec<Frame> = vec![];
+        let mut pointer = JsonPointer::root();
+        let mut json    = std::mem::replace(self, Json::Null(String::new()));
+        let orders      = Orders::new(&json);
+
+        loop {
+            f(&pointer, &mut json);
+
+            let mut finished = match Frame::new(pointer, json, &orders) {
+                Ok(frame) => {
+                    stack.push(frame);
+                    None
+                },
+                Err(scalar) => Some(scalar),
+            };
+
+            // Goes back up until a container has a child left to visit.
+            loop {
+                let frame = match stack.last_mut() {
+                    Some(frame) => frame,
+                    None => {
+                        *self = finished.unwrap();
+                        return;
+                    },
+                };
+
+                if let Some(child) = finished.take() {
+                    frame.finish_child(child);
+                }
+
+                match frame.next_child() {
+                    Some((child_pointer, child)) => {
+                        pointer = child_pointer;
+                        json    = child;
+                        break;
+                    },
+                    None => finished = stack.pop().map(Frame::build),
+                }
+            }
+        }
+    }
+}
+
+// Members in the order `walk` gives them.
+fn take_ordered(mut members: HashMap<String, Json>, source: &str, pointer: &JsonPointer, orders: &Orders) -> Vec<(String, Json)>
+{
+    let keys: Vec<String> = orders.ordered(pointer, &members, source).into_iter().map(|(key, _)| key.clone()).collect();
+
+    keys.into_iter()
+        .filter_map(|key| members.remove(&key).map(|value| (key, value)))
+        .collect()
+}
@@ -0,0 +1,94 @@
+extern crate simple_json;
+use simple_json::{ Json, Number };
+
+// Appendix B of RFC 8785, as IEEE 754 bit patterns.
+#[test]
+fn rfc_numbers()
+{
+    let vectors = [
+        (0x0000000000000000, "0"),
+        (0x8000000000000000, "0"),
+        (0x0000000000000001, "5e-324"),
+        (0x8000000000000001, "-5e-324"),
+        (0x7fefffffffffffff, "1.7976931348623157e+308"),
+        (0xffefffffffffffff, "-1.7976931348623157e+308"),
+        (0x4340000000000000, "9007199254740992"),
+        (0xc340000000000000, "-9007199254740992"),
+        (0x4430000000000000, "295147905179352830000"),
+        (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+        (0x44b52d02c7e14af6, "1e+23"),
+        (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+        (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+        (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+        (0x444b1ae4d6e2ef50, "1e+21"),
+        (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+        (0x3eb0c6f7a0b5ed8d, "0.000001"),
+        (0x41b3de4355555553, "333333333.3333332"),
+        (0x41b3de4355555554, "333333333.33333325"),
+        (0x41b3de4355555555, "333333333.3333333"),
+        (0x41b3de4355555556, "333333333.3333334"),
+        (0x41b3de4355555557, "333333333.33333343"),
+        (0xbecbf647612f3696, "-0.0000033333333333333333"),
+        (0x43143ff3c1cb0959, "1424953923781206.2"),
+    ];
+
+    for (bits, expected) in vectors.iter() {
+        assert_eq!(Number::Float(f64::from_bits(*bits)).to_ecmascript_string(), *expected, "{:016x}", bits);
+    }
+
+    assert_eq!(Number::Unsigned(100).to_ecmascript_string(), "100");
+    assert_eq!(Number::Integer(-7).to_ecmascript_string(), "-7");
+    assert_eq!(Number::Float(f64::NAN).to_ecmascript_string(), "NaN");
+}
+
+// Section 3.2.4 of RFC 8785.
+#[test]
+fn rfc_example()
+{
+    let json = Json::parse(r#"{
+  "numbers": [333333333.33333329, 1E30, 4.50,
+              2e-3, 0.000000000000000000000000001],
+  "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+  "literals": [null, true, false]
+}"#).unwrap();
+
+    assert_eq!(
+        json.canonical(),
+        "{\"literals\":[null,true,false],\"numbers\":[333333333.3333333,1e+30,4.5,0.002,1e-27],\"string\":\"\u{20ac}$\\u000f\\nA'B\\\"\\\\\\\\\\\"/\"}"
+    );
+}
+
+// Section 3.2.3 of RFC 8785, keys are sorted by UTF-16 code units.
+#[test]
+fn rfc_sorting()
+{
+    let json = Json::parse(r#"{
+  "\u20ac": "Euro Sign",
+  "\r": "Carriage Return",
+  "\ufb33": "Hebrew Letter Dalet With Dagesh",
+  "1": "One",
+  "\ud83d\ude00": "Emoji: Grinning Face",
+  "\u0080": "Control",
+  "\u00f6": "Latin Small Letter O With Diaeresis"
+}"#).unwrap();
+
+    assert_eq!(json.canonical(), "{\
+        \"\\r\":\"Carriage Return\",\
+        \"1\":\"One\",\
+        \"\u{0080}\":\"Control\",\
+        \"\u{00f6}\":\"Latin Small Letter O With Diaeresis\",\
+        \"\u{20ac}\":\"Euro Sign\",\
+        \"\u{1f600}\":\"Emoji: Grinning Face\",\
+        \"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"\
+    }");
+}
+
+#[test]
+fn surrogates()
+{
+    assert_eq!(Json::parse(r#""\ud83d\ude00""#).unwrap().as_str(), Some("\u{1f600}"));
+    assert_eq!(Json::parse(r#""\u00410""#).unwrap().as_str(), Some("A0"));
+    assert!(Json::parse(r#""\ud83d""#).is_err());
+    assert!(Json::parse(r#""\ude00""#).is_err());
+    assert!(Json::parse(r#""\ud83d\n""#).is_err());
+}
@@ -0,0 +1,162 @@
+#![cfg(feature = "cli")]
+
+use std::fs;
+use std::io::{ ErrorKind, Write };
+use std::path::PathBuf;
+use std::process::{ Command, Stdio };
+
+// Runs the binary, returning its exit code and standard output.
+fn run(args: &[&str], stdin: &str) -> (i32, String)
+{
+    let mut child = Command::new(env!("CARGO_BIN_EXE_simple-json"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // Commands that don't read their input may be gone already.
+    if let Err(error) = child.stdin.take().unwrap().write_all(stdin.as_bytes()) {
+        assert_eq!(error.kind(), ErrorKind::BrokenPipe);
+    }
+    let output = child.wait_with_output().unwrap();
+
+    (output.status.code().unwrap(), String::from_utf8(output.stdout).unwrap())
+}
+
+// Directory of the files of a test, removed with everything in it on drop.
+struct TempDir(PathBuf);
+
+impl TempDir
+{
+    fn new(test: &str) -> TempDir
+    {
+        let path = std::env::temp_dir().join(format!("simple-json-cli-{}-{}", std::process::id(), test));
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    fn file(&self, name: &str, contents: &str) -> PathBuf
+    {
+        let path = self.0.join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir
+{
+    fn drop(&mut self)
+    {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn fmt()
+{
+    let dir = TempDir::new("fmt");
+    assert_eq!(run(&["fmt"], "{\"a\":[1,2]}"), (0, "{\n  \"a\": [\n    1,\n    2\n  ]\n}\n".to_string()));
+    assert_eq!(run(&["fmt", "--compact"], "{ \"b\" : 1, \"a\" : [ ] }"), (0, "{\"b\":1,\"a\":[]}\n".to_string()));
+    assert_eq!(run(&["fmt", "--indent", "1", "--sort-keys"], "{\"b\":1,\"a\":2}"), (0, "{\n \"a\": 2,\n \"b\": 1\n}\n".to_string()));
+
+    let path = dir.file("fmt.json", "[ 1 ]");
+    assert_eq!(run(&["fmt", "-i", "--compact", path.to_str().unwrap()], ""), (0, String::new()));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "[1]\n");
+
+    assert_eq!(run(&["fmt", "-i"], "1").0, 2);
+    assert_eq!(run(&["fmt"], "[1,").0, 4);
+}
+
+#[test]
+fn check()
+{
+    let dir = TempDir::new("check");
+    let valid   = dir.file("valid.json", "{\"a\": [1, 5]}");
+    let invalid = dir.file("invalid.json", "{\n  \"a\": [1, ]\n}");
+    let schema  = dir.file("schema.json", "{\"properties\": {\"a\": {\"items\": {\"maximum\": 3}}}}");
+
+    assert_eq!(run(&["check", valid.to_str().unwrap()], ""), (0, String::new()));
+
+    let (code, output) = run(&["check", valid.to_str().unwrap(), invalid.to_str().unwrap()], "");
+    assert_eq!(code, 4);
+    assert_eq!(output, format!("{}:2:12: invalid character `]`\n", invalid.display()));
+
+    let (code, output) = run(&["check", "--schema", schema.to_str().unwrap(), valid.to_str().unwrap()], "");
+    assert_eq!(code, 5);
+    assert_eq!(output, format!("{}:1:11: 5 must be less than or equal to 3 (/properties/a/items/maximum)\n", valid.display()));
+
+    let blank = dir.file("blank.json", "\n\n\n{\"a\": [1, 5]}");
+    let (code, output) = run(&["check", "--schema", schema.to_str().unwrap(), blank.to_str().unwrap()], "");
+    assert_eq!(code, 5);
+    assert_eq!(output, format!("{}:4:11: 5 must be less than or equal to 3 (/properties/a/items/maximum)\n", blank.display()));
+}
+
+#[test]
+fn get()
+{
+    assert_eq!(run(&["get", "/a/1"], "{\"a\": [1, {\"b\": true}]}"), (0, "{\"b\": true}\n".to_string()));
+    assert_eq!(run(&["get", "/b"], "{\"a\": 1}").0, 6);
+    assert_eq!(run(&["get", "a"], "{\"a\": 1}").0, 2);
+    assert_eq!(run(&["get", "/a", "/does/not/exist.json"], "").0, 3);
+}
+
+#[test]
+fn set()
+{
+    let dir = TempDir::new("set");
+    assert_eq!(run(&["set", "/a/0", "\"x\""], "{ \"a\": [ 1, 2 ] }\n"), (0, "{ \"a\": [ \"x\", 2 ] }\n".to_string()));
+    assert_eq!(run(&["set", "/b", "null"], "{\n  \"a\": 1\n}\n"), (0, "{\n  \"a\": 1,\n  \"b\": null\n}\n".to_string()));
+    assert_eq!(run(&["set", "/a/b", "1"], "{}").0, 6);
+    assert_eq!(run(&["set", "/a", "nope"], "{}").0, 2);
+
+    // Negative numbers are values, anything after -- is an operand.
+    assert_eq!(run(&["set", "/a", "-1"], "{\"a\": 0}"), (0, "{\"a\": -1}".to_string()));
+    assert_eq!(run(&["set", "--", "/a", "-1.5e3"], "{\"a\": 0}"), (0, "{\"a\": -1.5e3}".to_string()));
+    assert_eq!(run(&["set", "/a", "-x"], "{}").0, 2);
+    assert_eq!(run(&["get", "/a", "--", "-does-not-exist.json"], "").0, 3);
+
+    let path = dir.file("set.json", "[1, 2]\n");
+    assert_eq!(run(&["set", "-i", "/-", "3", path.to_str().unwrap()], ""), (0, String::new()));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "[1, 2, 3]\n");
+}
+
+#[test]
+fn diff()
+{
+    let dir = TempDir::new("diff");
+    let before = dir.file("before.json", "{\"a\": 1, \"b\": [1]}");
+    let after  = dir.file("after.json", "{\"a\": 2, \"b\": [1]}");
+
+    let (code, output) = run(&["diff", before.to_str().unwrap(), after.to_str().unwrap()], "");
+    assert_eq!(code, 1);
+    assert_eq!(output, "@@ /a @@\n- 1\n+ 2\n");
+
+    assert_eq!(run(&["diff", before.to_str().unwrap(), "-"], "{\"b\":[1],\"a\":1}"), (0, String::new()));
+    assert_eq!(run(&["diff", before.to_str().unwrap()], "").0, 2);
+}
+
+#[test]
+fn config()
+{
+    let dir = TempDir::new("config");
+    let defaults = dir.file("defaults.json", "{\n  \"db\": {\"host\": \"localhost\", \"port\": 5432}\n}\n");
+    let local    = dir.file("local.json", "{\n  \"db\": {\n    \"port\": 6432\n  }\n}\n");
+    let files    = [defaults.to_str().unwrap(), local.to_str().unwrap()];
+
+    let (code, output) = run(&["config", "explain", "/db/port", files[0], files[1]], "");
+    assert_eq!(code, 0);
+    assert_eq!(output, format!("6432\nfrom {}:3:13\n", local.display()));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_simple-json"))
+        .args(["config", "explain", "--env", "SIMPLE_JSON_TEST", "/db/host", files[0], files[1]])
+        .env("SIMPLE_JSON_TEST__DB__HOST", "db.internal")
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "\"db.internal\"\nfrom environment variable SIMPLE_JSON_TEST__DB__HOST\n");
+
+    assert_eq!(run(&["config", files[0]], "").1, "{\n  \"db\": {\n    \"host\": \"localhost\",\n    \"port\": 5432\n  }\n}\n");
+    assert_eq!(run(&["config", "explain", "/db/user", files[0]], "").0, 6);
+    assert_eq!(run(&["config", "explain"], "").0, 2);
+}
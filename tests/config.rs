@@ -0,0 +1,153 @@
+extern crate simple_json;
+
+use std::fs;
+use std::path::{ Path, PathBuf };
+
+use simple_json::config::{ Config, ConfigError, ConfigOptions, Provenance };
+use simple_json::merge::{ Conflict, MergeOptions };
+use simple_json::{ Error, Json, JsonPointer };
+
+// Directory of the files of a test, removed with everything in it on drop.
+struct TempDir(PathBuf);
+
+impl TempDir
+{
+    fn new(test: &str) -> TempDir
+    {
+        let path = std::env::temp_dir().join(format!("simple-json-config-{}-{}", std::process::id(), test));
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    fn file(&self, name: &str, contents: &str) -> PathBuf
+    {
+        let path = self.0.join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn path(&self) -> &Path
+    {
+        &self.0
+    }
+}
+
+impl Drop for TempDir
+{
+    fn drop(&mut self)
+    {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)>
+{
+    vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+}
+
+fn explain(config: &Config, pointer: &str) -> Option<String>
+{
+    config.explain(&JsonPointer::parse(pointer).unwrap()).map(Provenance::to_string)
+}
+
+#[test]
+fn files()
+{
+    let dir = TempDir::new("files");
+    let defaults = dir.file("defaults.json", "{\n  \"db\": {\n    \"host\": \"localhost\",\n    \"port\": 5432\n  },\n  \"debug\": false\n}\n");
+    let local    = dir.file("local.json", "{\"db\": {\"port\": 6432}}");
+
+    let config = Config::load_with(&[&defaults, &local], vec![], &ConfigOptions::default()).unwrap();
+
+    assert!(config.value.semantic_eq(&Json::parse("{\"db\": {\"host\": \"localhost\", \"port\": 6432}, \"debug\": false}").unwrap()));
+    assert_eq!(explain(&config, "/db/host"), Some(format!("{}:3:13", defaults.display())));
+    assert_eq!(explain(&config, "/db/port"), Some(format!("{}:1:17", local.display())));
+    assert_eq!(explain(&config, "/db"), Some(format!("{}:1:8", local.display())));
+    assert_eq!(explain(&config, "/user"), None);
+
+    match config.explain(&JsonPointer::parse("/debug").unwrap()) {
+        Some(Provenance::File { path, pointer, span }) => {
+            assert_eq!((path, pointer.to_string(), span.start.line, span.end.column), (&defaults, "/debug".to_string(), 6, 17));
+        },
+        other => panic!("unexpected provenance {:?}", other),
+    }
+}
+
+#[test]
+fn environment()
+{
+    let dir = TempDir::new("environment");
+    let defaults = dir.file("env.json", "{\"db\": {\"host\": \"localhost\", \"replicas\": [\"a\", \"b\"]}}");
+
+    let config = Config::load_with(&[&defaults], vars(&[
+        ("APP__DB__HOST", "db.internal"),
+        ("APP__DB__REPLICAS__1", "\"c\""),
+        ("APP__CACHE__TTL", "60"),
+        ("APP__LOG", "{\"level\": \"debug\"}"),
+        ("OTHER__DB__HOST", "ignored"),
+    ]), &ConfigOptions::default()).unwrap();
+
+    let expected = "{\"db\": {\"host\": \"db.internal\", \"replicas\": [\"a\", \"c\"]}, \"cache\": {\"ttl\": 60}, \"log\": {\"level\": \"debug\"}}";
+    assert!(config.value.semantic_eq(&Json::parse(expected).unwrap()));
+
+    assert_eq!(explain(&config, "/db/host"), Some("environment variable APP__DB__HOST".to_string()));
+    assert_eq!(explain(&config, "/db/replicas/0"), Some(format!("{}:1:43", defaults.display())));
+    assert_eq!(explain(&config, "/db/replicas/1"), Some("environment variable APP__DB__REPLICAS__1".to_string()));
+    assert_eq!(explain(&config, "/cache"), Some("environment variable APP__CACHE__TTL".to_string()));
+    assert_eq!(explain(&config, "/log/level"), Some("environment variable APP__LOG".to_string()));
+
+    // Deeper variables override shallower ones.
+    let options = ConfigOptions { prefix: "MY".to_string(), separator: "_".to_string(), ..ConfigOptions::default() };
+    let config  = Config::load_with::<PathBuf, _>(&[], vars(&[("MY_A_B", "2"), ("MY_A", "{\"b\": 1, \"c\": 3}")]), &options).unwrap();
+
+    assert!(config.value.semantic_eq(&Json::parse("{\"a\": {\"b\": 2, \"c\": 3}}").unwrap()));
+    assert_eq!(explain(&config, "/a/b"), Some("environment variable MY_A_B".to_string()));
+    assert_eq!(explain(&config, "/a/c"), Some("environment variable MY_A".to_string()));
+    assert_eq!(explain(&config, ""), None);
+
+    // Scalars in the way are replaced, strings that aren't JSON are escaped.
+    let config = Config::load_with(&[&defaults], vars(&[("APP__DB__HOST__NAME", "a \"b\" \\ c")]), &ConfigOptions::default()).unwrap();
+    assert_eq!(config.value.pointer("/db/host/name").unwrap().as_str(), Some("a \"b\" \\ c"));
+    assert!(Json::parse(&config.value.to_source()).unwrap().semantic_eq(&config.value));
+    assert_eq!(explain(&config, "/db/host"), Some("environment variable APP__DB__HOST__NAME".to_string()));
+    assert_eq!(explain(&config, "/db/replicas/1"), Some(format!("{}:1:48", defaults.display())));
+
+    // `-` appends, its provenance is recorded under the index it got.
+    let config = Config::load_with(&[&defaults], vars(&[
+        ("APP__DB__REPLICAS__-", "c"),
+        ("APP__DB__REPLICAS__-__PORT", "1"),
+    ]), &ConfigOptions::default()).unwrap();
+
+    assert!(config.value.pointer("/db/replicas").unwrap().semantic_eq(&Json::parse("[\"a\", \"b\", \"c\", {\"port\": 1}]").unwrap()));
+    assert_eq!(explain(&config, "/db/replicas/2"), Some("environment variable APP__DB__REPLICAS__-".to_string()));
+    assert_eq!(explain(&config, "/db/replicas/3"), Some("environment variable APP__DB__REPLICAS__-__PORT".to_string()));
+    assert_eq!(explain(&config, "/db/replicas/3/port"), Some("environment variable APP__DB__REPLICAS__-__PORT".to_string()));
+    assert_eq!(explain(&config, "/db/replicas/-"), None);
+
+    // Anywhere else it is a key like any other.
+    let config = Config::load_with(&[&defaults], vars(&[("APP__DB__-", "1")]), &ConfigOptions::default()).unwrap();
+    assert_eq!(explain(&config, "/db/-"), Some("environment variable APP__DB__-".to_string()));
+}
+
+#[test]
+fn errors()
+{
+    let dir = TempDir::new("errors");
+    let valid   = dir.file("valid.json", "{\"a\": 1}");
+    let invalid = dir.file("invalid.json", "{\"a\": }");
+    let other   = dir.file("other.json", "{\"a\": 2}");
+    let missing = dir.path().join("does-not-exist.json");
+
+    assert!(matches!(Config::load_with(&[&missing], vec![], &ConfigOptions::default()), Err(ConfigError::Io(path, _)) if path == missing));
+    assert!(matches!(Config::load_with(&[&invalid], vec![], &ConfigOptions::default()), Err(ConfigError::Parse(path, _)) if path == invalid));
+
+    let strict = ConfigOptions { merge: MergeOptions { conflicts: Conflict::Error, ..MergeOptions::default() }, ..ConfigOptions::default() };
+    assert!(matches!(Config::load_with(&[&valid, &other], vec![], &strict), Err(ConfigError::Merge(Error::MergeConflict(_)))));
+
+    let env = |name: &str| Config::load_with(&[&valid], vars(&[(name, "1")]), &ConfigOptions::default()).err().map(|error| error.to_string());
+    assert_eq!(env("APP__A____B"), Some("APP__A____B: invalid key APP__A____B".to_string()));
+
+    let list = dir.file("list.json", "{\"a\": [1]}");
+    let error = Config::load_with(&[&list], vars(&[("APP__A__B", "1")]), &ConfigOptions::default()).unwrap_err();
+    assert_eq!(error.to_string(), "APP__A__B: invalid index at /a/b");
+}
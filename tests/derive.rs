@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+extern crate simple_json;
+extern crate simple_json_derive;
+use simple_json::convert::{ ConvertError, FromJson, ToJson };
+use simple_json::{ Json, JsonPointer };
+use simple_json_derive::{ FromJson, ToJson };
+
+fn parse(text: &str) -> Json
+{
+    Json::parse(text).unwrap()
+}
+
+fn round_trip<T: ToJson + FromJson + PartialEq + std::fmt::Debug>(value: T, text: &str)
+{
+    assert_eq!(value.to_json().to_source(), text);
+    assert_eq!(T::from_json(&parse(text)).unwrap(), value);
+}
+
+fn error_at<T: FromJson + std::fmt::Debug>(text: &str) -> (String, String)
+{
+    let ConvertError { path, message } = T::from_json(&parse(text)).unwrap_err();
+    (path.to_string(), message)
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+struct Point
+{
+    x: i32,
+    y: i32,
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+struct Meta
+{
+    version: u8,
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+struct Document
+{
+    #[json(rename = "title-text")]
+    title:   String,
+    points:  Vec<Point>,
+    #[json(default)]
+    tags:    Vec<String>,
+    note:    Option<String>,
+    #[json(skip)]
+    cache:   u32,
+    #[json(flatten)]
+    meta:    Meta,
+}
+
+#[test]
+fn structs()
+{
+    round_trip(Point { x: 1, y: -2 }, "{\"x\":1,\"y\":-2}");
+
+    let document = Document {
+        title:  "a \"quoted\" title".to_string(),
+        points: vec![Point { x: 0, y: 1 }],
+        tags:   vec![],
+        note:   None,
+        cache:  0,
+        meta:   Meta { version: 2 },
+    };
+
+    round_trip(document, concat!(
+        "{\"title-text\":\"a \\\"quoted\\\" title\",\"points\":[{\"x\":0,\"y\":1}],",
+        "\"tags\":[],\"note\":null,\"version\":2}",
+    ));
+}
+
+#[test]
+fn defaults_and_missing_fields()
+{
+    let document = Document::from_json(&parse("{\"title-text\":\"t\",\"points\":[],\"version\":1,\"cache\":9}")).unwrap();
+
+    assert_eq!(document.tags, Vec::<String>::new());
+    assert_eq!(document.note, None);
+    assert_eq!(document.cache, 0);
+    assert_eq!(document.meta, Meta { version: 1 });
+
+    assert_eq!(error_at::<Document>("{\"points\":[],\"version\":1}"), ("".to_string(), "missing field `title-text`".to_string()));
+}
+
+#[test]
+fn errors_have_field_paths()
+{
+    let text = "{\"title-text\":\"t\",\"points\":[{\"x\":0,\"y\":0},{\"x\":1,\"y\":\"2\"}],\"version\":1}";
+    assert_eq!(error_at::<Document>(text), ("/points/1/y".to_string(), "expected integer, found string".to_string()));
+
+    let text = "{\"title-text\":\"t\",\"points\":[],\"version\":300}";
+    assert_eq!(error_at::<Document>(text), ("/version".to_string(), "300 is out of range for u8".to_string()));
+
+    assert_eq!(error_at::<Point>("[1,2]"), ("".to_string(), "expected object, found array".to_string()));
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+struct Meters(f64);
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+struct Pair(u8, String);
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+struct Nothing;
+
+#[test]
+fn tuple_and_unit_structs()
+{
+    round_trip(Meters(1.5), "1.5");
+    round_trip(Pair(1, "a".to_string()), "[1,\"a\"]");
+    round_trip(Nothing, "null");
+
+    assert_eq!(error_at::<Pair>("[1,2]"), ("/1".to_string(), "expected string, found number".to_string()));
+    assert_eq!(error_at::<Pair>("[1]"), ("".to_string(), "expected 2 elements, found 1".to_string()));
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+enum External {
+    Unit,
+    #[json(rename = "new")]
+    Newtype(u32),
+    Tuple(u8, u8),
+    Struct { a: bool },
+}
+
+#[test]
+fn externally_tagged_enums()
+{
+    round_trip(External::Unit, "\"Unit\"");
+    round_trip(External::Newtype(3), "{\"new\":3}");
+    round_trip(External::Tuple(1, 2), "{\"Tuple\":[1,2]}");
+    round_trip(External::Struct { a: true }, "{\"Struct\":{\"a\":true}}");
+
+    assert_eq!(error_at::<External>("\"Other\""), ("".to_string(), "unknown variant `Other`".to_string()));
+    assert_eq!(error_at::<External>("{\"Struct\":{\"a\":1}}"), ("/Struct/a".to_string(), "expected boolean, found number".to_string()));
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(tag = "type")]
+enum Internal {
+    Empty,
+    Circle { radius: f64 },
+    Wrapped(Point),
+}
+
+#[test]
+fn internally_tagged_enums()
+{
+    round_trip(Internal::Empty, "{\"type\":\"Empty\"}");
+    round_trip(Internal::Circle { radius: 2.5 }, "{\"type\":\"Circle\",\"radius\":2.5}");
+    round_trip(Internal::Wrapped(Point { x: 1, y: 2 }), "{\"type\":\"Wrapped\",\"x\":1,\"y\":2}");
+
+    assert_eq!(error_at::<Internal>("{\"radius\":1}"), ("".to_string(), "missing field `type`".to_string()));
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(tag = "t", content = "c")]
+enum Adjacent {
+    Empty,
+    Value(String),
+    Many(u8, u8),
+}
+
+#[test]
+fn adjacently_tagged_enums()
+{
+    round_trip(Adjacent::Empty, "{\"t\":\"Empty\"}");
+    round_trip(Adjacent::Value("x".to_string()), "{\"t\":\"Value\",\"c\":\"x\"}");
+    round_trip(Adjacent::Many(1, 2), "{\"t\":\"Many\",\"c\":[1,2]}");
+
+    assert_eq!(error_at::<Adjacent>("{\"t\":\"Many\",\"c\":[1,-1]}"), ("/c/1".to_string(), "-1 is out of range for u8".to_string()));
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(untagged)]
+enum Untagged {
+    Number(i64),
+    Text(String),
+    Point { x: i32, y: i32 },
+    Missing,
+}
+
+#[test]
+fn untagged_enums()
+{
+    round_trip(Untagged::Number(-4), "-4");
+    round_trip(Untagged::Text("a".to_string()), "\"a\"");
+    round_trip(Untagged::Point { x: 1, y: 2 }, "{\"x\":1,\"y\":2}");
+    round_trip(Untagged::Missing, "null");
+
+    assert_eq!(
+        error_at::<Untagged>("true"),
+        ("".to_string(), "data did not match any variant of untagged enum Untagged".to_string())
+    );
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+struct Wrapper<T>
+{
+    items: HashMap<String, T>,
+}
+
+#[test]
+fn generics_and_maps()
+{
+    let mut items = HashMap::new();
+    items.insert("b".to_string(), 2u64);
+    items.insert("a".to_string(), 1u64);
+
+    round_trip(Wrapper { items }, "{\"items\":{\"a\":1,\"b\":2}}");
+
+    let error = Wrapper::<u64>::from_json(&parse("{\"items\":{\"a\":false}}")).unwrap_err();
+    assert_eq!(error.path, JsonPointer::parse("/items/a").unwrap());
+    assert_eq!(error.to_string(), "expected integer, found boolean at /items/a");
+}
+
+#[test]
+fn json_to()
+{
+    let point: Point = parse("{\"x\":3,\"y\":4}").to().unwrap();
+    assert_eq!(point, Point { x: 3, y: 4 });
+}
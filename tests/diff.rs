@@ -0,0 +1,126 @@
+extern crate simple_json;
+use simple_json::diff::{ diff, diff_with, render, Change, DiffOptions };
+use simple_json::{ Json, JsonPointer };
+
+fn parse(text: &str) -> Json
+{
+    Json::parse(text).unwrap()
+}
+
+fn pointer(text: &str) -> JsonPointer
+{
+    JsonPointer::parse(text).unwrap()
+}
+
+#[test]
+fn equal_documents_have_no_changes()
+{
+    assert_eq!(diff(&parse("{\"a\":[1,2],\"b\":1.0}"), &parse("{\"b\":1,\"a\":[1,2]}")), vec![]);
+}
+
+#[test]
+fn object_members()
+{
+    let changes = diff(&parse("{\"a\":1,\"b\":{\"c\":true},\"d\":null}"), &parse("{\"a\":2,\"b\":{\"c\":true,\"e\":0}}"));
+
+    assert_eq!(changes, vec![
+        Change::Modified { path: pointer("/a"), before: parse("1"), after: parse("2") },
+        Change::Added { path: pointer("/b/e"), value: parse("0") },
+        Change::Removed { path: pointer("/d"), value: Json::Null("null".to_string()) },
+    ]);
+}
+
+#[test]
+fn scalar_root()
+{
+    assert_eq!(diff(&parse("1"), &parse("\"x\"")), vec![
+        Change::Modified { path: JsonPointer::root(), before: parse("1"), after: Json::from("x".to_string()) },
+    ]);
+}
+
+#[test]
+fn array_insert_and_remove()
+{
+    let changes = diff(&parse("[1,2,3]"), &parse("[1,3,4]"));
+
+    assert_eq!(changes, vec![
+        Change::Removed { path: pointer("/1"), value: parse("2") },
+        Change::Added { path: pointer("/2"), value: parse("4") },
+    ]);
+}
+
+#[test]
+fn array_element_modified_in_place()
+{
+    let changes = diff(&parse("[{\"a\":1},2]"), &parse("[{\"a\":5},2]"));
+
+    assert_eq!(changes, vec![
+        Change::Modified { path: pointer("/0/a"), before: parse("1"), after: parse("5") },
+    ]);
+}
+
+#[test]
+fn array_element_moved()
+{
+    let changes = diff(&parse("[\"a\",\"b\",\"c\"]"), &parse("[\"b\",\"c\",\"a\"]"));
+
+    assert_eq!(changes, vec![
+        Change::Moved { from: pointer("/0"), path: pointer("/2"), value: Json::from("a".to_string()) },
+    ]);
+}
+
+#[test]
+fn keyed_arrays()
+{
+    let before = parse("[{\"id\":1,\"v\":\"a\"},{\"id\":2,\"v\":\"b\"},{\"id\":3,\"v\":\"c\"}]");
+    let after  = parse("[{\"id\":2,\"v\":\"b\"},{\"id\":1,\"v\":\"x\"},{\"id\":4,\"v\":\"d\"}]");
+
+    let options = DiffOptions { array_key: Some("id".to_string()) };
+    let changes = diff_with(&before, &after, &options);
+
+    assert_eq!(changes, vec![
+        Change::Removed { path: pointer("/2"), value: parse("{\"id\":3,\"v\":\"c\"}") },
+        Change::Added { path: pointer("/2"), value: parse("{\"id\":4,\"v\":\"d\"}") },
+        Change::Moved { from: pointer("/0"), path: pointer("/1"), value: parse("{\"id\":1,\"v\":\"x\"}") },
+        Change::Modified { path: pointer("/1/v"), before: Json::from("a".to_string()), after: Json::from("x".to_string()) },
+    ]);
+}
+
+#[test]
+fn keyed_arrays_fall_back_to_positions()
+{
+    let options = DiffOptions { array_key: Some("id".to_string()) };
+    let changes = diff_with(&parse("[{\"id\":1},2]"), &parse("[{\"id\":1}]"), &options);
+
+    assert_eq!(changes, vec![Change::Removed { path: pointer("/1"), value: parse("2") }]);
+}
+
+#[test]
+fn render_uses_source()
+{
+    let before = parse("{\"a\":1.50,\"b\":[1,2]}");
+    let after  = parse("{\"a\":2e0,\"b\":[2,1],\"c\":{\"d\":true}}");
+
+    assert_eq!(render(&diff(&before, &after)), concat!(
+        "@@ /a @@\n",
+        "- 1.50\n",
+        "+ 2e0\n",
+        "@@ /b/0 -> /b/1 @@\n",
+        "  1\n",
+        "@@ /c @@\n",
+        "+ {\"d\":true}\n",
+    ));
+
+    // The root and the member with an empty key don't look alike.
+    assert_eq!(render(&diff(&parse("1"), &parse("2"))), "@@ \"\" @@\n- 1\n+ 2\n");
+    assert_eq!(render(&diff(&parse("{\"\":1}"), &parse("{\"\":2}"))), "@@ / @@\n- 1\n+ 2\n");
+}
+
+#[test]
+fn change_path()
+{
+    let changes = parse("{\"a\":[1]}").diff(&parse("{\"a\":[]}"));
+
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].path(), &pointer("/a/0"));
+}
@@ -0,0 +1,54 @@
+extern crate simple_json;
+use simple_json::{ Json, Number, Semantic };
+use simple_json::equality::{ EqOptions, NumberEquivalence };
+
+fn parse(text: &str) -> Json
+{
+    Json::parse(text).unwrap()
+}
+
+#[test]
+fn semantic_eq()
+{
+    assert_ne!(parse("[1,2]"), parse("[1, 2]"));
+    assert!(parse("[1,2]").semantic_eq(&parse("[1, 2]")));
+    assert!(parse("{\"a\":1,\"b\":[true,null]}").semantic_eq(&parse("{ \"b\": [ true, null ], \"a\": 1 }")));
+    assert!(parse("\"\\u0041\"").semantic_eq(&parse("\"A\"")));
+
+    assert!(!parse("[1,2]").semantic_eq(&parse("[2,1]")));
+    assert!(!parse("{\"a\":1}").semantic_eq(&parse("{\"a\":1,\"b\":2}")));
+    assert!(!parse("1").semantic_eq(&parse("\"1\"")));
+    assert!(!parse("null").semantic_eq(&parse("false")));
+}
+
+#[test]
+fn number_equivalence()
+{
+    let unsigned = Json::Number(Number::Unsigned(1), "1".to_string());
+    let integer  = Json::Number(Number::Integer(1), "1".to_string());
+    let float    = Json::Number(Number::Float(1.), "1.0".to_string());
+
+    assert_ne!(unsigned, integer);
+
+    let value = EqOptions::default();
+    assert!(unsigned.semantic_eq_with(&integer, &value));
+    assert!(unsigned.semantic_eq_with(&float, &value));
+    assert!(parse("0").semantic_eq_with(&parse("-0.0"), &value));
+
+    let integers = EqOptions { numbers: NumberEquivalence::Integers };
+    assert!(unsigned.semantic_eq_with(&integer, &integers));
+    assert!(!unsigned.semantic_eq_with(&float, &integers));
+    assert!(parse("1.0").semantic_eq_with(&parse("1e0"), &integers));
+
+    let kind = EqOptions { numbers: NumberEquivalence::Kind };
+    assert!(!unsigned.semantic_eq_with(&integer, &kind));
+    assert!(!unsigned.semantic_eq_with(&float, &kind));
+    assert!(parse("[1, 2.5]").semantic_eq_with(&parse("[1,2.50]"), &kind));
+}
+
+#[test]
+fn semantic_operator()
+{
+    assert_eq!(Semantic(parse("{\"a\":[1, 2]}")), Semantic(parse("{\"a\":[1.0,2]}")));
+    assert_ne!(Semantic(parse("{\"a\":[1, 2]}")), Semantic(parse("{\"a\":[1]}")));
+}
@@ -0,0 +1,140 @@
+extern crate simple_json;
+
+use simple_json::flatten::{ FlattenOptions, Notation };
+use simple_json::{ Error, Json };
+
+fn keys(pairs: &[(String, Json)]) -> Vec<&str>
+{
+    pairs.iter().map(|(key, _)| key.as_str()).collect()
+}
+
+fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, Json)>
+{
+    pairs.iter().map(|(key, value)| (key.to_string(), Json::parse(value).unwrap())).collect()
+}
+
+#[test]
+fn flatten()
+{
+    let json = Json::parse("{\"a\": {\"b\": [1, {\"c\": true}], \"d\": {}}, \"e.f\": \"x\", \"g[0]\": null, \"h\\\\i\": []}").unwrap();
+    let flat = json.flatten();
+
+    assert_eq!(keys(&flat), vec!["a.b[0]", "a.b[1].c", "a.d", "e\\.f", "g\\[0\\]", "h\\\\i"]);
+    assert_eq!(flat[1].1, Json::parse("true").unwrap());
+    assert_eq!(flat[2].1.to_source(), "{}");
+    assert!(Json::unflatten(flat).unwrap().semantic_eq(&json));
+
+    let scalar = Json::parse("\"x\"").unwrap();
+    assert_eq!(scalar.flatten(), pairs(&[("", "\"x\"")]));
+    assert_eq!(Json::unflatten(scalar.flatten()).unwrap(), scalar);
+}
+
+#[test]
+fn separators()
+{
+    let options = FlattenOptions { separator: "__".to_string(), notation: Notation::Separator };
+    let json    = Json::parse("{\"db\": {\"hosts\": [\"a\", \"b\"], \"max__conn\": 5, \"10\": [[0]]}}").unwrap();
+    let flat    = json.flatten_with(&options);
+
+    assert_eq!(keys(&flat), vec!["db__hosts__0", "db__hosts__1", "db__max\\__conn", "db__\\10__0__0"]);
+    assert!(Json::unflatten_with(flat, &options).unwrap().semantic_eq(&json));
+
+    let unflattened = Json::unflatten_with(pairs(&[("a__1", "2"), ("a__0", "1"), ("b___c", "3")]), &options).unwrap();
+    assert!(unflattened.semantic_eq(&Json::parse("{\"a\": [1, 2], \"b\": {\"_c\": 3}}").unwrap()));
+}
+
+#[test]
+fn separators_at_key_edges()
+{
+    let options = FlattenOptions { separator: "__".to_string(), notation: Notation::Separator };
+
+    let json = Json::parse("{\"a_\": {\"b\": 1}}").unwrap();
+    let flat = json.flatten_with(&options);
+    assert_eq!(keys(&flat), vec!["a\\___b"]);
+    assert!(Json::unflatten_with(flat, &options).unwrap().semantic_eq(&json));
+
+    let json = Json::parse("{\"a\": {\"_b\": 1}}").unwrap();
+    let flat = json.flatten_with(&options);
+    assert_eq!(keys(&flat), vec!["a__\\_b"]);
+    assert!(Json::unflatten_with(flat, &options).unwrap().semantic_eq(&json));
+
+    let documents = [
+        "{\"a_\": {\"_b\": 1, \"_\": {\"__\": 2}}, \"_\": [{\"___\": 3}], \"x\": {\"\": {\"_\": 4}}}",
+        "{\"1_\": {\"_2\": [5]}, \"\\\\_\": {\"_\\\\\": 6}}",
+    ];
+
+    for separator in &["__", "_", "._", "aba"] {
+        let options = FlattenOptions { separator: separator.to_string(), notation: Notation::Separator };
+
+        for document in documents.iter().chain(&["{\"ab\": {\"ba\": {\"a\": 1}}, \"aab\": {\"bab\": 2}}"]) {
+            let json = Json::parse(document).unwrap();
+            let flat = json.flatten_with(&options);
+            assert!(Json::unflatten_with(flat.clone(), &options).unwrap().semantic_eq(&json), "{} {:?}", separator, keys(&flat));
+        }
+    }
+}
+
+#[test]
+fn empty_keys()
+{
+    let json = Json::parse("{\"\": {\"a\": 1, \"\": {\"b\": 2}}}").unwrap();
+    let flat = json.flatten();
+
+    assert_eq!(keys(&flat), vec![".a", "..b"]);
+    assert!(Json::unflatten(flat).unwrap().semantic_eq(&json));
+
+    let options = FlattenOptions { separator: "__".to_string(), notation: Notation::Separator };
+    let json    = Json::parse("{\"\": [1]}").unwrap();
+    let flat    = json.flatten_with(&options);
+
+    assert_eq!(keys(&flat), vec!["__0"]);
+    assert!(Json::unflatten_with(flat, &options).unwrap().semantic_eq(&json));
+}
+
+#[test]
+#[should_panic]
+fn empty_separator_flatten()
+{
+    let options = FlattenOptions { separator: String::new(), notation: Notation::Separator };
+    Json::parse("{\"a\": {\"b\": 1}}").unwrap().flatten_with(&options);
+}
+
+#[test]
+#[should_panic]
+fn empty_separator_unflatten()
+{
+    let options = FlattenOptions { separator: String::new(), notation: Notation::Brackets };
+    let _ = Json::unflatten_with(pairs(&[("ab", "1")]), &options);
+}
+
+#[test]
+fn pointers()
+{
+    let options = FlattenOptions { notation: Notation::Pointer, ..FlattenOptions::default() };
+    let json    = Json::parse("{\"a/b\": [1, {\"~\": 2}], \"200\": {\"1\": true}}").unwrap();
+    let flat    = json.flatten_with(&options);
+
+    assert_eq!(keys(&flat), vec!["/a~1b/0", "/a~1b/1/~0", "/200/1"]);
+    assert!(Json::unflatten_with(flat, &options).unwrap().semantic_eq(&json));
+
+    // Objects whose keys are all the indices from 0 come back as arrays.
+    let json = Json::parse("{\"a\": {\"1\": true, \"0\": false}}").unwrap();
+    let unflattened = Json::unflatten_with(json.flatten_with(&options), &options).unwrap();
+    assert!(unflattened.semantic_eq(&Json::parse("{\"a\": [false, true]}").unwrap()));
+}
+
+#[test]
+fn unflatten()
+{
+    assert_eq!(Json::unflatten(vec![]).unwrap().to_source(), "{}");
+    assert_eq!(Json::unflatten(pairs(&[("[1]", "1")])).unwrap().to_source(), "[null,1]");
+    assert_eq!(Json::unflatten(pairs(&[("b", "1"), ("a[0]", "2"), ("b", "3")])).unwrap().to_source(), "{\"b\":3,\"a\":[2]}");
+
+    assert_eq!(Json::unflatten(pairs(&[("a", "1"), ("a.b", "2")])), Err(Error::KeyConflict("a.b".to_string())));
+    assert_eq!(Json::unflatten(pairs(&[("a.b", "2"), ("a", "1")])), Err(Error::KeyConflict("a".to_string())));
+    assert_eq!(Json::unflatten(pairs(&[("a[0]", "1"), ("a.b", "2")])), Err(Error::KeyConflict("a.b".to_string())));
+
+    for key in &["a[x]", "a[01]", "a[0]b", "a\\", "a[0"] {
+        assert_eq!(Json::unflatten(pairs(&[(key, "1")])), Err(Error::InvalidKey(key.to_string())));
+    }
+}
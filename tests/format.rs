@@ -0,0 +1,55 @@
+extern crate simple_json;
+use simple_json::Json;
+use simple_json::format::PrettyOptions;
+
+const DOCUMENT: &str = "{\"name\":\"simple_json\",\"version\":1.0,\"tags\":[\"json\",\"parser\"],\"empty\":{},\"nested\":{\"z\":[],\"a\":null}}";
+
+#[test]
+fn pretty()
+{
+    let json = Json::parse(DOCUMENT).unwrap();
+
+    assert_eq!(json.pretty(), "{
+  \"name\": \"simple_json\",
+  \"version\": 1.0,
+  \"tags\": [
+    \"json\",
+    \"parser\"
+  ],
+  \"empty\": {},
+  \"nested\": {
+    \"z\": [],
+    \"a\": null
+  }
+}");
+}
+
+#[test]
+fn pretty_with()
+{
+    let json    = Json::parse("{\"b\":[1],\"a\":{\"d\":1,\"c\":2}}").unwrap();
+    let options = PrettyOptions { indent: 4, sort_keys: true };
+
+    assert_eq!(json.pretty_with(&options), "{
+    \"a\": {
+        \"c\": 2,
+        \"d\": 1
+    },
+    \"b\": [
+        1
+    ]
+}");
+}
+
+#[test]
+fn compact()
+{
+    let json = Json::parse(&Json::parse(DOCUMENT).unwrap().pretty()).unwrap();
+    assert_eq!(json.compact(), DOCUMENT);
+
+    // Scalars keep their spelling.
+    let json = Json::parse("[ 1e3 , \"\\u0041\" ]").unwrap();
+    assert_eq!(json.compact(), "[1e3,\"\\u0041\"]");
+
+    assert_eq!(Json::parse(" 1 ").unwrap().pretty(), "1");
+}
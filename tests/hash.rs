@@ -0,0 +1,72 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{ Hash, Hasher };
+
+extern crate simple_json;
+use simple_json::{ Json, Number, Semantic };
+
+fn hash<T: Hash>(value: &T) -> u64
+{
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn hash_ignores_formatting()
+{
+    let compact = Json::parse("{\"a\":[1,2],\"b\":{\"c\":null}}").unwrap();
+    let spaced  = Json::parse("{ \"b\" : { \"c\" : null } , \"a\" : [ 1 , 2 ] }").unwrap();
+
+    assert_eq!(hash(&compact), hash(&spaced));
+    assert_ne!(hash(&compact), hash(&Json::parse("{\"a\":[2,1],\"b\":{\"c\":null}}").unwrap()));
+    assert_ne!(hash(&Json::parse("[]").unwrap()), hash(&Json::parse("{}").unwrap()));
+}
+
+#[test]
+fn hash_numbers()
+{
+    assert_eq!(hash(&Number::Unsigned(1)), hash(&Number::Integer(1)));
+    assert_eq!(hash(&Number::Unsigned(1)), hash(&Number::Float(1.)));
+    assert_eq!(hash(&Number::Float(0.)), hash(&Number::Float(-0.)));
+    assert_ne!(hash(&Number::Unsigned(1)), hash(&Number::Unsigned(2)));
+
+    assert_eq!(hash(&Json::parse("[1]").unwrap()), hash(&Json::parse("[1.0e0]").unwrap()));
+}
+
+#[test]
+fn semantic()
+{
+    let documents = ["[1, 2]", "[1,2]", "[1.0,2]", "[2,1]", "{\"a\":1,\"b\":2}", "{\"b\":2,\"a\":1}"];
+
+    let unique: HashSet<Semantic> = documents.iter().map(|text| Semantic(Json::parse(text).unwrap())).collect();
+    assert_eq!(unique.len(), 3);
+
+    assert_eq!(Semantic(Json::from(1)), Semantic(Json::parse("1").unwrap()));
+    assert_ne!(Semantic(Json::from(1)), Semantic(Json::from("1")));
+    assert_eq!(Semantic(Json::from(1)).to_source(), "1");
+
+    // `Eq` is reflexive, NaN included.
+    let nan = Semantic(Json::from(vec![Json::from(f64::NAN)]));
+    assert_eq!(nan, nan.clone());
+    assert_eq!([nan.clone(), nan].iter().cloned().collect::<HashSet<_>>().len(), 1);
+}
+
+#[test]
+fn digest()
+{
+    let json = Json::parse("{ \"b\": [1, 2.0], \"a\": null }").unwrap();
+    assert_eq!(json.digest(), "ee743f2fa2570a1b5e3270cc405d0456b983ba03ab9cb27552fc6c1a720183c7");
+    assert_eq!(Json::parse("{\"a\":null,\"b\":[1e0,2]}").unwrap().digest(), json.digest());
+
+    // Integers past 2^53 are told apart, and match floats of the same value.
+    let digest = |text: &str| Json::parse(text).unwrap().digest();
+    assert_ne!(digest("9007199254740993"), digest("9007199254740992"));
+    assert_eq!(digest("9007199254740992"), digest("9007199254740992.0"));
+    assert_eq!(digest("1152921504606846976"), digest("1.152921504606846976e18"));
+    assert_eq!(digest("-0"), digest("0.0"));
+
+    // Longer than a single block.
+    let json = Json::from("x".repeat(100));
+    assert_eq!(json.digest(), "10a9270a01f7334f95712ee341cefe458d56e59e817f4f15f4e3e4834d4b42a9");
+}
@@ -0,0 +1,144 @@
+extern crate simple_json;
+
+use simple_json::incremental::Edit;
+use simple_json::Json;
+
+// Sources of every node, objects' members sorted by key.
+fn sources(json: &Json, output: &mut Vec<String>)
+{
+    output.push(json.to_source());
+
+    match json {
+        Json::Array(elements, _) => elements.iter().for_each(|element| sources(element, output)),
+        Json::Object(members, _) => {
+            let mut keys: Vec<_> = members.keys().collect();
+            keys.sort();
+            keys.into_iter().for_each(|key| sources(&members[key], output));
+        },
+        _ => {},
+    }
+}
+
+// Applies an edit both ways and checks they agree, returns the new text.
+fn check(text: &str, start: usize, end: usize, replacement: &str) -> String
+{
+    let edited = format!("{}{}{}", &text[..start], replacement, &text[end..]);
+    let edit   = Edit { range: start..end, text: replacement.to_string() };
+
+    let (mut json, mut spans) = Json::parse_with_spans(text).unwrap();
+    let (before, before_spans) = (json.clone(), spans.clone());
+
+    match (json.reparse(&mut spans, &edited, &edit), Json::parse_with_spans(&edited)) {
+        (Ok(()), Ok((expected, expected_spans))) => {
+            let (mut actual, mut wanted) = (vec![], vec![]);
+            sources(&json, &mut actual);
+            sources(&expected, &mut wanted);
+
+            assert_eq!(json, expected, "{:?}", edited);
+            assert_eq!(actual, wanted, "{:?}", edited);
+            assert_eq!(spans, expected_spans, "{:?}", edited);
+        },
+        (Err(error), Err(expected)) => {
+            assert_eq!(error, expected, "{:?}", edited);
+            assert_eq!((json, spans), (before, before_spans));
+        },
+        (result, expected) => panic!("{:?}: {:?} but a full parse gives {:?}", edited, result, expected.map(|_| ())),
+    }
+
+    edited
+}
+
+#[test]
+fn edits_inside_a_container()
+{
+    let text = "{\"a\": [1, 2, 3], \"b\": {\"c\": true}}";
+
+    check(text, 10, 11, "20, 21");
+    check(text, 28, 32, "false, \"d\": null");
+    check(text, 7, 14, "");
+}
+
+#[test]
+fn later_nodes_move()
+{
+    let text = "{\n  \"a\": {\"b\": 1}, \"c\": [2],\n  \"d\": \"é\"\n}";
+
+    // Same line, then more lines.
+    check(text, 16, 16, ", \"é\": [\"ü\"]");
+    check(text, 16, 16, ",\n    \"x\": 1,\n    \"y\": 2\n  ");
+    check(text, 25, 26, "\n    3\n  ");
+}
+
+#[test]
+fn moved_brackets_widen_the_reparse()
+{
+    let text = "{\"a\": [[1], [2]], \"b\": [1]}";
+
+    check(text, 14, 14, "], [3");
+    check(text, 13, 14, "");
+    check(text, 14, 14, "]], \"x\": [[3");
+}
+
+#[test]
+fn duplicate_keys()
+{
+    let text = "{\"a\": [1], \"a\": [2]}";
+
+    check(text, 7, 8, "3");
+    check(text, 18, 19, "4");
+}
+
+#[test]
+fn errors_leave_the_tree()
+{
+    let text = "{\"a\": [1, 2]}";
+
+    check(text, 9, 9, ",");
+    check(text, 12, 13, "");
+    check(text, 0, 0, "[");
+}
+
+#[test]
+fn outside_any_container()
+{
+    check("  [1]  ", 7, 7, "\n");
+    check("[1]", 0, 3, "{}");
+    check("\"text\"", 1, 5, "other");
+}
+
+#[test]
+fn chained_edits()
+{
+    let mut text = "[]".to_string();
+
+    for (index, value) in ["1", ", {}", "\"a\": [true]", ", \"b\": null"].iter().enumerate() {
+        let offset = match index {
+            0 | 1 => text.len() - 1,
+            _     => text.len() - 2,
+        };
+        text = check(&text, offset, offset, value);
+    }
+
+    assert_eq!(text, "[1, {\"a\": [true], \"b\": null}]");
+}
+
+// Every insertion and deletion around a document agrees with a full parse.
+#[test]
+fn every_offset()
+{
+    let text = "{\n  \"a\": [1, {\"b\": null}],\n  \"ç\": {\"d\": [[]], \"e\": \"f\"}\n}";
+    let insertions = ["", "0", ",", "]", "}", "[", "{", "\"", "\"k\": 1,", "\n"];
+
+    let boundaries: Vec<usize> = (0..=text.len()).filter(|offset| text.is_char_boundary(*offset)).collect();
+
+    for start in &boundaries {
+        for insertion in &insertions {
+            check(text, *start, *start, insertion);
+        }
+
+        if let Some(end) = boundaries.iter().find(|end| *end > start) {
+            check(text, *start, *end, "");
+            check(text, *start, *end, "2");
+        }
+    }
+}
@@ -0,0 +1,161 @@
+extern crate simple_json;
+use simple_json::Json;
+use simple_json::jq::{ self, JqError };
+
+fn run(program: &str, input: &str) -> Vec<String>
+{
+    Json::parse(input).unwrap()
+        .jq(program).unwrap()
+        .iter().map(Json::to_source).collect()
+}
+
+#[test]
+fn paths_and_pipes()
+{
+    assert_eq!(run(".", "[1,2]"), vec!["[1,2]"]);
+    assert_eq!(run(".a.b", "{\"a\":{\"b\":3}}"), vec!["3"]);
+    assert_eq!(run(".a | .b", "{\"a\":{\"b\":3}}"), vec!["3"]);
+    assert_eq!(run(".[1]", "[1,2,3]"), vec!["2"]);
+    assert_eq!(run(".[-1]", "[1,2,3]"), vec!["3"]);
+    assert_eq!(run(".[1:]", "[1,2,3]"), vec!["[2,3]"]);
+    assert_eq!(run(".[]", "[1,2,3]"), vec!["1", "2", "3"]);
+    assert_eq!(run(".missing", "{}"), vec!["null"]);
+    assert_eq!(run(".a, .b", "{\"a\":1,\"b\":2}"), vec!["1", "2"]);
+    assert_eq!(run("..", "[[1]]"), vec!["[[1]]", "[1]", "1"]);
+    assert_eq!(run(".[\"a\"]", "{\"a\":1}"), vec!["1"]);
+    assert_eq!(run(".a?", "[1]"), Vec::<String>::new());
+}
+
+#[test]
+fn backlog_example()
+{
+    let input = "{\"items\":[{\"enabled\":true},{\"enabled\":false},{\"enabled\":true}]}";
+
+    assert_eq!(run(".items | map(select(.enabled)) | length", input), vec!["2"]);
+}
+
+#[test]
+fn construction()
+{
+    assert_eq!(run("[.[] | . * 2]", "[1,2,3]"), vec!["[2,4,6]"]);
+    assert_eq!(run("{a: .x, \"b\": 1}", "{\"x\":true}")[0].len(), "{\"a\":true,\"b\":1}".len());
+    assert_eq!(run("{x}", "{\"x\":5}"), vec!["{\"x\":5}"]);
+    assert_eq!(run("{(.k): .v}", "{\"k\":\"key\",\"v\":1}"), vec!["{\"key\":1}"]);
+    assert_eq!(run("[{a: (1, 2)}]", "null"), vec!["[{\"a\":1},{\"a\":2}]"]);
+}
+
+#[test]
+fn arithmetic()
+{
+    assert_eq!(run(". + 1", "1"), vec!["2"]);
+    assert_eq!(run(". - 3", "1"), vec!["-2"]);
+    assert_eq!(run(". / 2", "3"), vec!["1.5"]);
+    assert_eq!(run(". / 2", "4"), vec!["2"]);
+    assert_eq!(run(". % 3", "10"), vec!["1"]);
+    assert_eq!(run("-.", "1"), vec!["-1"]);
+    assert_eq!(run("1 + 2 * 3", "null"), vec!["7"]);
+    assert_eq!(run("(1 + 2) * 3", "null"), vec!["9"]);
+    assert_eq!(run(".a + .b", "{\"a\":\"x\",\"b\":\"y\"}"), vec!["\"xy\""]);
+    assert_eq!(run(". - [2]", "[1,2,3]"), vec!["[1,3]"]);
+    assert_eq!(run("add", "[1,2,3]"), vec!["6"]);
+    assert_eq!(run(". / \",\"", "\"a,b\""), vec!["[\"a\",\"b\"]"]);
+}
+
+#[test]
+fn logic_and_conditionals()
+{
+    assert_eq!(run(". == 1, . != 1, . < 2, . >= 2", "1"), vec!["true", "false", "true", "false"]);
+    assert_eq!(run("true and false, true or false, (null | not)", "null"), vec!["false", "true", "true"]);
+    assert_eq!(run("if . > 2 then \"big\" elif . > 1 then \"medium\" else \"small\" end", "2"), vec!["\"medium\""]);
+    assert_eq!(run(".a // \"default\"", "{}"), vec!["\"default\""]);
+    assert_eq!(run("[.[] | select(. > 1)]", "[1,2,3]"), vec!["[2,3]"]);
+}
+
+#[test]
+fn variables_and_reduce()
+{
+    assert_eq!(run(".a as $x | .b + $x", "{\"a\":1,\"b\":2}"), vec!["3"]);
+    assert_eq!(run("reduce .[] as $n (0; . + $n)", "[1,2,3,4]"), vec!["10"]);
+    assert_eq!(run("[.[] as $x | $x * $x]", "[1,2,3]"), vec!["[1,4,9]"]);
+
+    let program = jq::compile(".value * $factor").unwrap();
+    let input   = Json::parse("{\"value\":21}").unwrap();
+    let output  = program.run_with(&input, &[("factor", Json::from(2))]).unwrap();
+    assert_eq!(output, vec![Json::from(42u64)]);
+}
+
+#[test]
+fn string_functions()
+{
+    assert_eq!(run("ascii_upcase", "\"abc\""), vec!["\"ABC\""]);
+    assert_eq!(run("ascii_downcase", "\"ABC\""), vec!["\"abc\""]);
+    assert_eq!(run("split(\", \")", "\"a, b\""), vec!["[\"a\",\"b\"]"]);
+    assert_eq!(run("join(\"-\")", "[\"a\",1,null]"), vec!["\"a-1-\""]);
+    assert_eq!(run("ltrimstr(\"foo\")", "\"foobar\""), vec!["\"bar\""]);
+    assert_eq!(run("rtrimstr(\"bar\")", "\"foobar\""), vec!["\"foo\""]);
+    assert_eq!(run("startswith(\"fo\"), endswith(\"fo\")", "\"foo\""), vec!["true", "false"]);
+    assert_eq!(run("test(\"o+$\")", "\"foo\""), vec!["true"]);
+    assert_eq!(run("\"\\(.name) is \\(.age)\"", "{\"name\":\"Ann\",\"age\":30}"), vec!["\"Ann is 30\""]);
+    assert_eq!(run("tostring", "[1]"), vec!["\"[1]\""]);
+    assert_eq!(run("tonumber", "\"12\""), vec!["12"]);
+    assert_eq!(run("length", "\"héllo\""), vec!["5"]);
+}
+
+#[test]
+fn string_results_are_escaped()
+{
+    assert_eq!(run("tostring", "{\"a\":\"b\\\\c\"}"), vec!["\"{\\\"a\\\":\\\"b\\\\\\\\c\\\"}\""]);
+    assert_eq!(run(".a + \"!\"", "{\"a\":\"\\\"\"}"), vec!["\"\\\"!\""]);
+    assert_eq!(run("ltrimstr(\"a\")", "\"a\\n\""), vec!["\"\\n\""]);
+    assert_eq!(run("join(\",\")", "[\"\\\"\",\"\\\\\"]"), vec!["\"\\\",\\\\\""]);
+
+    for program in &["tostring", ".[] + \"!\"", "join(\"\\\"\")"] {
+        for result in Json::parse("[\"\\\"\", \"\\\\\"]").unwrap().jq(program).unwrap() {
+            assert_eq!(Json::parse(&result.to_source()).unwrap(), result, "{}", program);
+        }
+    }
+}
+
+#[test]
+fn collection_functions()
+{
+    assert_eq!(run("keys", "{\"b\":1,\"a\":2}"), vec!["[\"a\",\"b\"]"]);
+    assert_eq!(run("has(\"a\"), has(\"z\")", "{\"a\":1}"), vec!["true", "false"]);
+    assert_eq!(run("sort", "[3,\"a\",null,1,true]"), vec!["[null,true,1,3,\"a\"]"]);
+    assert_eq!(run("sort_by(.n) | map(.n)", "[{\"n\":2},{\"n\":1}]"), vec!["[1,2]"]);
+    assert_eq!(run("unique", "[2,1,2,1]"), vec!["[1,2]"]);
+    assert_eq!(run("min, max", "[2,1,3]"), vec!["1", "3"]);
+    assert_eq!(run("reverse", "[1,2]"), vec!["[2,1]"]);
+    assert_eq!(run("[range(3)]", "null"), vec!["[0,1,2]"]);
+    assert_eq!(run("flatten", "[1,[2,[3]]]"), vec!["[1,2,3]"]);
+    assert_eq!(run("to_entries | map(.key)", "{\"a\":1}"), vec!["[\"a\"]"]);
+    assert_eq!(run("with_entries({key: (.key + \"x\"), value})", "{\"a\":1}"), vec!["{\"ax\":1}"]);
+    assert_eq!(run("map_values(. + 1)", "{\"a\":1}"), vec!["{\"a\":2}"]);
+    assert_eq!(run("[limit(2; .[])]", "[1,2,3]"), vec!["[1,2]"]);
+    assert_eq!(run("group_by(.) | length", "[1,2,1]"), vec!["2"]);
+    assert_eq!(run("contains([\"a\"])", "[\"abc\"]"), vec!["true"]);
+}
+
+#[test]
+fn errors()
+{
+    let input = Json::parse("{\"a\":1}").unwrap();
+
+    assert_eq!(input.jq(".a.b"), Err(JqError::Runtime("Cannot index number with string".to_string())));
+    assert_eq!(input.jq("error(\"boom\")"), Err(JqError::Runtime("boom".to_string())));
+    assert_eq!(run("try error(\"boom\") catch .", "null"), vec!["\"boom\""]);
+    assert_eq!(run("[.[] | (1 / .)?]", "[1,0]"), vec!["[1]"]);
+
+    match jq::compile(".a | nope(1)") {
+        Err(JqError::Compile { position, message }) => {
+            assert_eq!(position, 5);
+            assert_eq!(message, "nope/1 is not defined");
+        },
+        other => panic!("unexpected {:?}", other),
+    }
+
+    assert!(jq::compile(".a |").is_err());
+    assert!(jq::compile("if . then 1").is_err());
+    assert!(jq::compile("[1, 2").is_err());
+    assert!(jq::compile("{a: 1").is_err());
+}
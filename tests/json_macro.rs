@@ -0,0 +1,70 @@
+#[macro_use]
+extern crate simple_json;
+use simple_json::{ Json, Number };
+
+fn parse(text: &str) -> Json
+{
+    Json::parse(text).unwrap()
+}
+
+#[test]
+fn scalars()
+{
+    assert_eq!(json!(null), parse("null"));
+    assert_eq!(json!(true), parse("true"));
+    assert_eq!(json!(false), parse("false"));
+    assert_eq!(json!(12), parse("12"));
+    assert_eq!(json!(-12), parse("-12"));
+    assert_eq!(json!(1.5), parse("1.5"));
+    assert_eq!(json!("text"), parse("\"text\""));
+}
+
+#[test]
+fn nested()
+{
+    let json = json!({"a": [1, 2, {"b": null}], "c": {}, "d": [], "e": [[true], -3]});
+
+    assert_eq!(json, parse("{\"a\":[1,2,{\"b\":null}],\"c\":{},\"d\":[],\"e\":[[true],-3]}"));
+    assert_eq!(json.to_source(), "{\"a\":[1,2,{\"b\":null}],\"c\":{},\"d\":[],\"e\":[[true],-3]}");
+}
+
+#[test]
+fn interpolation()
+{
+    let x     = 40 + 2;
+    let name  = String::from("some \"name\"");
+    let inner = json!([x, null]);
+    let key   = "dynamic";
+
+    let json = json!({
+        "x": x,
+        "sum": x as f64 / 4.0,
+        "name": name,
+        "inner": inner,
+        (key): Json::from(vec![]),
+        "negative": -x,
+    });
+
+    assert_eq!(json.to_source(), concat!(
+        "{\"x\":42,\"sum\":10.5,\"name\":\"some \\\"name\\\"\",\"inner\":[42,null],",
+        "\"dynamic\":[],\"negative\":-42}",
+    ));
+    assert_eq!(json, parse(&json.to_source()));
+}
+
+#[test]
+fn trailing_commas()
+{
+    assert_eq!(json!([1, 2,]), parse("[1,2]"));
+    assert_eq!(json!({"a": 1,}), parse("{\"a\":1}"));
+}
+
+#[test]
+fn numbers_match_the_parser()
+{
+    if let Json::Number(number, _) = json!(7) {
+        assert_eq!(number, Number::Unsigned(7));
+    } else {
+        panic!("expected a number");
+    }
+}
@@ -0,0 +1,156 @@
+extern crate simple_json;
+use simple_json::{ Json, JsonPath };
+
+// The bookstore document from RFC 9535, section 1.5.
+const STORE: &str = concat!(
+    "{\"store\":{",
+        "\"book\":[",
+            "{\"category\":\"reference\",\"author\":\"Nigel Rees\",\"title\":\"Sayings of the Century\",\"price\":8.95},",
+            "{\"category\":\"fiction\",\"author\":\"Evelyn Waugh\",\"title\":\"Sword of Honour\",\"price\":12.99},",
+            "{\"category\":\"fiction\",\"author\":\"Herman Melville\",\"title\":\"Moby Dick\",\"isbn\":\"0-553-21311-3\",\"price\":8.99},",
+            "{\"category\":\"fiction\",\"author\":\"J. R. R. Tolkien\",\"title\":\"The Lord of the Rings\",\"isbn\":\"0-395-19395-8\",\"price\":22.99}",
+        "],",
+        "\"bicycle\":{\"color\":\"red\",\"price\":399}",
+    "}}"
+);
+
+fn paths(json: &Json, query: &str) -> Vec<String>
+{
+    json.query(query).unwrap().iter().map(|found| found.path.to_string()).collect()
+}
+
+fn sources(json: &Json, query: &str) -> Vec<String>
+{
+    json.query(query).unwrap().iter().map(|found| found.value.to_source()).collect()
+}
+
+#[test]
+fn rfc_bookstore_examples()
+{
+    let json = Json::parse(STORE).unwrap();
+
+    assert_eq!(sources(&json, "$.store.book[*].author"), vec![
+        "\"Nigel Rees\"", "\"Evelyn Waugh\"", "\"Herman Melville\"", "\"J. R. R. Tolkien\"",
+    ]);
+    assert_eq!(sources(&json, "$..author").len(), 4);
+    assert_eq!(sources(&json, "$.store.*").len(), 2);
+    assert_eq!(sources(&json, "$.store..price"), vec!["399", "8.95", "12.99", "8.99", "22.99"]);
+    assert_eq!(paths(&json, "$..book[2]"), vec!["$['store']['book'][2]"]);
+    assert_eq!(paths(&json, "$..book[-1]"), vec!["$['store']['book'][3]"]);
+    assert_eq!(paths(&json, "$..book[0,1]"), vec!["$['store']['book'][0]", "$['store']['book'][1]"]);
+    assert_eq!(paths(&json, "$..book[:2]"), vec!["$['store']['book'][0]", "$['store']['book'][1]"]);
+    assert_eq!(paths(&json, "$..book[?@.isbn]"), vec!["$['store']['book'][2]", "$['store']['book'][3]"]);
+    assert_eq!(paths(&json, "$..book[?@.price<10]"), vec!["$['store']['book'][0]", "$['store']['book'][2]"]);
+    assert_eq!(sources(&json, "$..*").len(), 27);
+}
+
+#[test]
+fn filter_with_title()
+{
+    let json = Json::parse(STORE).unwrap();
+
+    assert_eq!(
+        sources(&json, "$.store.book[?(@.price < 10)].title"),
+        vec!["\"Sayings of the Century\"", "\"Moby Dick\""]
+    );
+    assert_eq!(
+        sources(&json, "$.store.book[?@.category == 'fiction' && @.price > 20].title"),
+        vec!["\"The Lord of the Rings\""]
+    );
+    assert_eq!(
+        sources(&json, "$.store.book[?!@.isbn].title"),
+        vec!["\"Sayings of the Century\"", "\"Sword of Honour\""]
+    );
+    assert_eq!(
+        sources(&json, "$.store.book[?@.price == $.store.book[0].price].title"),
+        vec!["\"Sayings of the Century\""]
+    );
+}
+
+#[test]
+fn slices()
+{
+    let json = Json::parse("[\"a\",\"b\",\"c\",\"d\",\"e\",\"f\",\"g\"]").unwrap();
+
+    assert_eq!(sources(&json, "$[1:3]"),   vec!["\"b\"", "\"c\""]);
+    assert_eq!(sources(&json, "$[5:]"),    vec!["\"f\"", "\"g\""]);
+    assert_eq!(sources(&json, "$[1:5:2]"), vec!["\"b\"", "\"d\""]);
+    assert_eq!(sources(&json, "$[5:1:-2]"), vec!["\"f\"", "\"d\""]);
+    assert_eq!(sources(&json, "$[::-1]").len(), 7);
+    assert_eq!(sources(&json, "$[::0]").len(), 0);
+}
+
+#[test]
+fn functions()
+{
+    let json = Json::parse("[{\"a\":\"abc\",\"b\":[1,2]},{\"a\":\"bcd\",\"b\":[]},{\"a\":\"x\"}]").unwrap();
+
+    assert_eq!(paths(&json, "$[?length(@.a) == 3]"), vec!["$[0]", "$[1]"]);
+    assert_eq!(paths(&json, "$[?count(@.b[*]) > 1]"), vec!["$[0]"]);
+    assert_eq!(paths(&json, "$[?match(@.a, 'a.c')]"), vec!["$[0]"]);
+    assert_eq!(paths(&json, "$[?search(@.a, '[bx]')]"), vec!["$[0]", "$[1]", "$[2]"]);
+    assert_eq!(paths(&json, "$[?match(@.a, 'b.*')]"), vec!["$[1]"]);
+    assert_eq!(paths(&json, "$[?value(@..a) == 'x']"), vec!["$[2]"]);
+}
+
+#[test]
+fn regex_patterns()
+{
+    let json = Json::parse("[{\"a\":\"ab\",\"p\":\"a.\"},{\"a\":\"abab\",\"p\":\"(ab){2}\"},{\"a\":\"b\",\"p\":\"(\"}]").unwrap();
+
+    assert_eq!(paths(&json, "$[?match(@.a, @.p)]"), vec!["$[0]", "$[1]"]);
+    assert_eq!(paths(&json, "$[?match(@.a, '(a|ab)(c|bab)')]"), vec!["$[1]"]);
+    assert_eq!(paths(&json, "$[?search(@.a, '^b$')]"), vec!["$[2]"]);
+    assert_eq!(paths(&json, "$[?search(@.a, 'b$')]"), vec!["$[0]", "$[1]", "$[2]"]);
+    assert_eq!(paths(&json, "$[?search(@.a, '(')]"), Vec::<String>::new());
+    assert_eq!(paths(&json, "$[?search(@.a, 'a{1000}{1000}')]"), Vec::<String>::new());
+
+    // Nested quantifiers don't backtrack, and long texts don't recurse.
+    let text = format!("[\"{}!\"]", "a".repeat(10_000));
+    let json = Json::parse(&text).unwrap();
+    assert_eq!(paths(&json, "$[?match(@, '(a+)+')]"), Vec::<String>::new());
+    assert_eq!(paths(&json, "$[?search(@, '(a*)*b')]"), Vec::<String>::new());
+
+    let text = format!("[\"{}\"]", "x".repeat(200_000));
+    let json = Json::parse(&text).unwrap();
+    assert_eq!(paths(&json, "$[?match(@, '[a-z]+')]"), vec!["$[0]"]);
+}
+
+#[test]
+fn comparisons()
+{
+    let json = Json::parse("[1,-1,2.5,\"1\",true,null,[1],{\"a\":1}]").unwrap();
+
+    assert_eq!(paths(&json, "$[?@ == 1]"), vec!["$[0]"]);
+    assert_eq!(paths(&json, "$[?@ < 2]"), vec!["$[0]", "$[1]"]);
+    assert_eq!(paths(&json, "$[?@ >= 2.5]"), vec!["$[2]"]);
+    assert_eq!(paths(&json, "$[?@ == null]"), vec!["$[5]"]);
+    assert_eq!(paths(&json, "$[?@ == true]"), vec!["$[4]"]);
+    assert_eq!(paths(&json, "$[?@.a == 1]"), vec!["$[7]"]);
+    assert_eq!(paths(&json, "$[?@.z == @.y]").len(), 8);
+}
+
+#[test]
+fn normalized_paths()
+{
+    let json = Json::parse("{\"it's\":{\"a\\\\b\":1}}").unwrap();
+    let found = json.query("$.*.*").unwrap();
+
+    assert_eq!(found[0].path.to_string(), "$['it\\'s']['a\\\\b']");
+    assert_eq!(found[0].path.to_pointer().to_string(), "/it's/a\\b");
+}
+
+#[test]
+fn invalid_queries()
+{
+    for query in &[
+        "", "store", "$.", "$[", "$[]", "$['a'", "$[01]", "$[-0]", "$ ", "$[?@.a == ]",
+        "$[?1]", "$[?length(@.a)]", "$[?@..a == 1]", "$[?count(1) == 1]", "$[?foo(@)]",
+        "$[9007199254740992]",
+    ] {
+        assert!(JsonPath::parse(query).is_err(), "{} should not parse", query);
+    }
+
+    let error = JsonPath::parse("$.a[?@.b =]").unwrap_err();
+    assert_eq!(error.position, 9);
+}
@@ -0,0 +1,292 @@
+#![cfg(feature = "lsp")]
+
+extern crate simple_json;
+
+use std::fs;
+use std::io::{ BufRead, BufReader, Read, Write };
+use std::process::{ Child, ChildStdout, Command, Stdio };
+
+use simple_json::{ json, Json };
+
+struct Client
+{
+    child:  Child,
+    output: BufReader<ChildStdout>,
+    // Notifications received while waiting for a response.
+    notifications: Vec<Json>,
+    next_id: u64,
+}
+
+impl Client
+{
+    fn start() -> Client
+    {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_simple-json-lsp"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let output = BufReader::new(child.stdout.take().unwrap());
+        let mut client = Client { child, output, notifications: vec![], next_id: 1 };
+
+        client.request("initialize", json!({ "capabilities": {} }));
+        client.notify("initialized", json!({}));
+        client
+    }
+
+    fn send(&mut self, message: Json)
+    {
+        self.send_bytes(message.to_source().as_bytes());
+    }
+
+    fn send_bytes(&mut self, body: &[u8])
+    {
+        let stdin = self.child.stdin.as_mut().unwrap();
+
+        write!(stdin, "Content-Length: {}\r\n\r\n", body.len()).unwrap();
+        stdin.write_all(body).unwrap();
+        stdin.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Json
+    {
+        let mut length = 0;
+
+        loop {
+            let mut line = String::new();
+            self.output.read_line(&mut line).unwrap();
+
+            match line.trim_end().strip_prefix("Content-Length: ") {
+                Some(value) => length = value.parse().unwrap(),
+                None if line.trim_end().is_empty() => break,
+                None => {},
+            }
+        }
+
+        let mut body = vec![0; length];
+        self.output.read_exact(&mut body).unwrap();
+        Json::parse(&String::from_utf8(body).unwrap()).unwrap()
+    }
+
+    fn notify(&mut self, method: &str, params: Json)
+    {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    // Sends a request and returns the whole response.
+    fn call(&mut self, method: &str, params: Json) -> Json
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+
+        loop {
+            let message = self.receive();
+            match message.pointer("/id") {
+                Ok(_)  => return message,
+                Err(_) => self.notifications.push(message),
+            }
+        }
+    }
+
+    fn request(&mut self, method: &str, params: Json) -> Json
+    {
+        self.call(method, params).pointer("/result").unwrap().clone()
+    }
+
+    fn open(&mut self, uri: &str, text: &str) -> Json
+    {
+        self.notify("textDocument/didOpen", json!({
+            "textDocument": { "uri": uri, "languageId": "json", "version": 1, "text": text },
+        }));
+
+        self.sync(uri);
+        self.notifications.pop().unwrap()
+    }
+
+    // Diagnostics come before the answer to any later request.
+    fn sync(&mut self, uri: &str)
+    {
+        self.request("textDocument/documentSymbol", json!({ "textDocument": { "uri": uri } }));
+    }
+
+    fn at(&mut self, method: &str, uri: &str, line: u64, character: u64) -> Json
+    {
+        self.request(method, json!({
+            "textDocument": { "uri": uri },
+            "position":     { "line": line, "character": character },
+        }))
+    }
+}
+
+impl Drop for Client
+{
+    fn drop(&mut self)
+    {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn strings(json: &Json, pointer: &str) -> Vec<String>
+{
+    json.as_array().unwrap().iter().map(|item| item.pointer(pointer).unwrap().as_str().unwrap().to_string()).collect()
+}
+
+#[test]
+fn lifecycle()
+{
+    let mut client = Client::start();
+
+    let response = client.call("unknown/method", json!({}));
+    assert_eq!(response.pointer("/error/code").unwrap(), &json!(-32601));
+
+    assert_eq!(client.request("shutdown", json!(null)), json!(null));
+    client.notify("exit", json!(null));
+    assert_eq!(client.child.wait().unwrap().code(), Some(0));
+}
+
+#[test]
+fn malformed_messages()
+{
+    let mut client = Client::start();
+
+    client.send_bytes(b"{\"jsonrpc\": \"2.0\", \"method\": \"\xff\"}");
+    assert_eq!(client.receive().pointer("/error/code").unwrap(), &json!(-32700));
+
+    client.send_bytes(b"{\"id\": 1,");
+    assert_eq!(client.receive().pointer("/error/code").unwrap(), &json!(-32700));
+
+    assert_eq!(client.request("shutdown", json!(null)), json!(null));
+}
+
+#[test]
+fn diagnostics()
+{
+    let mut client = Client::start();
+
+    let published = client.open("file:///a.json", "{\n  \"a\": 1,\n  \"b\": [1 2]\n}");
+    assert_eq!(published.pointer("/params/uri").unwrap(), &json!("file:///a.json"));
+
+    let diagnostics = published.pointer("/params/diagnostics").unwrap();
+    assert_eq!(strings(diagnostics, "/message"), vec!["expected `,`"]);
+    assert_eq!(diagnostics.pointer("/0/range/start").unwrap(), &json!({ "line": 2, "character": 10 }));
+
+    client.notify("textDocument/didChange", json!({
+        "textDocument":   { "uri": "file:///a.json", "version": 2 },
+        "contentChanges": [{ "text": "{\"a\": 1}" }],
+    }));
+    client.sync("file:///a.json");
+    assert_eq!(client.notifications.pop().unwrap().pointer("/params/diagnostics").unwrap(), &json!([]));
+}
+
+#[test]
+fn formatting()
+{
+    let mut client = Client::start();
+    client.open("file:///a.json", "{\"a\":[1,2],\"b\":{}}");
+
+    let edits = client.request("textDocument/formatting", json!({
+        "textDocument": { "uri": "file:///a.json" },
+        "options":      { "tabSize": 4, "insertSpaces": true },
+    }));
+    assert_eq!(edits.pointer("/0/newText").unwrap(), &json!("{\n    \"a\": [\n        1,\n        2\n    ],\n    \"b\": {}\n}\n"));
+    assert_eq!(edits.pointer("/0/range/end").unwrap(), &json!({ "line": 0, "character": 18 }));
+
+    let edits = client.request("textDocument/formatting", json!({
+        "textDocument": { "uri": "file:///a.json" },
+        "options":      { "tabSize": 4, "insertSpaces": false },
+    }));
+    assert_eq!(edits.pointer("/0/newText").unwrap(), &json!("{\n\t\"a\": [\n\t\t1,\n\t\t2\n\t],\n\t\"b\": {}\n}\n"));
+}
+
+#[test]
+fn hover()
+{
+    let mut client = Client::start();
+    client.open("file:///a.json", "{\n  \"é\": [true, {\"a/b\": null}]\n}");
+
+    let hover = client.at("textDocument/hover", "file:///a.json", 1, 19);
+    assert_eq!(hover.pointer("/contents/value").unwrap(), &json!("`/é/1/a~1b`"));
+    assert_eq!(hover.pointer("/range").unwrap(), &json!({
+        "start": { "line": 1, "character": 15 },
+        "end":   { "line": 1, "character": 20 },
+    }));
+
+    let hover = client.at("textDocument/hover", "file:///a.json", 1, 9);
+    assert_eq!(hover.pointer("/contents/value").unwrap(), &json!("`/é/0`"));
+
+    // Invalid documents are answered from what could be recovered.
+    client.open("file:///a.json", "{\n  \"a\": [1 2],\n  \"b\": ,\n  \"c\": {\"d\": tru}\n}");
+    let hover = client.at("textDocument/hover", "file:///a.json", 1, 10);
+    assert_eq!(hover.pointer("/contents/value").unwrap(), &json!("`/a/1`"));
+
+    let symbols = client.request("textDocument/documentSymbol", json!({ "textDocument": { "uri": "file:///a.json" } }));
+    assert_eq!(strings(&symbols, "/name"), vec!["a", "c"]);
+    assert_eq!(symbols.pointer("/1/children").unwrap(), &json!([]));
+}
+
+#[test]
+fn folding_ranges_and_symbols()
+{
+    let mut client = Client::start();
+    client.open("file:///a.json", "{\n  \"a\": [\n    1\n  ],\n  \"b\": {\"c\": \"d\"}\n}\n");
+
+    let ranges = client.request("textDocument/foldingRange", json!({ "textDocument": { "uri": "file:///a.json" } }));
+    assert_eq!(ranges, json!([{ "startLine": 0, "endLine": 5 }, { "startLine": 1, "endLine": 3 }]));
+
+    let symbols = client.request("textDocument/documentSymbol", json!({ "textDocument": { "uri": "file:///a.json" } }));
+    assert_eq!(strings(&symbols, "/name"), vec!["a", "b"]);
+    assert_eq!(symbols.pointer("/0/kind").unwrap(), &json!(18));
+    assert_eq!(symbols.pointer("/0/selectionRange").unwrap(), &json!({
+        "start": { "line": 1, "character": 2 },
+        "end":   { "line": 1, "character": 5 },
+    }));
+    assert_eq!(strings(symbols.pointer("/1/children").unwrap(), "/name"), vec!["c"]);
+    assert_eq!(symbols.pointer("/1/children/0/detail").unwrap(), &json!("\"d\""));
+}
+
+#[test]
+fn completion()
+{
+    let directory = std::env::temp_dir().join(format!("simple-json-lsp-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("schema.json"), "{
+        \"properties\": {
+            \"$schema\": {},
+            \"name\":    { \"type\": \"string\", \"description\": \"The name.\" },
+            \"kind\":    { \"enum\": [\"a\", \"b\", 1] },
+            \"items\":   { \"items\": { \"$ref\": \"#/definitions/item\" } }
+        },
+        \"definitions\": { \"item\": { \"properties\": { \"enabled\": { \"type\": \"boolean\" } } } }
+    }").unwrap();
+
+    let uri = format!("file://{}/a.json", directory.display());
+    let mut client = Client::start();
+
+    // Still invalid while the key is being typed.
+    client.open(&uri, "{\"$schema\": \"schema.json\", \"name\": \"x\", \"\" }");
+    let items = client.at("textDocument/completion", &uri, 0, 41);
+    assert_eq!(strings(&items, "/label"), vec!["items", "kind"]);
+    assert_eq!(strings(&items, "/insertText"), vec!["items", "kind"]);
+
+    client.open(&uri, "{\"$schema\": \"schema.json\", \"kind\": }");
+    let items = client.at("textDocument/completion", &uri, 0, 35);
+    assert_eq!(strings(&items, "/insertText"), vec!["\"a\"", "\"b\"", "1"]);
+
+    client.open(&uri, "{\"$schema\": \"schema.json\", \"items\": [{}, {\"enabled\": ");
+    let items = client.at("textDocument/completion", &uri, 0, 53);
+    assert_eq!(strings(&items, "/label"), vec!["true", "false"]);
+
+    client.open(&uri, "{\"$schema\": \"schema.json\", \"items\": [{ }]}");
+    let items = client.at("textDocument/completion", &uri, 0, 39);
+    assert_eq!(strings(&items, "/insertText"), vec!["\"enabled\""]);
+    assert_eq!(items.pointer("/0/detail").unwrap(), &json!("\"boolean\""));
+
+    client.open("file:///nowhere.json", "{\"$schema\": \"https://example.com/schema.json\", }");
+    assert_eq!(client.at("textDocument/completion", "file:///nowhere.json", 0, 47), json!([]));
+
+    fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,122 @@
+extern crate simple_json;
+
+use simple_json::merge::{ ArrayMerge, Conflict, MergeOptions, MergeRule, Origin };
+use simple_json::{ json, Error, Json, JsonPointer };
+
+fn layers(texts: &[&str]) -> Vec<Json>
+{
+    texts.iter().map(|text| Json::parse(text).unwrap()).collect()
+}
+
+fn pointer(text: &str) -> JsonPointer
+{
+    JsonPointer::parse(text).unwrap()
+}
+
+fn origin(layer: usize, text: &str) -> Option<Origin>
+{
+    Some(Origin { layer, pointer: pointer(text) })
+}
+
+fn with_arrays(arrays: ArrayMerge) -> MergeOptions
+{
+    MergeOptions { arrays, ..MergeOptions::default() }
+}
+
+#[test]
+fn merge_objects()
+{
+    let merged = Json::merge(&layers(&[
+        "{\"db\": {\"host\": \"localhost\", \"port\": 5432}, \"tags\": [\"a\"]}",
+        "{\"db\": {\"host\": \"db.internal\"}}",
+        "{\"tags\": [\"b\"], \"debug\": true}",
+    ])).unwrap();
+
+    assert_eq!(merged.value.to_source(), "{\"db\":{\"host\":\"db.internal\",\"port\":5432},\"tags\":[\"b\"],\"debug\":true}");
+    assert_eq!(merged.origin(&pointer("/db/host")).cloned(), origin(1, "/db/host"));
+    assert_eq!(merged.origin(&pointer("/db/port")).cloned(), origin(0, "/db/port"));
+    assert_eq!(merged.origin(&pointer("/tags/0")).cloned(), origin(2, "/tags/0"));
+    assert_eq!(merged.origin(&pointer("/db")).cloned(), origin(1, "/db"));
+    assert_eq!(merged.origin(&pointer("")).cloned(), origin(2, ""));
+    assert_eq!(merged.origins.len(), 7);
+
+    assert_eq!(Json::merge(&[]).unwrap().value.to_source(), "{}");
+}
+
+#[test]
+fn merge_arrays()
+{
+    let numbers = layers(&["[1, 2]", "[2, 3]"]);
+
+    assert_eq!(Json::merge(&numbers).unwrap().value.to_source(), "[2,3]");
+    assert_eq!(Json::merge_with(&numbers, &with_arrays(ArrayMerge::Append)).unwrap().value.to_source(), "[1,2,2,3]");
+
+    let union = Json::merge_with(&numbers, &with_arrays(ArrayMerge::Union)).unwrap();
+    assert_eq!(union.value.to_source(), "[1,2,3]");
+    assert_eq!(union.origin(&pointer("/1")).cloned(), origin(0, "/1"));
+    assert_eq!(union.origin(&pointer("/2")).cloned(), origin(1, "/1"));
+
+    let servers = layers(&[
+        "[{\"name\": \"a\", \"port\": 1}, {\"name\": \"b\"}]",
+        "[{\"name\": \"b\", \"port\": 2}, {\"name\": \"c\"}, 5]",
+    ]);
+    let merged = Json::merge_with(&servers, &with_arrays(ArrayMerge::ByKey("name".to_string()))).unwrap();
+
+    assert_eq!(merged.value.to_source(), "[{\"name\":\"a\",\"port\":1},{\"name\":\"b\",\"port\":2},{\"name\":\"c\"},5]");
+    assert_eq!(merged.origin(&pointer("/1/port")).cloned(), origin(1, "/0/port"));
+    assert_eq!(merged.origin(&pointer("/2")).cloned(), origin(1, "/1"));
+
+    // Numbers equal in value are the same key, elements are found by value.
+    let large = |start: usize| Json::from((start..start + 3_000).map(|index| json!({ "id": (index as u64), "n": 1.0 })).collect::<Vec<_>>());
+    let layers = vec![large(0), large(1_500), layers(&["[{\"id\": 1.0, \"n\": 2}]"]).remove(0)];
+
+    let union = Json::merge_with(&layers, &with_arrays(ArrayMerge::Union)).unwrap();
+    assert_eq!(union.value.as_array().unwrap().len(), 4_501);
+
+    let merged = Json::merge_with(&layers, &with_arrays(ArrayMerge::ByKey("id".to_string()))).unwrap();
+    assert_eq!(merged.value.as_array().unwrap().len(), 4_500);
+    assert_eq!(merged.value.pointer("/1/n").unwrap().to_source(), "2");
+}
+
+#[test]
+fn conflicts()
+{
+    let values = layers(&["{\"a\": 1, \"b\": {\"c\": true}, \"d\": [0]}", "{\"a\": 2, \"b\": [], \"d\": [0]}"]);
+
+    let left = Json::merge_with(&values, &MergeOptions { conflicts: Conflict::LeftWins, ..MergeOptions::default() }).unwrap();
+    assert_eq!(left.value.to_source(), "{\"a\":1,\"b\":{\"c\":true},\"d\":[0]}");
+
+    let right = Json::merge(&values).unwrap();
+    assert_eq!(right.value.to_source(), "{\"a\":2,\"b\":[],\"d\":[0]}");
+
+    let strict = MergeOptions { conflicts: Conflict::Error, ..MergeOptions::default() };
+    assert_eq!(Json::merge_with(&values, &strict), Err(Error::MergeConflict("/a".to_string())));
+
+    // Equal values don't conflict.
+    let equal = layers(&["{\"a\": 1, \"b\": null}", "{\"a\": 1.0, \"b\": null}"]);
+    assert_eq!(Json::merge_with(&equal, &strict).unwrap().origin(&pointer("/a")).cloned(), origin(1, "/a"));
+}
+
+#[test]
+fn rules()
+{
+    let options = MergeOptions {
+        arrays:    ArrayMerge::Replace,
+        conflicts: Conflict::Error,
+        rules:     vec![
+            MergeRule { path: pointer("/plugins"), arrays: Some(ArrayMerge::Append), conflicts: None },
+            MergeRule { path: pointer("/overrides"), arrays: None, conflicts: Some(Conflict::RightWins) },
+            MergeRule { path: pointer("/overrides/locked"), arrays: None, conflicts: Some(Conflict::LeftWins) },
+        ],
+    };
+
+    let merged = Json::merge_with(&layers(&[
+        "{\"plugins\": [\"a\"], \"paths\": [\"x\"], \"overrides\": {\"level\": 1, \"locked\": {\"on\": true}}}",
+        "{\"plugins\": [\"b\"], \"paths\": [\"y\"], \"overrides\": {\"level\": 2, \"locked\": {\"on\": false}}}",
+    ]), &options).unwrap();
+
+    assert_eq!(merged.value.to_source(), "{\"plugins\":[\"a\",\"b\"],\"paths\":[\"y\"],\"overrides\":{\"level\":2,\"locked\":{\"on\":true}}}");
+
+    let conflicting = layers(&["{\"plugins\": [], \"name\": \"a\"}", "{\"name\": \"b\"}"]);
+    assert_eq!(Json::merge_with(&conflicting, &options), Err(Error::MergeConflict("/name".to_string())));
+}
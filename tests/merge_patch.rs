@@ -0,0 +1,76 @@
+extern crate simple_json;
+use simple_json::{ Json, Patch };
+
+fn assert_same(left: &Json, right: &Json)
+{
+    assert_eq!(Patch::diff(left, right), Patch::default(), "{} != {}", left, right);
+}
+
+// Test cases from RFC 7396, appendix A.
+const CASES: &[(&str, &str, &str)] = &[
+    ("{\"a\":\"b\"}", "{\"a\":\"c\"}", "{\"a\":\"c\"}"),
+    ("{\"a\":\"b\"}", "{\"b\":\"c\"}", "{\"a\":\"b\",\"b\":\"c\"}"),
+    ("{\"a\":\"b\"}", "{\"a\":null}", "{}"),
+    ("{\"a\":\"b\",\"b\":\"c\"}", "{\"a\":null}", "{\"b\":\"c\"}"),
+    ("{\"a\":[\"b\"]}", "{\"a\":\"c\"}", "{\"a\":\"c\"}"),
+    ("{\"a\":\"c\"}", "{\"a\":[\"b\"]}", "{\"a\":[\"b\"]}"),
+    ("{\"a\":{\"b\":\"c\"}}", "{\"a\":{\"b\":\"d\",\"c\":null}}", "{\"a\":{\"b\":\"d\"}}"),
+    ("{\"a\":[{\"b\":\"c\"}]}", "{\"a\":[1]}", "{\"a\":[1]}"),
+    ("[\"a\",\"b\"]", "[\"c\",\"d\"]", "[\"c\",\"d\"]"),
+    ("{\"a\":\"b\"}", "[\"c\"]", "[\"c\"]"),
+    ("{\"a\":\"foo\"}", "null", "null"),
+    ("{\"a\":\"foo\"}", "\"bar\"", "\"bar\""),
+    ("{\"e\":null}", "{\"a\":1}", "{\"e\":null,\"a\":1}"),
+    ("[1,2]", "{\"a\":\"b\",\"c\":null}", "{\"a\":\"b\"}"),
+    ("{}", "{\"a\":{\"bb\":{\"ccc\":null}}}", "{\"a\":{\"bb\":{}}}"),
+];
+
+#[test]
+fn rfc_examples()
+{
+    for (target, patch, expected) in CASES {
+        let mut json = Json::parse(target).unwrap();
+        json.merge_patch(&Json::parse(patch).unwrap());
+
+        assert_same(&json, &Json::parse(expected).unwrap());
+    }
+}
+
+#[test]
+fn source_is_refreshed()
+{
+    let mut json = Json::parse("{\"a\":{\"b\":1}}").unwrap();
+    json.merge_patch(&Json::parse("{\"a\":{\"b\":2}}").unwrap());
+
+    assert_eq!(json.to_source(), "{\"a\":{\"b\":2}}");
+}
+
+#[test]
+fn create_merge_patch()
+{
+    let from = Json::parse("{\"a\":1,\"b\":{\"c\":[1],\"d\":true},\"e\":\"x\"}").unwrap();
+    let to   = Json::parse("{\"a\":1,\"b\":{\"c\":[1,2],\"d\":true},\"f\":2}").unwrap();
+
+    let patch = Json::create_merge_patch(&from, &to);
+    assert_same(&patch, &Json::parse("{\"b\":{\"c\":[1,2]},\"e\":null,\"f\":2}").unwrap());
+
+    let mut patched = from.clone();
+    patched.merge_patch(&patch);
+    assert_same(&patched, &to);
+
+    assert_same(&Json::create_merge_patch(&from, &from), &Json::parse("{}").unwrap());
+    assert_same(&Json::create_merge_patch(&from, &Json::from(1)), &Json::from(1));
+}
+
+#[test]
+fn create_merge_patch_round_trips()
+{
+    for (target, _, expected) in CASES {
+        let from = Json::parse(target).unwrap();
+        let to   = Json::parse(expected).unwrap();
+
+        let mut patched = from.clone();
+        patched.merge_patch(&Json::create_merge_patch(&from, &to));
+        assert_same(&patched, &to);
+    }
+}
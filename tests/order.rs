@@ -0,0 +1,79 @@
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+extern crate simple_json;
+use simple_json::{ Json, Number, Semantic };
+
+fn parse(text: &str) -> Json
+{
+    Json::parse(text).unwrap()
+}
+
+#[test]
+fn type_rank()
+{
+    let values = ["null", "false", "true", "-1", "0", "2.5", "\"\"", "\"a\"", "[]", "[0]", "{}"];
+
+    for (index, left) in values.iter().enumerate() {
+        for (other, right) in values.iter().enumerate() {
+            assert_eq!(parse(left).total_cmp(&parse(right)), index.cmp(&other), "{} {}", left, right);
+        }
+    }
+}
+
+#[test]
+fn numbers()
+{
+    assert_eq!(parse("1").total_cmp(&parse("1.0")), Ordering::Equal);
+    assert_eq!(parse("-0.0").total_cmp(&parse("0")), Ordering::Equal);
+    assert_eq!(parse("-1").total_cmp(&parse("18446744073709551615")), Ordering::Less);
+
+    // Exact, where going through f64 would find them equal.
+    assert_eq!(parse("9007199254740993").total_cmp(&parse("9007199254740992.0")), Ordering::Greater);
+    assert_eq!(parse("18446744073709551615").total_cmp(&parse("1.8446744073709552e19")), Ordering::Less);
+    assert_eq!(parse("1e300").total_cmp(&parse("18446744073709551615")), Ordering::Greater);
+    assert_eq!(parse("-0.5").total_cmp(&parse("-1")), Ordering::Greater);
+
+    let nan = Json::from(f64::NAN);
+    assert_eq!(nan.total_cmp(&Json::from(f64::INFINITY)), Ordering::Greater);
+    assert_eq!(Json::from(1).total_cmp(&nan), Ordering::Less);
+    assert_eq!(nan.total_cmp(&Json::from(f64::NAN)), Ordering::Equal);
+    assert_eq!(Json::Number(Number::Float(f64::NAN), String::new()).total_cmp(&parse("\"a\"")), Ordering::Less);
+}
+
+#[test]
+fn containers()
+{
+    assert_eq!(parse("[1,2]").total_cmp(&parse("[1,3]")), Ordering::Less);
+    assert_eq!(parse("[1,2]").total_cmp(&parse("[1]")), Ordering::Greater);
+    assert_eq!(parse("[1, 2]").total_cmp(&parse("[1.0,2]")), Ordering::Equal);
+
+    assert_eq!(parse("{\"b\":1,\"a\":2}").total_cmp(&parse("{\"a\":2,\"b\":1}")), Ordering::Equal);
+    assert_eq!(parse("{\"a\":2}").total_cmp(&parse("{\"b\":1}")), Ordering::Less);
+    assert_eq!(parse("{\"a\":2}").total_cmp(&parse("{\"a\":1}")), Ordering::Greater);
+    assert_eq!(parse("{\"a\":1}").total_cmp(&parse("{\"a\":1,\"b\":0}")), Ordering::Less);
+}
+
+#[test]
+fn sort()
+{
+    let mut json = parse("{\"z\": [3, \"b\", null, [1], 1.5, {\"b\":1,\"a\":2}], \"a\": {\"y\": [2, 1], \"x\": true}}");
+
+    let mut keys = json.clone();
+    keys.sort_keys();
+    assert_eq!(keys.to_source(), "{\"a\":{\"x\":true,\"y\":[2,1]},\"z\":[3,\"b\",null,[1],1.5,{\"a\":2,\"b\":1}]}");
+
+    json.sort();
+    assert_eq!(json.to_source(), "{\"a\":{\"x\":true,\"y\":[1,2]},\"z\":[null,1.5,3,\"b\",[1],{\"a\":2,\"b\":1}]}");
+    assert!(json.semantic_eq(&parse(&json.to_source())));
+}
+
+#[test]
+fn semantic_ord()
+{
+    let set: BTreeSet<Semantic> = ["[2]", "1", "[2.0]", "\"x\"", "1.0"].iter().map(|text| Semantic(parse(text))).collect();
+    let values: Vec<Semantic> = set.into_iter().collect();
+
+    assert_eq!(values, vec![Semantic(parse("1")), Semantic(parse("\"x\"")), Semantic(parse("[2]"))]);
+    assert_eq!(Semantic(Json::from(f64::NAN)), Semantic(Json::from(f64::NAN)));
+}
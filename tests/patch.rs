@@ -0,0 +1,233 @@
+extern crate simple_json;
+use simple_json::{ Error, Json, JsonPointer, Patch };
+use simple_json::patch::Operation;
+
+fn apply(document: &str, patch: &str) -> Result<Json, Error>
+{
+    let mut json = Json::parse(document).unwrap();
+    json.apply_patch(&Json::parse(patch).unwrap())?;
+    Ok(json)
+}
+
+macro_rules! assert_patch {
+    ($document:expr, $patch:expr, $expected:expr) => {{
+        let result   = apply($document, $patch).unwrap();
+        let expected = Json::parse($expected).unwrap();
+        assert_eq!(Patch::diff(&result, &expected), Patch::default(), "got {}", result);
+    }};
+}
+
+// Examples from RFC 6902, appendix A.
+#[test]
+fn rfc_examples()
+{
+    assert_patch!("{\"foo\":\"bar\"}", "[{\"op\":\"add\",\"path\":\"/baz\",\"value\":\"qux\"}]", "{\"baz\":\"qux\",\"foo\":\"bar\"}");
+    assert_patch!("{\"foo\":[\"bar\",\"baz\"]}", "[{\"op\":\"add\",\"path\":\"/foo/1\",\"value\":\"qux\"}]", "{\"foo\":[\"bar\",\"qux\",\"baz\"]}");
+    assert_patch!("{\"baz\":\"qux\",\"foo\":\"bar\"}", "[{\"op\":\"remove\",\"path\":\"/baz\"}]", "{\"foo\":\"bar\"}");
+    assert_patch!("{\"foo\":[\"bar\",\"qux\",\"baz\"]}", "[{\"op\":\"remove\",\"path\":\"/foo/1\"}]", "{\"foo\":[\"bar\",\"baz\"]}");
+    assert_patch!("{\"baz\":\"qux\",\"foo\":\"bar\"}", "[{\"op\":\"replace\",\"path\":\"/baz\",\"value\":\"boo\"}]", "{\"baz\":\"boo\",\"foo\":\"bar\"}");
+    assert_patch!(
+        "{\"foo\":{\"bar\":\"baz\",\"waldo\":\"fred\"},\"qux\":{\"corge\":\"grault\"}}",
+        "[{\"op\":\"move\",\"from\":\"/foo/waldo\",\"path\":\"/qux/thud\"}]",
+        "{\"foo\":{\"bar\":\"baz\"},\"qux\":{\"corge\":\"grault\",\"thud\":\"fred\"}}"
+    );
+    assert_patch!(
+        "{\"foo\":[\"all\",\"grass\",\"cows\",\"eat\"]}",
+        "[{\"op\":\"move\",\"from\":\"/foo/1\",\"path\":\"/foo/3\"}]",
+        "{\"foo\":[\"all\",\"cows\",\"eat\",\"grass\"]}"
+    );
+    assert_patch!(
+        "{\"baz\":\"qux\",\"foo\":[\"a\",2,\"c\"]}",
+        "[{\"op\":\"test\",\"path\":\"/baz\",\"value\":\"qux\"},{\"op\":\"test\",\"path\":\"/foo/1\",\"value\":2}]",
+        "{\"baz\":\"qux\",\"foo\":[\"a\",2,\"c\"]}"
+    );
+    assert_eq!(
+        apply("{\"baz\":\"qux\"}", "[{\"op\":\"test\",\"path\":\"/baz\",\"value\":\"bar\"}]"),
+        Err(Error::TestFailed("/baz".to_string()))
+    );
+    assert_patch!("{\"foo\":\"bar\"}", "[{\"op\":\"add\",\"path\":\"/child\",\"value\":{\"grandchild\":{}}}]", "{\"foo\":\"bar\",\"child\":{\"grandchild\":{}}}");
+    assert_patch!("{\"foo\":\"bar\"}", "[{\"op\":\"add\",\"path\":\"/baz\",\"value\":\"qux\",\"xyz\":123}]", "{\"foo\":\"bar\",\"baz\":\"qux\"}");
+    assert_eq!(
+        apply("{\"foo\":\"bar\"}", "[{\"op\":\"add\",\"path\":\"/baz/bat\",\"value\":\"qux\"}]"),
+        Err(Error::MissingKey("/baz".to_string()))
+    );
+    assert_patch!("{\"/\":9,\"~1\":10}", "[{\"op\":\"test\",\"path\":\"/~01\",\"value\":10}]", "{\"/\":9,\"~1\":10}");
+    assert_eq!(
+        apply("{\"/\":9,\"~1\":10}", "[{\"op\":\"test\",\"path\":\"/~01\",\"value\":\"10\"}]"),
+        Err(Error::TestFailed("/~01".to_string()))
+    );
+    assert_patch!("{\"foo\":[\"bar\"]}", "[{\"op\":\"add\",\"path\":\"/foo/-\",\"value\":[\"abc\",\"def\"]}]", "{\"foo\":[\"bar\",[\"abc\",\"def\"]]}");
+}
+
+#[test]
+fn failed_patch_is_rolled_back()
+{
+    let mut json = Json::parse("{\"a\":1}").unwrap();
+    let patch    = Json::parse("[{\"op\":\"add\",\"path\":\"/b\",\"value\":2},{\"op\":\"remove\",\"path\":\"/c\"}]").unwrap();
+
+    assert_eq!(json.apply_patch(&patch), Err(Error::MissingKey("/c".to_string())));
+    assert_eq!(json.to_source(), "{\"a\":1}");
+}
+
+#[test]
+fn invalid_patches()
+{
+    for patch in &[
+        "{}",
+        "[1]",
+        "[{\"path\":\"/a\"}]",
+        "[{\"op\":\"frobnicate\",\"path\":\"/a\"}]",
+        "[{\"op\":\"add\",\"path\":\"/a\"}]",
+        "[{\"op\":\"move\",\"path\":\"/a\"}]",
+    ] {
+        let result = Patch::from_json(&Json::parse(patch).unwrap());
+        assert!(matches!(result, Err(Error::InvalidPatch(_))), "{} should be rejected", patch);
+    }
+
+    assert!(matches!(
+        apply("{\"a\":{\"b\":1}}", "[{\"op\":\"move\",\"from\":\"/a\",\"path\":\"/a/b/c\"}]"),
+        Err(Error::InvalidPatch(_))
+    ));
+    assert_eq!(
+        apply("{\"a\":1}", "[{\"op\":\"replace\",\"path\":\"/b\",\"value\":2}]"),
+        Err(Error::MissingKey("/b".to_string()))
+    );
+}
+
+#[test]
+fn copy_and_replace_root()
+{
+    assert_patch!("{\"a\":[1]}", "[{\"op\":\"copy\",\"from\":\"/a\",\"path\":\"/b\"}]", "{\"a\":[1],\"b\":[1]}");
+    assert_patch!("{\"a\":1}", "[{\"op\":\"replace\",\"path\":\"\",\"value\":[1]}]", "[1]");
+}
+
+#[test]
+fn diff_generates_minimal_patches()
+{
+    let from = Json::parse("{\"a\":1,\"b\":[1,2,3,4],\"c\":{\"d\":true},\"e\":\"x\"}").unwrap();
+    let to   = Json::parse("{\"a\":1,\"b\":[1,3,4,5],\"c\":{\"d\":false},\"f\":null}").unwrap();
+
+    let pointer = |text: &str| JsonPointer::parse(text).unwrap();
+
+    let patch = Patch::diff(&from, &to);
+    assert_eq!(patch.0, vec![
+        Operation::Remove { path: pointer("/b/1") },
+        Operation::Add { path: pointer("/b/3"), value: Json::from(5u64) },
+        Operation::Replace { path: pointer("/c/d"), value: Json::from(false) },
+        Operation::Remove { path: pointer("/e") },
+        Operation::Add { path: pointer("/f"), value: Json::from(()) },
+    ]);
+
+    let mut patched = from.clone();
+    patch.apply(&mut patched).unwrap();
+    assert_eq!(Patch::diff(&patched, &to), Patch::default());
+
+    assert_eq!(Patch::diff(&from, &from), Patch::default());
+    assert_eq!(Patch::diff(&Json::from(1), &Json::from(1.0)), Patch::default());
+}
+
+#[test]
+fn diff_round_trips()
+{
+    let cases = [
+        ("[1,2,3]", "[3,2,1]"),
+        ("[]", "[1,2]"),
+        ("[1,2]", "[]"),
+        ("[{\"a\":1},{\"b\":2}]", "[{\"a\":2},{\"b\":2},{\"c\":3}]"),
+        ("{\"a\":[1,[2,3]]}", "{\"a\":[1,[2,4],5]}"),
+        ("1", "{\"a\":1}"),
+    ];
+
+    for (from, to) in &cases {
+        let from = Json::parse(from).unwrap();
+        let to   = Json::parse(to).unwrap();
+
+        let mut patched = from.clone();
+        Patch::diff(&from, &to).apply(&mut patched).unwrap();
+        assert_eq!(Patch::diff(&patched, &to), Patch::default(), "{} -> {}", from, to);
+    }
+}
+
+#[test]
+fn to_json_round_trips_escaped_keys()
+{
+    let from = Json::parse("{\"a\\\"b\":1,\"c\\\\d\":[1],\"e/~f\":true}").unwrap();
+    let to   = Json::parse("{\"c\\\\d\":[1,2],\"e/~f\":false,\"g\\\"\":null}").unwrap();
+
+    let patch  = Patch::diff(&from, &to);
+    let parsed = Json::parse(&patch.to_json().to_source()).unwrap();
+    assert_eq!(Patch::from_json(&parsed).unwrap(), patch);
+
+    let mut patched = from.clone();
+    Patch::from_json(&parsed).unwrap().apply(&mut patched).unwrap();
+    assert_eq!(Patch::diff(&patched, &to), Patch::default());
+}
+
+// Cases from the json-patch-tests suite, https://github.com/json-patch/json-patch-tests.
+#[test]
+fn json_patch_test_suite()
+{
+    let valid = [
+        ("{}", "[]", "{}"),
+        ("{}", "[{\"op\":\"add\",\"path\":\"/foo\",\"value\":\"1\"}]", "{\"foo\":\"1\"}"),
+        ("{\"foo\":\"bar\"}", "[{\"op\":\"add\",\"path\":\"\",\"value\":{\"baz\":\"qux\"}}]", "{\"baz\":\"qux\"}"),
+        ("{}", "[{\"op\":\"add\",\"path\":\"/\",\"value\":1}]", "{\"\":1}"),
+        ("{\"foo\":{}}", "[{\"op\":\"add\",\"path\":\"/foo/\",\"value\":1}]", "{\"foo\":{\"\":1}}"),
+        ("{\"foo\":1}", "[{\"op\":\"add\",\"path\":\"/bar\",\"value\":[1,2]}]", "{\"foo\":1,\"bar\":[1,2]}"),
+        ("{\"foo\":1,\"baz\":[{\"qux\":\"hello\"}]}", "[{\"op\":\"add\",\"path\":\"/baz/0/foo\",\"value\":\"world\"}]", "{\"foo\":1,\"baz\":[{\"qux\":\"hello\",\"foo\":\"world\"}]}"),
+        ("[\"foo\",\"sil\"]", "[{\"op\":\"add\",\"path\":\"/1\",\"value\":[\"bar\",\"baz\"]}]", "[\"foo\",[\"bar\",\"baz\"],\"sil\"]"),
+        ("{\"1e0\":\"foo\"}", "[{\"op\":\"test\",\"path\":\"/1e0\",\"value\":\"foo\"}]", "{\"1e0\":\"foo\"}"),
+        ("{\"foo\":\"bar\"}", "[{\"op\":\"add\",\"path\":\"/FOO\",\"value\":\"BAR\"}]", "{\"foo\":\"bar\",\"FOO\":\"BAR\"}"),
+        ("{\"foo\":1}", "[{\"op\":\"move\",\"from\":\"/foo\",\"path\":\"/foo\"}]", "{\"foo\":1}"),
+        ("{\"foo\":{\"bar\":[1,2,5,4]}}", "[{\"op\":\"test\",\"path\":\"/foo\",\"value\":{\"bar\":[1,2,5,4]}}]", "{\"foo\":{\"bar\":[1,2,5,4]}}"),
+        ("{\"foo\":1}", "[{\"op\":\"test\",\"path\":\"\",\"value\":{\"foo\":1}}]", "{\"foo\":1}"),
+        ("{\"\":1}", "[{\"op\":\"test\",\"path\":\"/\",\"value\":1}]", "{\"\":1}"),
+        ("{\"foo\":null}", "[{\"op\":\"test\",\"path\":\"/foo\",\"value\":null}]", "{\"foo\":null}"),
+        ("{\"foo\":null}", "[{\"op\":\"replace\",\"path\":\"/foo\",\"value\":\"truthy\"}]", "{\"foo\":\"truthy\"}"),
+        ("{\"foo\":\"bar\"}", "[{\"op\":\"replace\",\"path\":\"/foo\",\"value\":null}]", "{\"foo\":null}"),
+        ("{\"foo\":null}", "[{\"op\":\"move\",\"from\":\"/foo\",\"path\":\"/bar\"}]", "{\"bar\":null}"),
+        ("{\"foo\":null}", "[{\"op\":\"copy\",\"from\":\"/foo\",\"path\":\"/bar\"}]", "{\"foo\":null,\"bar\":null}"),
+        ("{\"baz\":[{\"qux\":\"hello\"}],\"bar\":1}", "[{\"op\":\"move\",\"from\":\"/baz/0/qux\",\"path\":\"/baz/1\"}]", "{\"baz\":[{},\"hello\"],\"bar\":1}"),
+        ("[1,2,3,4]", "[{\"op\":\"remove\",\"path\":\"/0\"},{\"op\":\"add\",\"path\":\"/-\",\"value\":5}]", "[2,3,4,5]"),
+    ];
+
+    for (document, patch, expected) in &valid {
+        assert_patch!(document, patch, expected);
+    }
+
+    let invalid = [
+        ("{\"bar\":[1,2]}", "[{\"op\":\"add\",\"path\":\"/bar/8\",\"value\":\"5\"}]"),
+        ("{\"bar\":[1,2]}", "[{\"op\":\"add\",\"path\":\"/bar/-1\",\"value\":\"5\"}]"),
+        ("[\"foo\",\"sil\"]", "[{\"op\":\"add\",\"path\":\"/bar\",\"value\":42}]"),
+        ("[\"foo\",\"bar\"]", "[{\"op\":\"test\",\"path\":\"/00\",\"value\":\"foo\"}]"),
+        ("[\"foo\",\"bar\"]", "[{\"op\":\"test\",\"path\":\"/1e0\",\"value\":\"bar\"}]"),
+        ("{\"foo\":\"bar\"}", "[{\"op\":\"remove\",\"path\":\"/baz\"}]"),
+        ("{\"foo\":\"bar\"}", "[{\"op\":\"remove\",\"path\":\"/missing1/missing2\"}]"),
+        ("[\"foo\",\"bar\"]", "[{\"op\":\"remove\",\"path\":\"/2\"}]"),
+        ("{\"foo\":1,\"baz\":[{\"qux\":\"hello\"}]}", "[{\"op\":\"remove\",\"path\":\"/baz/1e0/qux\"}]"),
+        ("{\"bar\":\"baz\"}", "[{\"op\":\"replace\",\"path\":\"/foo/bar\",\"value\":false}]"),
+        ("{\"foo\":{\"bar\":[1,2,5,4]}}", "[{\"op\":\"test\",\"path\":\"/foo\",\"value\":[1,2]}]"),
+        ("[1]", "[{\"op\":\"test\",\"path\":\"/0\"}]"),
+        ("{\"foo\":1}", "[{\"op\":\"spam\",\"path\":\"/foo\",\"value\":1}]"),
+        ("{\"foo\":1}", "[{\"op\":\"copy\",\"from\":\"/bar\",\"path\":\"/baz\"}]"),
+    ];
+
+    for (document, patch) in &invalid {
+        assert!(apply(document, patch).is_err(), "{} should not apply to {}", patch, document);
+    }
+}
+
+#[test]
+fn diff_large_arrays()
+{
+    // Too large for a full table, the differing middle is replaced in place.
+    let from = Json::from((0..3000).map(|index| Json::from(index as u64)).collect::<Vec<Json>>());
+    let to   = Json::from((0..3000).map(|index| Json::from(if index % 2 == 0 { index as u64 } else { 0 })).collect::<Vec<Json>>());
+
+    let patch = Patch::diff(&from, &to);
+    assert!(patch.0.iter().all(|operation| matches!(operation, Operation::Replace { .. })));
+
+    let mut patched = from.clone();
+    patch.apply(&mut patched).unwrap();
+    assert_eq!(Patch::diff(&patched, &to), Patch::default());
+}
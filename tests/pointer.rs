@@ -0,0 +1,145 @@
+extern crate simple_json;
+use simple_json::{ Error, Json, JsonPointer };
+
+// The example document from RFC 6901, section 5.
+const RFC_DOCUMENT: &str = "{\"foo\":[\"bar\",\"baz\"],\"\":0,\"a/b\":1,\"c%d\":2,\"e^f\":3,\"g|h\":4,\"i\\\\j\":5,\"k\\\"l\":6,\" \":7,\"m~n\":8}";
+
+#[test]
+fn rfc_examples()
+{
+    let json = Json::parse(RFC_DOCUMENT).unwrap();
+
+    assert_eq!(json.pointer("").unwrap(), &json);
+    assert_eq!(json.pointer("/foo").unwrap().to_source(), "[\"bar\",\"baz\"]");
+    assert_eq!(json.pointer("/foo/0").unwrap().to_source(), "\"bar\"");
+    assert_eq!(json.pointer("/").unwrap().to_source(),      "0");
+    assert_eq!(json.pointer("/a~1b").unwrap().to_source(),  "1");
+    assert_eq!(json.pointer("/c%d").unwrap().to_source(),   "2");
+    assert_eq!(json.pointer("/e^f").unwrap().to_source(),   "3");
+    assert_eq!(json.pointer("/g|h").unwrap().to_source(),   "4");
+    assert_eq!(json.pointer("/i\\j").unwrap().to_source(),  "5");
+    assert_eq!(json.pointer("/k\"l").unwrap().to_source(),  "6");
+    assert_eq!(json.pointer("/ ").unwrap().to_source(),     "7");
+    assert_eq!(json.pointer("/m~0n").unwrap().to_source(),  "8");
+}
+
+#[test]
+fn escaping()
+{
+    let pointer = JsonPointer::parse("/a~1b/m~0n/~01").unwrap();
+    assert_eq!(pointer.tokens(), &["a/b", "m~n", "~1"]);
+    assert_eq!(pointer.to_string(), "/a~1b/m~0n/~01");
+
+    assert_eq!(JsonPointer::from_tokens(vec!["x/y", "~"]).to_string(), "/x~1y/~0");
+
+    assert_eq!(JsonPointer::parse("a"),     Err(Error::InvalidPointer("a".to_string())));
+    assert_eq!(JsonPointer::parse("/a~2"),  Err(Error::InvalidPointer("/a~2".to_string())));
+    assert_eq!(JsonPointer::parse("/a~"),   Err(Error::InvalidPointer("/a~".to_string())));
+}
+
+#[test]
+fn lookup_errors()
+{
+    let json = Json::parse("{\"a\":{\"b\":[1,2]},\"s\":\"x\"}").unwrap();
+
+    assert_eq!(json.pointer("/a/b/1").unwrap().to_source(), "2");
+    assert_eq!(json.pointer("/z"),     Err(Error::MissingKey("/z".to_string())));
+    assert_eq!(json.pointer("/a/c/0"), Err(Error::MissingKey("/a/c".to_string())));
+    assert_eq!(json.pointer("/a/b/2"), Err(Error::IndexOutOfRange("/a/b/2".to_string())));
+    assert_eq!(json.pointer("/a/b/-"), Err(Error::IndexOutOfRange("/a/b/-".to_string())));
+    assert_eq!(json.pointer("/a/b/01"), Err(Error::InvalidIndex("/a/b/01".to_string())));
+    assert_eq!(json.pointer("/a/b/x"), Err(Error::InvalidIndex("/a/b/x".to_string())));
+    assert_eq!(json.pointer("/s/0"),   Err(Error::NotAContainer("/s".to_string())));
+}
+
+#[test]
+fn pointer_mut()
+{
+    let mut json = Json::parse("{\"a\":[1,2]}").unwrap();
+
+    *json.pointer_mut("/a/0").unwrap() = Json::from(10);
+    assert_eq!(json.pointer("/a/0").unwrap(), &Json::from(10));
+}
+
+#[test]
+fn set_and_refresh_source()
+{
+    let mut json = Json::parse("{\"a\":[1,2]}").unwrap();
+
+    assert_eq!(json.set_pointer("/a/1", Json::from("x")), Ok(Some(Json::parse("2").unwrap())));
+    assert_eq!(json.to_source(), "{\"a\":[1,\"x\"]}");
+
+    assert_eq!(json.set_pointer("/a/-", Json::from(true)), Ok(None));
+    assert_eq!(json.set_pointer("/a/3", Json::from(())), Ok(None));
+    assert_eq!(json.to_source(), "{\"a\":[1,\"x\",true,null]}");
+
+    assert_eq!(json.set_pointer("/a/9", Json::from(1)), Err(Error::IndexOutOfRange("/a/9".to_string())));
+    assert_eq!(json.set_pointer("/b/c", Json::from(1)), Err(Error::MissingKey("/b".to_string())));
+
+    assert_eq!(json.set_pointer("", Json::from(1)).unwrap().unwrap().to_source(), "{\"a\":[1,\"x\",true,null]}");
+    assert_eq!(json, Json::from(1));
+}
+
+#[test]
+fn refreshed_sources_escape_keys()
+{
+    let mut json = Json::parse("{\"a\\\"b\":1,\"c\":[1]}").unwrap();
+
+    json.set_pointer("/c/0", Json::from(2)).unwrap();
+    assert!(Json::parse(&json.to_source()).unwrap().semantic_eq(&json));
+
+    json.remove_pointer("/c/0").unwrap();
+    assert_eq!(Json::parse(&json.to_source()).unwrap().pointer("/a\"b"), Ok(&Json::parse("1").unwrap()));
+}
+
+#[test]
+fn insert_shifts_elements()
+{
+    let mut json = Json::parse("[1,3]").unwrap();
+    let pointer  = JsonPointer::parse("/1").unwrap();
+
+    pointer.insert(&mut json, Json::from(2)).unwrap();
+    assert_eq!(json.to_source(), "[1,2,3]");
+}
+
+#[test]
+fn remove()
+{
+    let mut json = Json::parse("{\"a\":{\"b\":[1,2,3]}}").unwrap();
+
+    assert_eq!(json.remove_pointer("/a/b/0").unwrap().to_source(), "1");
+    assert_eq!(json.to_source(), "{\"a\":{\"b\":[2,3]}}");
+
+    assert_eq!(json.remove_pointer("/a/b").unwrap().to_source(), "[2,3]");
+    assert_eq!(json.to_source(), "{\"a\":{}}");
+
+    assert_eq!(json.remove_pointer("/a/b"), Err(Error::MissingKey("/a/b".to_string())));
+    assert_eq!(json.remove_pointer(""),     Err(Error::InvalidPointer("".to_string())));
+}
+
+#[test]
+fn set_in_source()
+{
+    let text = "{\n  \"a\": [ 1, 2 ],\n  \"b\": {}\n}\n";
+
+    let pointer = JsonPointer::parse("/a/1").unwrap();
+    assert_eq!(pointer.set_in_source(text, &Json::parse("true").unwrap()), Ok("{\n  \"a\": [ 1, true ],\n  \"b\": {}\n}\n".to_string()));
+
+    let pointer = JsonPointer::parse("/a/-").unwrap();
+    assert_eq!(pointer.set_in_source(text, &Json::parse("3").unwrap()), Ok("{\n  \"a\": [ 1, 2, 3 ],\n  \"b\": {}\n}\n".to_string()));
+
+    let pointer = JsonPointer::parse("/c").unwrap();
+    assert_eq!(pointer.set_in_source(text, &Json::parse("null").unwrap()), Ok("{\n  \"a\": [ 1, 2 ],\n  \"b\": {},\n  \"c\": null\n}\n".to_string()));
+
+    let pointer = JsonPointer::parse("/b/x").unwrap();
+    assert_eq!(pointer.set_in_source(text, &Json::parse("1").unwrap()), Ok("{\n  \"a\": [ 1, 2 ],\n  \"b\": {\"x\": 1}\n}\n".to_string()));
+
+    let pointer = JsonPointer::parse("").unwrap();
+    assert_eq!(pointer.set_in_source(" 1 ", &Json::parse("[]").unwrap()), Ok(" [] ".to_string()));
+
+    let pointer = JsonPointer::parse("/x/y").unwrap();
+    assert_eq!(pointer.set_in_source(text, &Json::parse("1").unwrap()), Err(Error::MissingKey("/x".to_string())));
+
+    let pointer = JsonPointer::parse("/a/5").unwrap();
+    assert_eq!(pointer.set_in_source(text, &Json::parse("1").unwrap()), Err(Error::IndexOutOfRange("/a/5".to_string())));
+}
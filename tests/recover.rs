@@ -0,0 +1,174 @@
+extern crate simple_json;
+use simple_json::{ Json, JsonPointer };
+use simple_json::recover::{ Diagnostic, Placeholder, Recovered };
+
+// Messages along with the text they cover.
+fn diagnostics<'a>(text: &'a str, diagnostics: &[Diagnostic]) -> Vec<(&'a str, String)>
+{
+    diagnostics.iter()
+        .map(|diagnostic| (&text[diagnostic.span.start.offset..diagnostic.span.end.offset], diagnostic.message.clone()))
+        .collect()
+}
+
+// Pointers of the placeholders along with the text they stand for.
+fn placeholders<'a>(text: &'a str, placeholders: &[Placeholder]) -> Vec<(String, &'a str)>
+{
+    placeholders.iter()
+        .map(|placeholder| (placeholder.pointer.to_string(), &text[placeholder.span.start.offset..placeholder.span.end.offset]))
+        .collect()
+}
+
+fn pointer(text: &str) -> JsonPointer
+{
+    JsonPointer::parse(text).unwrap()
+}
+
+#[test]
+fn valid()
+{
+    let text = "{ \"a\": [1, \"two\", null],\n  \"b\": { \"c\": true }, \"a\": { \"d\": [] } }";
+    let recovered = Json::parse_tolerant(text);
+
+    assert_eq!(recovered.diagnostics, vec![]);
+    assert_eq!(recovered.placeholders, vec![]);
+    assert_eq!((recovered.value, recovered.spans), Json::parse_with_spans(text).unwrap());
+}
+
+#[test]
+fn missing_values()
+{
+    let text = "{\"a\": , \"b\": [1, , 3,], \"c\": }";
+    let recovered = Json::parse_tolerant(text);
+    let (json, found) = (&recovered.value, &recovered.diagnostics);
+
+    assert_eq!(diagnostics(text, found), vec![
+        ("", "expected a value".to_string()),
+        ("", "expected a value".to_string()),
+        (",", "trailing comma".to_string()),
+        ("", "expected a value".to_string()),
+    ]);
+    assert_eq!(found[0].pointer, pointer("/a"));
+    assert_eq!(found[1].pointer, pointer("/b/1"));
+    assert_eq!(found[3].pointer, pointer("/c"));
+
+    assert_eq!(placeholders(text, &recovered.placeholders), vec![
+        ("/a".to_string(), ""), ("/b/1".to_string(), ""), ("/c".to_string(), ""),
+    ]);
+    assert_eq!(recovered.placeholders[1].span.start.column, 18);
+    assert!(json.pointer("/a").unwrap().is_null());
+    assert_eq!(json.pointer("/b/2").unwrap().to_source(), "3");
+    assert_eq!(json.pointer("/b").unwrap().as_array().unwrap().len(), 3);
+    assert_eq!(recovered.spans.value(&pointer("/a")), None);
+    assert_eq!(recovered.spans.key(&pointer("/a")).unwrap().start.column, 2);
+}
+
+#[test]
+fn invalid_values()
+{
+    let text = "[tru, 1, @x, {\"k\": nul}, \"s\"]";
+    let recovered = Json::parse_tolerant(text);
+    let (json, found) = (&recovered.value, &recovered.diagnostics);
+
+    assert_eq!(diagnostics(text, found), vec![
+        ("tru", "invalid value: invalid character `,`".to_string()),
+        ("@x", "unexpected character `@`".to_string()),
+        ("nul", "invalid value: invalid character `}`".to_string()),
+    ]);
+    assert_eq!(found[2].pointer, pointer("/3/k"));
+
+    assert_eq!(placeholders(text, &recovered.placeholders), vec![
+        ("/0".to_string(), "tru"), ("/2".to_string(), "@x"), ("/3/k".to_string(), "nul"),
+    ]);
+    assert_eq!(json.pointer("/0").unwrap(), &Json::from(()));
+    assert_eq!(json.pointer("/1").unwrap().to_source(), "1");
+    assert_eq!(json.pointer("/4").unwrap().as_str(), Some("s"));
+}
+
+#[test]
+fn partial_numbers()
+{
+    let text = "[-, -e, 1., 1e, -.e1, 2]";
+    let recovered = Json::parse_tolerant(text);
+
+    assert_eq!(diagnostics(text, &recovered.diagnostics), vec![
+        ("-", "invalid value: invalid character `,`".to_string()),
+        ("-e", "invalid value: invalid character `e`".to_string()),
+        ("1.", "invalid value: invalid character `,`".to_string()),
+        ("1e", "invalid value: invalid character `,`".to_string()),
+        ("-.e1", "invalid value: invalid character `.`".to_string()),
+    ]);
+    assert_eq!(recovered.placeholders.len(), 5);
+    assert_eq!(recovered.value.pointer("/5").unwrap().to_source(), "2");
+
+    for text in &["[-e1]", "-", "1e"] {
+        assert_eq!(Json::parse_tolerant(text).diagnostics.len(), 1, "{}", text);
+    }
+}
+
+#[test]
+fn strings()
+{
+    let text = "{\n  \"a\": \"open,\n  \"b\": \"bad \\x escape\", \"c\": 1,\n  \"d: 2\n}";
+    let recovered = Json::parse_tolerant(text);
+    let (json, found) = (&recovered.value, &recovered.diagnostics);
+
+    assert_eq!(diagnostics(text, found), vec![
+        ("\"open,", "unterminated string".to_string()),
+        // The comma went with the string.
+        ("", "expected `,`".to_string()),
+        ("\"bad \\x escape\"", "invalid string: invalid character `x`".to_string()),
+        ("\"d: 2", "unterminated string".to_string()),
+        ("", "expected `:`".to_string()),
+        ("", "expected a value".to_string()),
+    ]);
+    assert_eq!((found[2].span.start.line, found[2].span.start.column), (3, 8));
+
+    assert_eq!(placeholders(text, &recovered.placeholders), vec![
+        ("/a".to_string(), "\"open,"), ("/b".to_string(), "\"bad \\x escape\""), ("/d: 2".to_string(), ""),
+    ]);
+    assert_eq!((recovered.placeholders[0].span.start.line, recovered.placeholders[0].span.start.column), (2, 8));
+    assert_eq!(json.pointer("/c").unwrap().to_source(), "1");
+    assert!(json.pointer("/d: 2").is_ok());
+    assert_eq!(&text[recovered.spans.key(&pointer("/d: 2")).unwrap().start.offset..][..5], "\"d: 2");
+}
+
+#[test]
+fn structure()
+{
+    let text = "{\"a\": [1, 2 \"b\": 3 4, x: 5,}";
+    let Recovered { value: json, diagnostics: found, placeholders, .. } = Json::parse_tolerant(text);
+
+    // The array isn't closed, so it takes everything up to the `}`.
+    assert_eq!(diagnostics(text, &found), vec![
+        ("", "expected `,`".to_string()),
+        (": 3 4", "expected `,` or `]`".to_string()),
+        ("x: 5", "unexpected character `x`".to_string()),
+        ("", "expected `]`".to_string()),
+    ]);
+    assert_eq!(json.pointer("/a/2").unwrap().as_str(), Some("b"));
+    assert_eq!(placeholders[0].pointer, pointer("/a/3"));
+    assert_eq!(&text[placeholders[0].span.start.offset..placeholders[0].span.end.offset], "x: 5");
+
+    let text = "{\"a\": 1 \"b\": 2, x: 5, \"c\": 3,}";
+    let Recovered { value: json, diagnostics: found, .. } = Json::parse_tolerant(text);
+
+    assert_eq!(diagnostics(text, &found), vec![
+        ("", "expected `,`".to_string()),
+        ("x: 5", "expected a key".to_string()),
+        (",", "trailing comma".to_string()),
+    ]);
+    assert_eq!(json.pointer("/c").unwrap().to_source(), "3");
+
+    // Unclosed containers end with the text.
+    let Recovered { value: json, diagnostics: found, .. } = Json::parse_tolerant("[{\"a\": [1");
+    assert_eq!(found.iter().map(|found| found.message.as_str()).collect::<Vec<&str>>(), vec!["expected `]`", "expected `}`", "expected `]`"]);
+    assert_eq!(json.pointer("/0/a/0").unwrap().to_source(), "1");
+
+    let Recovered { value: json, diagnostics: found, placeholders, .. } = Json::parse_tolerant("");
+    assert_eq!(json, Json::from(()));
+    assert_eq!(placeholders[0].pointer, pointer(""));
+    assert_eq!(found[0].message, "expected a value");
+
+    let found = Json::parse_tolerant("[] []").diagnostics;
+    assert_eq!(diagnostics("[] []", &found), vec![("[]", "unexpected text after the document".to_string())]);
+}
@@ -0,0 +1,287 @@
+extern crate simple_json;
+use simple_json::schema::{ Schema, ValidationError };
+use simple_json::{ Json, JsonPointer };
+
+fn parse(text: &str) -> Json
+{
+    Json::parse(text).unwrap()
+}
+
+fn schema(text: &str) -> Schema
+{
+    Schema::new(parse(text)).unwrap()
+}
+
+fn errors(schema_text: &str, instance: &str) -> Vec<(String, String)>
+{
+    match schema(schema_text).validate(&parse(instance)) {
+        Ok(()) => vec![],
+        Err(errors) => errors.into_iter().map(|error| (error.instance_path.to_string(), error.schema_path.to_string())).collect(),
+    }
+}
+
+fn valid(schema_text: &str, instance: &str) -> bool
+{
+    schema(schema_text).is_valid(&parse(instance))
+}
+
+#[test]
+fn types()
+{
+    assert!(valid("{\"type\":\"string\"}", "\"a\""));
+    assert!(!valid("{\"type\":\"string\"}", "1"));
+    assert!(valid("{\"type\":\"integer\"}", "1.0"));
+    assert!(!valid("{\"type\":\"integer\"}", "1.5"));
+    assert!(valid("{\"type\":\"number\"}", "-1"));
+    assert!(valid("{\"type\":[\"null\",\"boolean\"]}", "null"));
+    assert!(!valid("{\"type\":[\"null\",\"boolean\"]}", "{}"));
+    assert!(valid("true", "{}"));
+    assert!(!valid("false", "{}"));
+}
+
+#[test]
+fn enum_and_const()
+{
+    assert!(valid("{\"enum\":[1,\"a\",{\"b\":[true]}]}", "{\"b\":[true]}"));
+    assert!(valid("{\"enum\":[1,\"a\"]}", "1.0"));
+    assert!(!valid("{\"enum\":[1,\"a\"]}", "\"b\""));
+    assert!(valid("{\"const\":[1,2]}", "[1,2]"));
+    assert!(!valid("{\"const\":[1,2]}", "[2,1]"));
+}
+
+#[test]
+fn numeric_bounds()
+{
+    let bounds = "{\"minimum\":1,\"exclusiveMaximum\":10,\"multipleOf\":0.5}";
+
+    assert!(valid(bounds, "1"));
+    assert!(valid(bounds, "9.5"));
+    assert!(!valid(bounds, "0.5"));
+    assert!(!valid(bounds, "10"));
+    assert!(!valid(bounds, "2.25"));
+    assert!(valid("{\"maximum\":-1,\"exclusiveMinimum\":-3}", "-2"));
+    assert!(!valid("{\"multipleOf\":3}", "10"));
+
+    // Multiples are checked on the decimal values.
+    assert!(valid("{\"multipleOf\":0.1}", "0.3"));
+    assert!(valid("{\"multipleOf\":0.01}", "19.99"));
+    assert!(valid("{\"multipleOf\":1e-300}", "7"));
+    assert!(valid("{\"multipleOf\":2}", "1e300"));
+    assert!(!valid("{\"multipleOf\":3}", "1e300"));
+    assert!(!valid("{\"multipleOf\":0.3}", "0.1"));
+    assert!(!valid("{\"multipleOf\":1e300}", "7"));
+}
+
+#[test]
+fn string_bounds_and_patterns()
+{
+    let strings = "{\"minLength\":2,\"maxLength\":3,\"pattern\":\"^[a-z]+\\\\d?$\"}";
+
+    assert!(valid(strings, "\"ab\""));
+    assert!(valid(strings, "\"ab1\""));
+    assert!(!valid(strings, "\"a\""));
+    assert!(!valid(strings, "\"abcd\""));
+    assert!(!valid(strings, "\"A1\""));
+    assert!(valid("{\"maxLength\":1}", "\"é\""));
+    assert!(valid("{\"pattern\":\"b\"}", "\"abc\""));
+}
+
+#[test]
+fn objects()
+{
+    let object = concat!(
+        "{\"properties\":{\"a\":{\"type\":\"integer\"},\"b\":{\"type\":\"string\"}},",
+        "\"patternProperties\":{\"^x-\":{\"type\":\"boolean\"}},",
+        "\"additionalProperties\":false,\"required\":[\"a\"]}",
+    );
+
+    assert!(valid(object, "{\"a\":1,\"x-flag\":true}"));
+    assert_eq!(errors(object, "{\"b\":2,\"x-flag\":1,\"c\":null}"), vec![
+        ("/b".to_string(), "/properties/b/type".to_string()),
+        ("/c".to_string(), "/additionalProperties".to_string()),
+        ("/x-flag".to_string(), "/patternProperties/^x-/type".to_string()),
+        ("".to_string(), "/required".to_string()),
+    ]);
+
+    assert!(!valid("{\"minProperties\":1}", "{}"));
+    assert!(!valid("{\"maxProperties\":1}", "{\"a\":1,\"b\":2}"));
+    assert!(valid("{\"additionalProperties\":{\"type\":\"number\"}}", "{\"a\":1}"));
+}
+
+#[test]
+fn arrays()
+{
+    let array = "{\"prefixItems\":[{\"type\":\"string\"}],\"items\":{\"type\":\"number\"},\"minItems\":1,\"maxItems\":3}";
+
+    assert!(valid(array, "[\"a\",1,2]"));
+    assert_eq!(errors(array, "[1,\"b\"]"), vec![
+        ("/0".to_string(), "/prefixItems/0/type".to_string()),
+        ("/1".to_string(), "/items/type".to_string()),
+    ]);
+    assert!(!valid(array, "[]"));
+    assert!(!valid(array, "[\"a\",1,2,3]"));
+    assert!(!valid("{\"uniqueItems\":true}", "[1,{\"a\":1},1.0]"));
+    assert!(valid("{\"uniqueItems\":true}", "[1,\"1\",[1]]"));
+}
+
+#[test]
+fn combinators()
+{
+    assert!(valid("{\"allOf\":[{\"type\":\"integer\"},{\"minimum\":2}]}", "3"));
+    assert_eq!(errors("{\"allOf\":[{\"type\":\"integer\"},{\"minimum\":2}]}", "1"), vec![
+        ("".to_string(), "/allOf/1/minimum".to_string()),
+    ]);
+
+    assert!(valid("{\"anyOf\":[{\"type\":\"string\"},{\"minimum\":2}]}", "\"x\""));
+    assert_eq!(errors("{\"anyOf\":[{\"type\":\"string\"},{\"minimum\":2}]}", "1"), vec![
+        ("".to_string(), "/anyOf".to_string()),
+    ]);
+
+    assert!(valid("{\"oneOf\":[{\"type\":\"integer\"},{\"minimum\":2}]}", "1"));
+    assert!(!valid("{\"oneOf\":[{\"type\":\"integer\"},{\"minimum\":2}]}", "3"));
+    assert!(!valid("{\"not\":{\"type\":\"null\"}}", "null"));
+}
+
+#[test]
+fn references()
+{
+    let tree = concat!(
+        "{\"$defs\":{\"node\":{\"type\":\"object\",\"properties\":{",
+        "\"value\":{\"type\":\"integer\"},\"children\":{\"type\":\"array\",\"items\":{\"$ref\":\"#/$defs/node\"}}}}},",
+        "\"$ref\":\"#/$defs/node\"}",
+    );
+
+    assert!(valid(tree, "{\"value\":1,\"children\":[{\"value\":2,\"children\":[]}]}"));
+    assert_eq!(errors(tree, "{\"value\":1,\"children\":[{\"value\":\"2\"}]}"), vec![(
+        "/children/0/value".to_string(),
+        "/$ref/properties/children/items/$ref/properties/value/type".to_string(),
+    )]);
+
+    assert!(valid("{\"items\":{\"$ref\":\"#\"},\"type\":\"array\"}", "[[[]]]"));
+
+    // Patterns only reached through a `$ref` are compiled too.
+    let target = "{\"x\":{\"type\":\"string\",\"pattern\":\"^a\"},\"$ref\":\"#/x\"}";
+    assert!(valid(target, "\"abc\""));
+    assert_eq!(errors(target, "\"bbb\""), vec![("".to_string(), "/$ref/pattern".to_string())]);
+
+    let keys = "{\"x\":{\"patternProperties\":{\"^a\":{\"type\":\"null\"}}},\"y\":{\"$ref\":\"#/x\"},\"$ref\":\"#/y\"}";
+    assert_eq!(errors(keys, "{\"ab\":1,\"b\":1}"), vec![("/ab".to_string(), "/$ref/$ref/patternProperties/^a/type".to_string())]);
+    assert!(Schema::new(parse("{\"x\":{\"pattern\":\"(\"},\"$ref\":\"#/x\"}")).is_err());
+    assert!(valid("{\"$ref\":\"#/definitions/a%20b\",\"definitions\":{\"a b\":{\"type\":\"null\"}}}", "null"));
+}
+
+#[test]
+fn reference_cycles_stop()
+{
+    assert!(!valid("{\"$defs\":{\"a\":{\"$ref\":\"#/$defs/a\"}},\"$ref\":\"#/$defs/a\"}", "1"));
+    assert_eq!(errors("{\"$ref\":\"#\"}", "1"), vec![("".to_string(), "/$ref".to_string())]);
+
+    // Branching cycles end as quickly.
+    assert!(!valid("{\"anyOf\":[{\"$ref\":\"#\"},{\"$ref\":\"#\"}]}", "1"));
+    assert!(!valid("{\"allOf\":[{\"$ref\":\"#\"},{\"$ref\":\"#\"}]}", "1"));
+    assert!(valid("{\"oneOf\":[{\"not\":{\"$ref\":\"#\"}},{\"type\":\"null\"}]}", "1"));
+}
+
+#[test]
+fn invalid_schemas()
+{
+    let error = Schema::new(parse("{\"properties\":{\"a\":{\"pattern\":\"(\"}}}")).unwrap_err();
+    assert_eq!(error.path, JsonPointer::parse("/properties/a/pattern").unwrap());
+
+    let error = Schema::new(parse("{\"items\":{\"$ref\":\"#/$defs/missing\"}}")).unwrap_err();
+    assert_eq!(error.path, JsonPointer::parse("/items/$ref").unwrap());
+
+    assert!(Schema::new(parse("{\"not\":1}")).is_err());
+}
+
+#[test]
+fn error_positions()
+{
+    let schema = schema("{\"properties\":{\"list\":{\"items\":{\"type\":\"string\"}},\"name\":{\"type\":\"string\"}}}");
+    let instance = parse("{\n  \"name\":\"x\",\n  \"list\":[\"a\",\n    2, \"b\",\n    null]\n}");
+
+    let errors: Vec<ValidationError> = instance.validate(&schema).unwrap_err();
+    let positions: Vec<(String, usize, usize)> = errors.iter()
+        .map(|error| (error.instance_path.to_string(), error.line, error.column))
+        .collect();
+
+    assert_eq!(positions, vec![("/list/1".to_string(), 4, 5), ("/list/3".to_string(), 5, 5)]);
+    assert_eq!(errors[0].to_string(), "4:5: expected string, found number (/properties/list/items/type)");
+
+    // Positions in the parsed text count the lines before the document.
+    let text = "\n\n  {\"name\":\n 1}";
+    let (instance, spans) = Json::parse_with_spans(text).unwrap();
+    let error = &schema.validate_with_spans(&instance, &spans).unwrap_err()[0];
+    assert_eq!((error.line, error.column), (4, 2));
+
+    let error = &schema.validate(&instance).unwrap_err()[0];
+    assert_eq!((error.line, error.column), (2, 2));
+}
+
+#[test]
+fn pointer_offsets()
+{
+    let json = parse("{\"a\" :[1, {\"b\":true} ],\n\"a\":{\"c\":\"d\"}}");
+
+    assert_eq!(JsonPointer::parse("").unwrap().offset(&json), Some(0));
+    assert_eq!(JsonPointer::parse("/a/c").unwrap().offset(&json), Some(33));
+    assert_eq!(JsonPointer::parse("/a/x").unwrap().offset(&json), None);
+
+    let json = parse("[1, {\"b\":true} ]");
+    assert_eq!(JsonPointer::parse("/1/b").unwrap().offset(&json), Some(9));
+}
+
+#[test]
+fn inferred_schema()
+{
+    use simple_json::schema::infer;
+
+    let samples = vec![
+        parse("{\"id\":1,\"kind\":\"user\",\"score\":2,\"tags\":[\"a\"],\"meta\":null}"),
+        parse("{\"id\":2,\"kind\":\"admin\",\"score\":2.5,\"tags\":[],\"meta\":{\"x\":true}}"),
+        parse("{\"id\":3,\"kind\":\"user\",\"tags\":[\"b\",\"c\"],\"meta\":null}"),
+        parse("{\"id\":4,\"kind\":\"admin\",\"tags\":[\"d\"],\"meta\":null}"),
+    ];
+
+    let inferred = infer(&samples);
+
+    assert_eq!(inferred.to_source(), concat!(
+        "{\"$schema\":\"https://json-schema.org/draft/2020-12/schema\",\"type\":\"object\",\"properties\":{",
+        "\"id\":{\"type\":\"integer\"},",
+        "\"kind\":{\"type\":\"string\",\"enum\":[\"user\",\"admin\"]},",
+        "\"meta\":{\"type\":[\"null\",\"object\"],\"properties\":{\"x\":{\"type\":\"boolean\"}},\"required\":[\"x\"]},",
+        "\"score\":{\"type\":\"number\"},",
+        "\"tags\":{\"type\":\"array\",\"items\":{\"type\":\"string\"}}},",
+        "\"required\":[\"id\",\"kind\",\"meta\",\"tags\"]}",
+    ));
+
+    let schema = Schema::new(inferred).unwrap();
+    for sample in &samples {
+        assert!(schema.is_valid(sample));
+    }
+
+    assert!(!schema.is_valid(&parse("{\"id\":5,\"kind\":\"guest\",\"tags\":[],\"meta\":null}")));
+    assert!(!schema.is_valid(&parse("{\"id\":1.5,\"kind\":\"user\",\"tags\":[],\"meta\":null}")));
+}
+
+#[test]
+fn inferred_scalars()
+{
+    use simple_json::schema::{ infer, infer_with, InferOptions };
+
+    assert_eq!(infer(&[]).to_source(), "{\"$schema\":\"https://json-schema.org/draft/2020-12/schema\"}");
+    assert_eq!(infer(&[parse("1"), parse("\"a\""), parse("true")]).to_source(), concat!(
+        "{\"$schema\":\"https://json-schema.org/draft/2020-12/schema\",",
+        "\"type\":[\"boolean\",\"integer\",\"string\"]}",
+    ));
+
+    // Unique strings are not an enum, unless asked for.
+    assert_eq!(infer(&[parse("\"a\""), parse("\"b\"")]).pointer("/enum"), Err(simple_json::Error::MissingKey("/enum".to_string())));
+
+    let options = InferOptions { max_enum_values: 2, min_enum_repeats: 1 };
+    assert_eq!(infer_with(&[parse("\"a\""), parse("null")], &options).pointer("/enum").unwrap().to_source(), "[\"a\",null]");
+
+    // Many distinct strings are no enum, and are not all kept.
+    let samples: Vec<Json> = (0..50_000).map(|index| Json::from(format!("{}", index % 25_000))).collect();
+    assert!(infer_with(&samples, &options).pointer("/enum").is_err());
+}
@@ -0,0 +1,165 @@
+#![cfg(feature = "serde")]
+
+use std::collections::{ BTreeMap, HashMap };
+
+extern crate serde;
+extern crate simple_json;
+use serde::{ Deserialize, Serialize };
+use simple_json::serde::{ to_json, Error };
+use simple_json::{ from_str, to_string, Json, Number };
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Point
+{
+    x: i32,
+    y: i32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Shape
+{
+    name:   String,
+    points: Vec<Point>,
+    closed: bool,
+    scale:  Option<f64>,
+    kind:   Kind,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Kind {
+    Plain,
+    Colored(String),
+    Pair(u8, u8),
+    Styled { width: u32 },
+}
+
+fn shape() -> Shape
+{
+    Shape {
+        name:   "tri\"angle\"".to_string(),
+        points: vec![Point { x: 0, y: 0 }, Point { x: -1, y: 2 }],
+        closed: true,
+        scale:  None,
+        kind:   Kind::Styled { width: 3 },
+    }
+}
+
+#[test]
+fn to_string_keeps_field_order()
+{
+    assert_eq!(to_string(&shape()).unwrap(), concat!(
+        "{\"name\":\"tri\\\"angle\\\"\",\"points\":[{\"x\":0,\"y\":0},{\"x\":-1,\"y\":2}],",
+        "\"closed\":true,\"scale\":null,\"kind\":{\"Styled\":{\"width\":3}}}",
+    ));
+}
+
+#[test]
+fn round_trip()
+{
+    let text = to_string(&shape()).unwrap();
+    assert_eq!(from_str::<Shape>(&text).unwrap(), shape());
+}
+
+#[test]
+fn enum_representations()
+{
+    for kind in [Kind::Plain, Kind::Colored("red".to_string()), Kind::Pair(1, 2), Kind::Styled { width: 0 }] {
+        let text = to_string(&kind).unwrap();
+        assert_eq!(from_str::<Kind>(&text).unwrap(), kind);
+    }
+
+    assert_eq!(to_string(&Kind::Plain).unwrap(), "\"Plain\"");
+    assert_eq!(to_string(&Kind::Pair(1, 2)).unwrap(), "{\"Pair\":[1,2]}");
+}
+
+#[test]
+fn from_str_with_whitespace()
+{
+    let point: Point = from_str(" {\n  \"y\" : 2 ,\n  \"x\": 1\n}\n").unwrap();
+    assert_eq!(point, Point { x: 1, y: 2 });
+
+    let map: BTreeMap<String, Vec<Option<u8>>> = from_str("{\"a\":[1,null],\"b\":[]}").unwrap();
+    assert_eq!(map["a"], vec![Some(1), None]);
+    assert!(map["b"].is_empty());
+}
+
+#[test]
+fn json_values()
+{
+    let json: Json = from_str("{\"a\":[1,-2,3.5,\"x\\n\",true,null]}").unwrap();
+
+    assert_eq!(json, Json::parse("{\"a\":[1,-2,3.5,\"x\\n\",true,null]}").unwrap());
+    assert_eq!(to_string(&json).unwrap(), "{\"a\":[1,-2,3.5,\"x\\n\",true,null]}");
+
+    let number: Number = from_str("-7").unwrap();
+    assert_eq!(number, Number::Integer(-7));
+    assert_eq!(to_json(&Point { x: 1, y: 2 }).unwrap(), Json::parse("{\"x\":1,\"y\":2}").unwrap());
+}
+
+fn position(error: Error) -> (usize, usize)
+{
+    (error.line, error.column)
+}
+
+#[test]
+fn syntax_errors_have_positions()
+{
+    assert_eq!(position(from_str::<Json>("{\"a\":1,\n \"b\" 2}").unwrap_err()), (2, 6));
+    assert_eq!(position(from_str::<Json>("[1,\n  tru]").unwrap_err()), (2, 6));
+    assert_eq!(position(from_str::<Json>("[1,]").unwrap_err()), (1, 4));
+    assert_eq!(position(from_str::<Json>("\"abc").unwrap_err()), (1, 5));
+    assert_eq!(position(from_str::<Json>("1 2").unwrap_err()), (1, 3));
+
+    let error = from_str::<Json>("[1,\n  tru]").unwrap_err();
+    assert_eq!(error.to_string(), "invalid character `]` at line 2 column 6");
+}
+
+#[test]
+fn data_errors_have_positions()
+{
+    let error = from_str::<Point>("{\"x\":1,\n\"y\":\"2\"}").unwrap_err();
+
+    assert_eq!(position(error.clone()), (2, 8));
+    assert!(error.message.starts_with("invalid type: string \"2\""));
+
+    let error = from_str::<Point>("{\"x\":1}").unwrap_err();
+    assert_eq!(error.message, "missing field `y`");
+    assert_eq!(position(error), (1, 7));
+
+    assert!(from_str::<u8>("300").is_err());
+
+    let error = from_str::<Vec<f64>>("[1,\n 1e400]").unwrap_err();
+    assert_eq!((error.message.as_str(), position(error.clone())), ("number out of range", (2, 2)));
+    assert!(from_str::<Json>("-1e400").is_err());
+    assert_eq!(from_str::<f64>("1e308").unwrap(), 1e308);
+
+    let error = from_str::<HashMap<u8, i32>>("{\"1\":1,\n \"x\":2}").unwrap_err();
+    assert_eq!((error.message.as_str(), position(error.clone())), ("invalid key \"x\"", (2, 2)));
+}
+
+#[test]
+fn numeric_and_boolean_keys()
+{
+    let map: HashMap<i32, i32> = from_str("{\"1\":2, \"-3\": 4}").unwrap();
+    assert_eq!(map, vec![(1, 2), (-3, 4)].into_iter().collect());
+
+    let mut numbers = BTreeMap::new();
+    numbers.insert(10u64, "a".to_string());
+    numbers.insert(2u64, "b".to_string());
+    assert_eq!(from_str::<BTreeMap<u64, String>>(&to_string(&numbers).unwrap()).unwrap(), numbers);
+
+    let mut flags = BTreeMap::new();
+    flags.insert(true, 1.5f64);
+    flags.insert(false, -2.);
+    assert_eq!(from_str::<BTreeMap<bool, f64>>(&to_string(&flags).unwrap()).unwrap(), flags);
+
+    let floats: BTreeMap<String, f64> = from_str("{\"0.5\": 1}").unwrap();
+    assert_eq!(floats.get("0.5"), Some(&1.));
+    assert_eq!(from_str::<HashMap<String, u8>>("{\"a\": 1}").unwrap().get("a"), Some(&1));
+}
+
+#[test]
+fn non_finite_floats_become_null()
+{
+    assert_eq!(to_string(&vec![f64::NAN, 1.5]).unwrap(), "[null,1.5]");
+}
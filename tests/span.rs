@@ -0,0 +1,88 @@
+extern crate simple_json;
+use simple_json::{ Json, JsonPointer };
+use simple_json::span::{ Position, Span };
+
+const DOCUMENT: &str = "\n{\n  \"name\": \"caf\u{e9}\",\n  \"tags\": [ 1, [ true ] ],\n  \"dup\": 1, \"dup\": { \"x\": null }\n}\n";
+
+fn pointer(text: &str) -> JsonPointer
+{
+    JsonPointer::parse(text).unwrap()
+}
+
+fn text(span: Span) -> &'static str
+{
+    &DOCUMENT[span.start.offset..span.end.offset]
+}
+
+#[test]
+fn values()
+{
+    let (json, spans) = Json::parse_with_spans(DOCUMENT).unwrap();
+
+    let root = spans.value(&pointer("")).unwrap();
+    assert_eq!(root.start, Position { offset: 1, line: 2, column: 1 });
+    assert_eq!(root.end, Position { offset: DOCUMENT.len() - 1, line: 6, column: 2 });
+
+    let name = spans.value(&pointer("/name")).unwrap();
+    assert_eq!(text(name), "\"caf\u{e9}\"");
+    assert_eq!((name.start.line, name.start.column, name.end.column), (3, 11, 17));
+
+    assert_eq!(text(spans.value(&pointer("/tags/1/0")).unwrap()), "true");
+    assert_eq!(text(spans.value(&pointer("/dup/x")).unwrap()), "null");
+    assert_eq!(spans.value(&pointer("/missing")), None);
+
+    // Every node of the tree has a span.
+    assert_eq!(spans.iter().count(), 8);
+    for (pointer, span) in spans.iter() {
+        assert_eq!(text(*span), pointer.resolve(&json).unwrap().to_source());
+    }
+}
+
+#[test]
+fn keys()
+{
+    let (_, spans) = Json::parse_with_spans(DOCUMENT).unwrap();
+
+    let key = spans.key(&pointer("/tags")).unwrap();
+    assert_eq!(text(key), "\"tags\"");
+    assert_eq!((key.start.line, key.start.column), (4, 3));
+
+    // The last occurrence of a repeated key.
+    assert_eq!(spans.key(&pointer("/dup")).unwrap().start.column, 13);
+    assert_eq!(spans.key(&pointer("")), None);
+    assert_eq!(spans.key(&pointer("/tags/0")), None);
+}
+
+#[test]
+fn repeated_keys_and_long_lines()
+{
+    let (_, spans) = Json::parse_with_spans("{\"d\": {\"y\": 1}, \"d\": [2]}").unwrap();
+    assert_eq!(spans.value(&pointer("/d/y")), None);
+    assert_eq!(spans.value(&pointer("/d/0")).unwrap().start.column, 23);
+    assert_eq!(spans.iter().count(), 3);
+
+    // Positions come from a single pass over the text.
+    let text = format!("[{}\"\u{e9}\\\"\", 7]", "1, ".repeat(100_000));
+    let (_, spans) = Json::parse_with_spans(&text).unwrap();
+    let last = spans.value(&pointer("/100001")).unwrap();
+    assert_eq!((last.start.line, last.start.column, last.end.offset), (1, 300_009, text.len() - 1));
+}
+
+#[test]
+fn at()
+{
+    let (_, spans) = Json::parse_with_spans(DOCUMENT).unwrap();
+
+    let offset = DOCUMENT.find("true").unwrap();
+    assert_eq!(spans.at(offset), Some(pointer("/tags/1/0")));
+    assert_eq!(spans.at(offset - 1), Some(pointer("/tags/1")));
+    assert_eq!(spans.at(DOCUMENT.find("name").unwrap()), Some(pointer("/name")));
+    assert_eq!(spans.at(0), None);
+}
+
+#[test]
+fn errors()
+{
+    let error = Json::parse_with_spans("[1, }").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 5));
+}
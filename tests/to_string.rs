@@ -69,7 +69,7 @@ fn string_to_string()
 
 	test!(
         "I can escape some things, like \"\\/\u{0008}\u{000C}\n\r\t!",
-        "\"I can escape some things, like \"\\/\u{0008}\u{000C}\n\r\t!\""
+        "\"I can escape some things, like \\\"\\\\/\\b\\f\\n\\r\\t!\""
     );
 
 	test!(
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 extern crate simple_json;
-use simple_json::{ Json, Number };
+use simple_json::{ Error, Json, Number, ParseError };
 
 macro_rules! test {
     ($T: ty, $jsn:expr, $val:expr) => {{
@@ -68,6 +68,35 @@ fn valid_exponent_number()
     test!(f64, "-21.12e2",  -21.12e2);
 }
 
+#[test]
+fn valid_edge_numbers()
+{
+    test!(f64, "1e0",  1.);
+    test!(f64, "1e-0", 1.);
+    test!(f64, "1e05", 1e5);
+    test!(f64, "1E00", 1.);
+
+    // Integers too big for 64 bits become floats.
+    test!(f64, "18446744073709551616",  18446744073709551616.);
+    test!(f64, "-9223372036854775809", -9223372036854775809.);
+}
+
+#[test]
+fn invalid_truncated_values()
+{
+    for text in &["-", "1.", "1e", "1e+", "\"abc", "\"a\\"] {
+        assert_eq!(Json::parse(text), Err(simple_json::Error::UnexpectedEof), "{}", text);
+    }
+}
+
+#[test]
+fn invalid_numbers()
+{
+    for (text, character) in &[("-e1", "e"), ("-.e1", "."), ("-.5", "."), ("1.e5", "e"), ("-E", "E")] {
+        assert_eq!(Json::parse(text), Err(simple_json::Error::InvalidCharacter(character.to_string())), "{}", text);
+    }
+}
+
 #[test]
 fn valid_string()
 {
@@ -97,6 +126,23 @@ fn valid_string()
     );
 }
 
+#[test]
+fn valid_unicode_escapes()
+{
+    test!(String, "\"\\ud83d\\ude00\"", String::from("\u{1F600}"));
+    test!(String, "\"\\u00411\"",       String::from("A1"));
+    test!(String, "\"\\u00e9\\u00E9\"", String::from("éé"));
+}
+
+#[test]
+fn invalid_surrogates()
+{
+    assert_eq!(Json::parse("\"\\ud83d\""),        Err(Error::InvalidCharacter("\"".to_string())));
+    assert_eq!(Json::parse("\"\\ud83d\\n\""),     Err(Error::InvalidCharacter("n".to_string())));
+    assert_eq!(Json::parse("\"\\ude00\""),        Err(Error::InvalidCharacter("U+de00".to_string())));
+    assert_eq!(Json::parse("\"\\ud83d\\ud83d\""), Err(Error::InvalidCharacter("U+d83d".to_string())));
+}
+
 #[test]
 fn valid_array()
 {
@@ -137,3 +183,28 @@ fn valid_object()
     let json = Json::parse(original);
     assert_eq!(json, Ok(Json::Object(map, original.to_string())));
 }
+
+#[test]
+fn valid_whitespace()
+{
+    let json = Json::parse(" \n{ \"a\" : [ 1 , 2 ] ,\n\t\"b\" : null }\n ").unwrap();
+    assert_eq!(json.pointer("/a/1").unwrap().to_source(), "2");
+    assert!(json.pointer("/b").unwrap().is_null());
+}
+
+#[test]
+fn invalid_documents()
+{
+    assert_eq!(Json::parse(""), Err(Error::UnexpectedEof));
+    assert_eq!(Json::parse("nullnull"), Err(Error::InvalidCharacter("n".to_string())));
+    assert_eq!(Json::parse("[1] x"), Err(Error::InvalidCharacter("x".to_string())));
+    assert_eq!(Json::parse("[1"), Err(Error::UnexpectedEof));
+    assert_eq!(Json::parse("{\"a\": 1"), Err(Error::UnexpectedEof));
+
+    let error = Json::parse_with_position("{\n  \"a\": [1, ?]\n}").unwrap_err();
+    assert_eq!(error, ParseError { error: Error::InvalidCharacter("?".to_string()), offset: 13, line: 2, column: 12 });
+    assert_eq!(error.to_string(), "2:12: invalid character `?`");
+
+    let error = Json::parse_with_position("[1,\n").unwrap_err();
+    assert_eq!((error.error, error.line, error.column), (Error::UnexpectedEof, 2, 1));
+}
@@ -0,0 +1,110 @@
+extern crate simple_json;
+
+use simple_json::visit::{ walk_member, walk_object, Fold, Visit, VisitMut };
+use simple_json::{ Json, JsonPointer };
+
+// Every node visited, and where from.
+#[derive(Default)]
+struct Trace(Vec<String>);
+
+impl Visit for Trace
+{
+    fn visit_object(&mut self, path: &JsonPointer, json: &Json)
+    {
+        self.0.push(format!("object {}", path));
+        walk_object(self, path, json);
+    }
+
+    fn visit_member(&mut self, path: &JsonPointer, key: &str, value: &Json)
+    {
+        // Members named `skip` aren't gone into.
+        if key != "skip" {
+            walk_member(self, path, key, value);
+        }
+    }
+
+    fn visit_element(&mut self, path: &JsonPointer, index: usize, value: &Json)
+    {
+        self.0.push(format!("element {} {}", index, path));
+        self.visit_value(path, value);
+    }
+
+    fn visit_scalar(&mut self, path: &JsonPointer, json: &Json)
+    {
+        self.0.push(format!("{} {}", json, path));
+    }
+}
+
+#[test]
+fn visit()
+{
+    let json = Json::parse("{\"z\": [1, {\"a/b\": true}], \"skip\": [2], \"a\": null}").unwrap();
+
+    let mut trace = Trace::default();
+    json.visit(&mut trace);
+
+    assert_eq!(trace.0, vec![
+        "object ",
+        "element 0 /z/0",
+        "1 /z/0",
+        "element 1 /z/1",
+        "object /z/1",
+        "true /z/1/a~1b",
+        "null /a",
+    ]);
+}
+
+// Doubles every number.
+struct Double;
+
+impl VisitMut for Double
+{
+    fn visit_scalar_mut(&mut self, _path: &JsonPointer, json: &mut Json)
+    {
+        if let Some(number) = json.as_number() {
+            *json = Json::from(f64::from(number.clone()) * 2.);
+        }
+    }
+}
+
+#[test]
+fn visit_mut()
+{
+    let mut json = Json::parse("{\"b\": [1, {\"c\": 2.5}], \"a\": \"x\"}").unwrap();
+    json.visit_mut(&mut Double);
+
+    assert!(json.semantic_eq(&Json::parse("{\"b\": [2, {\"c\": 5}], \"a\": \"x\"}").unwrap()));
+    assert_eq!(json.to_source(), "{\"b\":[2,{\"c\":5}],\"a\":\"x\"}");
+}
+
+// Drops nulls and upper-cases keys.
+struct Clean;
+
+impl Fold for Clean
+{
+    fn fold_member(&mut self, path: &JsonPointer, key: String, value: Json) -> Option<(String, Json)>
+    {
+        match value.is_null() {
+            true  => None,
+            false => Some((key.to_uppercase(), self.fold_value(path, value))),
+        }
+    }
+
+    fn fold_element(&mut self, path: &JsonPointer, _index: usize, value: Json) -> Option<Json>
+    {
+        match value.is_null() {
+            true  => None,
+            false => Some(self.fold_value(path, value)),
+        }
+    }
+}
+
+#[test]
+fn fold()
+{
+    let json = Json::parse("{\"b\": [null, {\"c\": null, \"d\": 1}], \"a\": null}").unwrap();
+    let folded = json.fold(&mut Clean);
+
+    assert_eq!(folded.to_source(), "{\"B\":[{\"D\":1}]}");
+    assert_eq!(Json::parse("null").unwrap().fold(&mut Clean), Json::parse("null").unwrap());
+}
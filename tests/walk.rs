@@ -0,0 +1,86 @@
+extern crate simple_json;
+
+use simple_json::walk::{ Order, WalkOptions };
+use simple_json::{ Json, JsonPointer };
+
+fn paths<'a, I: Iterator<Item = (JsonPointer, &'a Json)>>(iter: I) -> Vec<String>
+{
+    iter.map(|(pointer, _)| pointer.to_string()).collect()
+}
+
+#[test]
+fn walk()
+{
+    let json = Json::parse("{\"b\": [1, {}], \"a\": {\"c\": null}}").unwrap();
+
+    assert_eq!(paths(json.walk()), vec!["", "/b", "/b/0", "/b/1", "/a", "/a/c"]);
+    assert_eq!(paths(json.walk_with(&WalkOptions { order: Order::Post })), vec!["/b/0", "/b/1", "/b", "/a/c", "/a", ""]);
+    assert_eq!(paths(json.leaves()), vec!["/b/0", "/b/1", "/a/c"]);
+
+    let (pointer, value) = json.walk().nth(5).unwrap();
+    assert_eq!((pointer.to_string(), value), ("/a/c".to_string(), &Json::parse("null").unwrap()));
+
+    let scalar = Json::parse("1").unwrap();
+    assert_eq!(paths(scalar.walk_with(&WalkOptions { order: Order::Post })), vec![""]);
+}
+
+#[test]
+fn walk_mut()
+{
+    let mut json = Json::parse("{\"b\": [1, 2], \"a\": \"x\"}").unwrap();
+    let mut seen = vec![];
+
+    json.walk_mut(|pointer, value| {
+        seen.push(pointer.to_string());
+
+        // The walk goes into replaced nodes.
+        if pointer.to_string() == "/b/1" {
+            *value = Json::parse("[3, 4]").unwrap();
+        } else if let Some(number) = value.as_number() {
+            *value = Json::from(f64::from(number.clone()) * 10.);
+        }
+    });
+
+    assert_eq!(seen, vec!["", "/b", "/b/0", "/b/1", "/b/1/0", "/b/1/1", "/a"]);
+    assert_eq!(json.to_source(), "{\"b\":[10,[30,40]],\"a\":\"x\"}");
+}
+
+#[test]
+fn deep_trees()
+{
+    let depth = 1000;
+    let text  = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+    let mut json = Json::parse(&text).unwrap();
+
+    assert_eq!(json.walk().count(), depth);
+    assert_eq!(json.walk_with(&WalkOptions { order: Order::Post }).next().unwrap().0.len(), depth - 1);
+    assert_eq!(json.leaves().count(), 1);
+
+    let mut count = 0;
+    json.walk_mut(|_, _| count += 1);
+    assert_eq!(count, depth);
+    assert_eq!(json.to_source(), text);
+}
+
+#[test]
+fn member_order()
+{
+    // Repeated keys come where they first appear, with the members of the last.
+    let json = Json::parse("{\"z\": {\"y\": 1, \"x\": [2]}, \"a\": 0, \"z\": {\"q\": {\"s\": 1, \"r\": 2}, \"p\": 3}}").unwrap();
+    assert_eq!(paths(json.walk()), vec!["", "/z", "/z/q", "/z/q/s", "/z/q/r", "/z/p", "/a"]);
+
+    let flattened: Vec<String> = json.flatten().into_iter().map(|(key, _)| key).collect();
+    assert_eq!(flattened, vec!["z.q.s", "z.q.r", "z.p", "a"]);
+
+    // The order is read once, not again for every object.
+    let depth = 1000;
+    let text  = format!("{}0{}", "{\"b\": 1, \"a\": ".repeat(depth), "}".repeat(depth));
+    let mut json = Json::parse(&text).unwrap();
+
+    let last = json.walk().last().unwrap().0;
+    assert_eq!(last.len(), depth);
+    assert_eq!(last.last(), Some("a"));
+
+    json.walk_mut(|_, _| {});
+    assert_eq!(json.walk().nth(1).unwrap().0.to_string(), "/b");
+}